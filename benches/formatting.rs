@@ -0,0 +1,243 @@
+use cggtts::{
+    buffer::Utf8Buffer,
+    prelude::{
+        CommonViewClass, Constellation, Duration, Epoch, Header, IonosphericData, Track, TrackData,
+        CGGTTS, SV,
+    },
+};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use std::{
+    cmp::{max as cmp_max, min as cmp_min},
+    io::BufWriter,
+};
+
+/// Synthetic tracks, covering all columns (including saturation)
+fn tracks(size: usize) -> Vec<Track> {
+    let t0 = Epoch::from_mjd_utc(60_000.0);
+
+    (0..size)
+        .map(|nth| {
+            let x = nth as f64;
+            let glonass = nth % 4 == 0;
+
+            let mut track = Track::default();
+
+            track.class = if nth % 2 == 0 {
+                CommonViewClass::SingleChannel
+            } else {
+                CommonViewClass::MultiChannel
+            };
+            track.epoch = t0 + Duration::from_seconds(960.0 * x);
+            track.duration = Duration::from_seconds(780.0);
+            track.sv = if glonass {
+                SV::new(Constellation::Glonass, (nth % 24) as u8 + 1)
+            } else {
+                SV::new(Constellation::GPS, (nth % 32) as u8 + 1)
+            };
+            track.elevation_deg = (x * 0.37) % 95.0;
+            track.azimuth_deg = (x * 1.23) % 360.0;
+            track.data = TrackData {
+                refsv: (x - 50_000.0) * 1.234E-7,
+                srsv: (x - 50_000.0) * 1.1E-15,
+                refsys: (x - 50_000.0) * 2.345E-8,
+                srsys: (x - 50_000.0) * 3.3E-16,
+                dsg: x * 1.7E-12,
+                ioe: (nth % 1000) as u16,
+                mdtr: x * 3.1E-12,
+                smdt: (x - 50_000.0) * 1.3E-17,
+                mdio: x * 1.9E-12,
+                smdi: (x - 50_000.0) * 2.1E-17,
+            };
+            track.iono = if nth % 3 == 0 {
+                Some(IonosphericData {
+                    msio: x * 1.3E-12,
+                    smsi: (x - 50_000.0) * 1.7E-17,
+                    isg: x * 2.3E-13,
+                })
+            } else {
+                None
+            };
+            track.fdma_channel = if glonass {
+                Some((nth % 24) as u8 + 1)
+            } else {
+                None
+            };
+            track.hc = (nth % 100) as u8;
+            track.frc = if glonass {
+                "L3P".parse().unwrap()
+            } else {
+                "L1C".parse().unwrap()
+            };
+
+            track
+        })
+        .collect()
+}
+
+fn fmt_saturated<T: std::cmp::Ord + std::fmt::Display>(nb: T, sat: T, padding: usize) -> String {
+    format!("{:>padding$}", std::cmp::min(nb, sat))
+}
+
+fn fmt_saturated_f64(nb: f64, scaling: f64, sat: i64, padding: usize) -> String {
+    let scaled = (nb * scaling).round() as i64;
+    if scaled.is_negative() {
+        format!(
+            "{:>padding$}",
+            cmp_max(scaled, -sat / 10),
+            padding = padding
+        )
+    } else {
+        format!("{:>padding$}", cmp_min(scaled, sat), padding = padding)
+    }
+}
+
+/// Previous (format! based) implementation, used as reference
+fn legacy_format(track: &Track, buffer: &mut Utf8Buffer) -> String {
+    buffer.clear();
+
+    buffer.push_str(&format!("{} {:X} ", track.sv, track.class));
+
+    buffer.push_str(&format!(
+        "{} ",
+        fmt_saturated_f64(track.epoch.to_mjd_utc_days().floor(), 1.0, 99999, 4)
+    ));
+
+    let (_, _, _, h, m, s, _) = track.epoch.to_gregorian_utc();
+    buffer.push_str(&format!("{:02}{:02}{:02} ", h, m, s));
+
+    buffer.push_str(&format!(
+        "{} ",
+        fmt_saturated(track.duration.to_seconds() as u64, 9999, 4)
+    ));
+
+    buffer.push_str(&format!(
+        "{} ",
+        fmt_saturated_f64(track.elevation_deg, 10.0, 999, 3)
+    ));
+
+    buffer.push_str(&format!(
+        "{} ",
+        fmt_saturated_f64(track.azimuth_deg, 10.0, 9999, 4)
+    ));
+
+    for (value, scaling, sat, padding) in [
+        (track.data.refsv, 1E10, 99_999_999_999, 11),
+        (track.data.srsv, 1E13, 999_999, 6),
+        (track.data.refsys, 1E10, 99_999_999_999, 11),
+        (track.data.srsys, 1E13, 999_999, 6),
+        (track.data.dsg, 1E10, 9_999, 4),
+    ] {
+        buffer.push_str(&format!(
+            "{} ",
+            fmt_saturated_f64(value, scaling, sat, padding)
+        ));
+    }
+
+    buffer.push_str(&format!("{} ", fmt_saturated(track.data.ioe, 999, 3)));
+
+    for (value, scaling, sat, padding) in [
+        (track.data.mdtr, 1E10, 9_999, 4),
+        (track.data.smdt, 1E13, 9_999, 4),
+        (track.data.mdio, 1E10, 9_999, 4),
+        (track.data.smdi, 1E13, 9_999, 4),
+    ] {
+        buffer.push_str(&format!(
+            "{} ",
+            fmt_saturated_f64(value, scaling, sat, padding)
+        ));
+    }
+
+    if let Some(iono) = track.iono {
+        buffer.push_str(&format!(
+            "{} {} {} ",
+            fmt_saturated_f64(iono.msio, 1E10, 9_999, 4),
+            fmt_saturated_f64(iono.smsi, 1E13, 999_999, 4),
+            fmt_saturated_f64(iono.isg, 1E10, 9_999, 3),
+        ));
+    }
+
+    if let Some(fdma) = &track.fdma_channel {
+        buffer.push_str(&format!("{:2} ", fdma));
+    } else {
+        buffer.push_str(" 0 ");
+    }
+
+    buffer.push_str(&format!(
+        "{:2} {:>frc_padding$} ",
+        track.hc,
+        track.frc,
+        frc_padding = 3
+    ));
+
+    let crc = buffer.calculate_crc();
+    buffer.push_str(&format!("{:02X}", crc));
+
+    buffer.to_utf8_ascii().unwrap().to_string()
+}
+
+fn track_formatting(c: &mut Criterion) {
+    let tracks = tracks(100_000);
+    let mut buffer = Utf8Buffer::new(256);
+
+    // both implementations must generate the same bytes, except for angles
+    // that the legacy implementation did not clamp (elevation) or wrap (azimuth)
+    for track in tracks.iter().filter(|trk| {
+        (trk.elevation_deg * 10.0).round() <= 900.0 && (trk.azimuth_deg * 10.0).round() < 3600.0
+    }) {
+        let mut writer = BufWriter::new(Vec::new());
+        track.format(&mut writer, &mut buffer).unwrap();
+
+        let content = writer.into_inner().unwrap();
+        assert_eq!(
+            String::from_utf8(content).unwrap(),
+            legacy_format(track, &mut buffer)
+        );
+    }
+
+    let mut group = c.benchmark_group("formatting");
+    group.throughput(Throughput::Elements(tracks.len() as u64));
+
+    group.bench_function("legacy/100k", |b| {
+        b.iter(|| {
+            for track in tracks.iter() {
+                let _ = black_box(legacy_format(black_box(track), &mut buffer));
+            }
+        })
+    });
+
+    group.bench_function("track/100k", |b| {
+        let mut writer = BufWriter::new(Vec::with_capacity(16 * 1024 * 1024));
+        b.iter(|| {
+            writer.get_mut().clear();
+            for track in tracks.iter() {
+                track.format(&mut writer, &mut buffer).unwrap();
+            }
+            black_box(writer.get_ref().len());
+        })
+    });
+
+    group.finish();
+}
+
+fn daily_file_formatting(c: &mut Criterion) {
+    // 1 day, 90 periods, 40 SV
+    let cggtts = CGGTTS::default()
+        .with_header(Header::default().with_station("LAB"))
+        .with_tracks(tracks(90 * 40));
+
+    let mut group = c.benchmark_group("formatting");
+    group.throughput(Throughput::Elements(cggtts.tracks.len() as u64));
+
+    group.bench_function("daily/to_vec", |b| {
+        b.iter(|| {
+            let _ = black_box(black_box(&cggtts).to_vec().unwrap());
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, track_formatting, daily_file_formatting);
+criterion_main!(benches);
@@ -0,0 +1,364 @@
+use cggtts::prelude::{
+    CarrierCode, CommonViewClass, Duration as TrackDuration, IonosphericData, Track, TrackData,
+    CGGTTS, SV,
+};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+use std::{
+    fmt::Write,
+    fs::{read_dir, write},
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use cggtts::prelude::Header;
+
+const HEADER: &str = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+REV DATE = 2023-06-27
+RCVR = GTR51 2204005 1.12.0 2022 2.0
+CH = 20
+IMS = GTR51 2204005 1.12.0 2022 2.0
+LAB = LAB
+X = +3970727.80 m
+Y = +1018888.02 m
+Z = +4870276.84 m
+FRAME = ITRF
+COMMENTS = NO COMMENTS
+INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021
+CAB DLY =  155.2 ns
+REF DLY =    0.0 ns
+REF = UTC(LAB)
+CKSUM = 37
+
+SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK
+             hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s.1ns.1ps/s.1ns
+";
+
+/// Synthetic, yet valid, Galileo track lines
+fn track_lines(size: usize) -> Vec<String> {
+    (0..size)
+        .map(|nth| {
+            format!(
+                "E{:02} FF {} {:02}{:02}00  780 {:3} {:4} {:+11} {:+6} {:+11} {:+6} {:4} {:3} {:4} {:+4} {:4} {:+4} {:4} {:+4} {:3} {:2} {:2} E1  00",
+                nth % 36 + 1,
+                50_000 + nth / 90,
+                (nth / 6) % 24,
+                (nth % 6) * 10,
+                nth % 900,
+                nth % 3600,
+                nth as i64 * 7 - 350_000,
+                nth as i64 % 999,
+                nth as i64 * 3 - 150_000,
+                nth as i64 % 99,
+                nth % 9999,
+                nth % 999,
+                nth % 9999,
+                nth as i64 % 999 - 500,
+                nth % 9999,
+                nth as i64 % 999 - 500,
+                nth % 9999,
+                nth as i64 % 999 - 500,
+                nth % 999,
+                0,
+                nth % 99,
+            )
+        })
+        .collect()
+}
+
+/// Synthetic, yet valid, BeiDou daily file:
+/// `nb_sv` tracked during each of the `nb_periods` common view periods.
+fn daily_file(nb_periods: usize, nb_sv: usize) -> String {
+    let mut content = String::from(HEADER);
+
+    for period in 0..nb_periods {
+        let minutes = 2 + period * 16;
+
+        for sv in 0..nb_sv {
+            let nth = (period * nb_sv + sv) as i64;
+            writeln!(
+                content,
+                "C{:02} FF 60258 {:02}{:02}00  780 {:3} {:4} {:+11} {:+6} {:+11} {:+6} {:4} {:3} {:4} {:+4} {:4} {:+4}  0  0 B1i 00",
+                sv + 1,
+                minutes / 60,
+                minutes % 60,
+                nth % 900,
+                nth % 3600,
+                nth * 7 - 350_000,
+                nth % 999,
+                nth * 3 - 150_000,
+                nth % 99,
+                nth % 9999,
+                nth % 999,
+                nth % 9999,
+                nth % 999 - 500,
+                nth % 9999,
+                nth % 999 - 500,
+            )
+            .unwrap();
+        }
+    }
+
+    content
+}
+
+/// Largest readable file of the data/CGGTTS directory,
+/// or a synthetic daily file when the sample data is not available.
+fn sample_daily_file() -> PathBuf {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data/CGGTTS");
+
+    let largest = read_dir(&dir).ok().and_then(|entries| {
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && CGGTTS::from_file(path).is_ok())
+            .max_by_key(|path| path.metadata().map(|meta| meta.len()).unwrap_or(0))
+    });
+
+    largest.unwrap_or_else(|| {
+        let path = std::env::temp_dir().join("cggtts-bench-daily.cggtts");
+        write(&path, daily_file(90, 40)).unwrap();
+        path
+    })
+}
+
+/// Previous implementation: one [String] per line, default capacity.
+fn legacy_parse<R: Read>(reader: &mut BufReader<R>) -> Vec<Track> {
+    let _ = Header::parse(reader).unwrap();
+
+    let mut tracks = Vec::with_capacity(16);
+
+    for line in reader.lines() {
+        let line = line.unwrap();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Ok(track) = Track::from_str(&line) {
+            tracks.push(track);
+        }
+    }
+
+    tracks
+}
+
+/// Previous implementation: one [String] per parsed field
+/// (the error is built eagerly, whether the field is missing or not).
+fn legacy_field<T: FromStr>(
+    items: &mut std::str::SplitAsciiWhitespace<'_>,
+    name: &str,
+) -> Result<T, String> {
+    items
+        .next()
+        .ok_or(String::from(name))?
+        .parse::<T>()
+        .map_err(|_| String::from(name))
+}
+
+/// Previous [Track] parser, used as reference
+fn legacy_from_str(line: &str) -> Result<Track, String> {
+    if !line.is_ascii() {
+        return Err(String::from("invalid format"));
+    }
+
+    let cleanedup = String::from(line.trim());
+    let mut items = cleanedup.split_ascii_whitespace();
+
+    let nb_items = items.clone().count();
+
+    let sv = legacy_field::<SV>(&mut items, "SV")?;
+    let class = legacy_field::<CommonViewClass>(&mut items, "CL")?;
+    let mjd = legacy_field::<i32>(&mut items, "MJD")?;
+
+    let trk_sttime = items.next().ok_or(String::from("STTIME"))?;
+
+    let mut sttime = [0u8; 3];
+    for (nth, value) in sttime.iter_mut().enumerate() {
+        *value = trk_sttime
+            .get(nth * 2..nth * 2 + 2)
+            .ok_or(String::from("STTIME"))?
+            .parse::<u8>()
+            .map_err(|_| String::from("STTIME"))?;
+    }
+
+    let epoch = Track::epoch_from_mjd_sttime(mjd as u32, sttime[0], sttime[1], sttime[2]);
+
+    let duration = TrackDuration::from_seconds(legacy_field::<f64>(&mut items, "TRKL")?);
+    let elevation_deg = legacy_field::<f64>(&mut items, "ELV")? * 0.1;
+    let azimuth_deg = legacy_field::<f64>(&mut items, "AZTH")? * 0.1;
+
+    let data = TrackData {
+        refsv: legacy_field::<f64>(&mut items, "REFSV")? * 1E-10,
+        srsv: legacy_field::<f64>(&mut items, "SRSV")? * 1E-13,
+        refsys: legacy_field::<f64>(&mut items, "REFSYS")? * 1E-10,
+        srsys: legacy_field::<f64>(&mut items, "SRSYS")? * 1E-13,
+        dsg: legacy_field::<f64>(&mut items, "DSG")? * 1E-10,
+        ioe: legacy_field::<u16>(&mut items, "IOE")?,
+        mdtr: legacy_field::<f64>(&mut items, "MDTR")? * 1E-10,
+        smdt: legacy_field::<f64>(&mut items, "SMDT")? * 1E-13,
+        mdio: legacy_field::<f64>(&mut items, "MDIO")? * 1E-10,
+        smdi: legacy_field::<f64>(&mut items, "SMDI")? * 1E-13,
+    };
+
+    let iono = match nb_items {
+        24 => Some(IonosphericData {
+            msio: legacy_field::<f64>(&mut items, "MSIO")? * 0.1E-9,
+            smsi: legacy_field::<f64>(&mut items, "SMSI")? * 0.1E-12,
+            isg: legacy_field::<f64>(&mut items, "ISG")? * 0.1E-9,
+        }),
+        21 => None,
+        _ => return Err(String::from("invalid format")),
+    };
+
+    let fr = legacy_field::<u8>(&mut items, "fr")?;
+    let hc = legacy_field::<u8>(&mut items, "hc")?;
+    let frc = legacy_field::<String>(&mut items, "frc")?;
+    let ck = items.next().ok_or(String::from("ck"))?;
+
+    let mut track = Track::default();
+    track.sv = sv;
+    track.class = class;
+    track.epoch = epoch;
+    track.duration = duration;
+    track.elevation_deg = elevation_deg;
+    track.azimuth_deg = azimuth_deg;
+    track.data = data;
+    track.iono = iono;
+    track.hc = hc;
+    track.frc = frc
+        .parse::<CarrierCode>()
+        .map_err(|_| String::from("frc"))?;
+    track.fdma_channel = if fr == 0 { None } else { Some(fr) };
+    track.ck = Some(u8::from_str_radix(ck, 16).map_err(|_| String::from("ck"))?);
+
+    Ok(track)
+}
+
+/// Best execution time, out of `runs`
+fn best_of<F: FnMut()>(runs: usize, mut f: F) -> Duration {
+    (0..runs)
+        .map(|_| {
+            let t = Instant::now();
+            f();
+            t.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn daily_file_parsing(c: &mut Criterion) {
+    // 1 day, 90 periods, 40 SV
+    let content = daily_file(90, 40);
+    let capacity = 90 * 40;
+
+    // identical output
+    let legacy = legacy_parse(&mut BufReader::new(content.as_bytes()));
+    let cggtts =
+        CGGTTS::parse_with_capacity(&mut BufReader::new(content.as_bytes()), capacity).unwrap();
+
+    assert_eq!(cggtts.tracks.len(), capacity);
+    assert_eq!(cggtts.tracks, legacy);
+
+    // measurable improvement
+    let legacy = best_of(20, || {
+        let _ = black_box(legacy_parse(&mut BufReader::new(content.as_bytes())));
+    });
+
+    let new = best_of(20, || {
+        let _ = black_box(
+            CGGTTS::parse_with_capacity(&mut BufReader::new(content.as_bytes()), capacity).unwrap(),
+        );
+    });
+
+    assert!(
+        new < legacy,
+        "no improvement: {:?} (legacy: {:?})",
+        new,
+        legacy
+    );
+
+    let mut group = c.benchmark_group("parsing");
+    group.throughput(Throughput::Elements(capacity as u64));
+
+    group.bench_function("daily/legacy", |b| {
+        b.iter(|| {
+            let _ = black_box(legacy_parse(&mut BufReader::new(content.as_bytes())));
+        })
+    });
+
+    group.bench_function("daily/cggtts", |b| {
+        b.iter(|| {
+            let mut reader = BufReader::new(content.as_bytes());
+            let _ = black_box(CGGTTS::parse_with_capacity(&mut reader, capacity).unwrap());
+        })
+    });
+
+    group.finish();
+}
+
+fn track_parsing(c: &mut Criterion) {
+    let lines = track_lines(100_000);
+
+    // identical output
+    for line in lines.iter() {
+        assert_eq!(
+            Track::from_str(line).unwrap(),
+            legacy_from_str(line).unwrap(),
+            "failed for \"{}\"",
+            line
+        );
+    }
+
+    let mut group = c.benchmark_group("parsing");
+    group.throughput(Throughput::Elements(lines.len() as u64));
+
+    group.bench_function("legacy/100k", |b| {
+        b.iter(|| {
+            for line in lines.iter() {
+                let _ = black_box(legacy_from_str(black_box(line)).unwrap());
+            }
+        })
+    });
+
+    group.bench_function("track/100k", |b| {
+        b.iter(|| {
+            for line in lines.iter() {
+                let _ = black_box(Track::from_str(black_box(line)).unwrap());
+            }
+        })
+    });
+
+    let mut content = String::from(HEADER);
+    for line in lines.iter() {
+        writeln!(content, "{}", line).unwrap();
+    }
+
+    group.bench_function("cggtts/100k", |b| {
+        b.iter(|| {
+            let mut reader = BufReader::new(content.as_bytes());
+            let _ = black_box(CGGTTS::parse(&mut reader).unwrap());
+        })
+    });
+
+    group.finish();
+}
+
+fn file_parsing(c: &mut Criterion) {
+    let path = sample_daily_file();
+    let size = path.metadata().unwrap().len();
+
+    let mut group = c.benchmark_group("parsing");
+    group.throughput(Throughput::Bytes(size));
+
+    group.bench_function("daily/from_file", |b| {
+        b.iter(|| {
+            let _ = black_box(CGGTTS::from_file(black_box(&path)).unwrap());
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, track_parsing, daily_file_parsing, file_parsing);
+criterion_main!(benches);
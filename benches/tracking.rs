@@ -0,0 +1,45 @@
+use cggtts::prelude::{Constellation, Duration, Epoch, Observation, SVTracker, SV};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+/// [SVTracker] loaded with a complete common view period,
+/// sampled every 30s: 27 [Observation]s over 13'.
+fn loaded_tracker() -> SVTracker {
+    let t0 = Epoch::from_mjd_utc(60_000.0);
+    let mut tracker = SVTracker::new(SV::new(Constellation::GPS, 1));
+
+    for nth in 0..27 {
+        let x = nth as f64;
+        tracker.new_observation(
+            Observation::new(t0 + Duration::from_seconds(30.0 * x))
+                .with_refsv(1.0E-6 + x * 1.0E-11)
+                .with_refsys(2.0E-7 + x * 2.0E-12)
+                .with_mdtr(1.0E-8 + x * 1.0E-12)
+                .with_mdio(5.0E-9 + x * 5.0E-13)
+                .with_msio(5.0E-9 + x * 4.0E-13)
+                .with_elevation(45.0 + x * 0.1)
+                .with_azimuth(120.0 + x * 0.2),
+        );
+    }
+
+    tracker
+}
+
+fn tracker_fit(c: &mut Criterion) {
+    let tracker = loaded_tracker();
+    assert!(tracker.clone().fit().is_ok());
+
+    let mut group = c.benchmark_group("tracking");
+
+    group.bench_function("fit/27", |b| {
+        b.iter_batched(
+            || tracker.clone(),
+            |mut tracker| black_box(tracker.fit().unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, tracker_fit);
+criterion_main!(benches);
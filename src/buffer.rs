@@ -1,58 +1,154 @@
+//! Utf-8 buffering
+use crate::crc::Crc8;
+use std::io::Write;
 use std::str::{from_utf8, Utf8Error};
 
+/// [Utf8Buffer] is the intermediate buffer used when formatting CGGTTS
+/// content. It allows calculating the [Crc8] of the content, prior
+/// forwarding it to the user.
+///
+/// The buffer is not bounded: it is pre-allocated with [Utf8Buffer::new]
+/// and grows like any [Vec] when that capacity is exceeded.
+/// [Utf8Buffer::clear] discards the content but preserves the allocation,
+/// so one buffer may be reused for an entire file (or many files).
+///
+/// [Utf8Buffer] implements [Write] so it can also be used as a sink,
+/// for example to format a complete file in memory:
+/// ```
+/// use cggtts::prelude::CGGTTS;
+/// use cggtts::buffer::Utf8Buffer;
+/// use std::io::BufWriter;
+///
+/// let cggtts = CGGTTS::default();
+///
+/// let mut writer = BufWriter::new(Utf8Buffer::new(1024));
+/// cggtts.format(&mut writer).unwrap();
+///
+/// let buffer = writer.into_inner().unwrap_or_else(|_| panic!("flush error"));
+/// let content = buffer.to_utf8_ascii().unwrap();
+/// assert!(content.starts_with("CGGTTS     GENERIC DATA FORMAT VERSION = 2E"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
 pub struct Utf8Buffer {
     inner: Vec<u8>,
 }
 
-#[cfg(test)]
-use std::io::Write;
-
-/// [Write] implementation is only used when testing.
-#[cfg(test)]
+/// [Write] appends the bytes to the internal content.
+/// They are not interpreted until [Utf8Buffer::to_utf8_ascii] is invoked.
 impl Write for Utf8Buffer {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        for b in buf {
-            self.inner.push(*b);
-        }
+        self.inner.extend_from_slice(buf);
         Ok(buf.len())
     }
 
+    /// Content is kept in memory: this has no effect.
     fn flush(&mut self) -> std::io::Result<()> {
-        self.inner.clear();
         Ok(())
     }
 }
 
 impl Utf8Buffer {
-    /// Allocated new [Utf8Buffer].
-    pub fn new(size: usize) -> Self {
+    /// Allocates a new [Utf8Buffer] with desired capacity (in bytes).
+    pub fn new(capacity: usize) -> Self {
         Self {
-            inner: Vec::with_capacity(size),
+            inner: Vec::with_capacity(capacity),
         }
     }
 
-    /// Clear (discard) internal content
+    /// Clear (discard) internal content. The allocation is preserved.
     pub fn clear(&mut self) {
         self.inner.clear();
     }
 
+    /// Returns the number of buffered bytes.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if this [Utf8Buffer] is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns current capacity (in bytes).
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Releases memory that exceeds the current content, or `capacity`,
+    /// whichever is larger. Useful after buffering exceptionally large content.
+    pub fn shrink_to(&mut self, capacity: usize) {
+        self.inner.shrink_to(capacity);
+    }
+
     /// Pushes "content" (valid Utf-8) into internal buffer.
+    /// The buffer grows if its capacity is exceeded.
     pub fn push_str(&mut self, content: &str) {
-        let bytes = content.as_bytes();
-        self.inner.extend_from_slice(&bytes);
+        self.inner.extend_from_slice(content.as_bytes());
     }
 
-    pub fn calculate_crc(&self) -> u8 {
-        let mut crc = 0u8;
-        for byte in self.inner.iter() {
-            if *byte != b'\n' && *byte != b'\r' {
-                crc = crc.wrapping_add(*byte);
-            }
+    /// Pushes `count` times the `fill` (ASCII) byte.
+    fn push_fill(&mut self, fill: u8, count: usize) {
+        self.inner.resize(self.inner.len() + count, fill);
+    }
+
+    /// Pushes integer `value`, right aligned on `width` characters.
+    /// This is equivalent to `{:>width$}` formatting, without its overhead.
+    pub fn push_integer<I: itoa::Integer>(&mut self, value: I, width: usize) {
+        let mut digits = itoa::Buffer::new();
+        let digits = digits.format(value);
+        self.push_fill(b' ', width.saturating_sub(digits.len()));
+        self.push_str(digits);
+    }
+
+    /// Pushes integer `value`, zero padded on `width` digits.
+    /// This is equivalent to `{:0width$}` formatting, without its overhead.
+    pub fn push_zero_padded(&mut self, value: u64, width: usize) {
+        let mut digits = itoa::Buffer::new();
+        let digits = digits.format(value);
+        self.push_fill(b'0', width.saturating_sub(digits.len()));
+        self.push_str(digits);
+    }
+
+    /// Pushes `value` with a single decimal, on `width` characters,
+    /// either right aligned (`{:width$.1}`) or zero padded (`{:0width$.1}`).
+    /// The output is identical to the standard formatter, which is only
+    /// used when the rounding is not trivial (close to a tie, negative or
+    /// very large values).
+    pub fn push_tenths(&mut self, value: f64, width: usize, zero_padded: bool) {
+        let scaled = value * 10.0;
+        let rounded = scaled.round();
+
+        // Away from a tie, rounding the (inexact) product is
+        // identical to rounding the exact decimal value.
+        if value.is_sign_positive() && rounded < 1.0E14 && (scaled - rounded).abs() < 0.4 {
+            let rounded = rounded as u64;
+            let mut digits = itoa::Buffer::new();
+            let units = digits.format(rounded / 10);
+
+            let fill = if zero_padded { b'0' } else { b' ' };
+            self.push_fill(fill, width.saturating_sub(units.len() + 2));
+
+            self.push_str(units);
+            self.inner.push(b'.');
+            self.inner.push(b'0' + (rounded % 10) as u8);
+        } else if zero_padded {
+            self.push_str(&format!("{:0width$.1}", value, width = width));
+        } else {
+            self.push_str(&format!("{:width$.1}", value, width = width));
         }
-        crc
     }
 
-    pub fn to_utf8_ascii<'a>(&'a self) -> Result<&'a str, Utf8Error> {
+    /// Calculates the [Crc8] of the internal content.
+    pub fn calculate_crc(&self) -> u8 {
+        let mut crc = Crc8::new();
+        crc.update(&self.inner);
+        crc.finalize()
+    }
+
+    /// Interprets internal content as Utf-8 (ASCII) string.
+    /// Fails if invalid bytes were written into this [Utf8Buffer].
+    pub fn to_utf8_ascii(&self) -> Result<&str, Utf8Error> {
         from_utf8(&self.inner[..])
     }
 }
@@ -60,6 +156,7 @@ impl Utf8Buffer {
 #[cfg(test)]
 mod test {
     use super::Utf8Buffer;
+    use std::io::Write;
 
     #[test]
     fn test_crc_tracks_buffering() {
@@ -120,4 +217,141 @@ CKSUM = ";
         buf.push_str(&content);
         assert_eq!(buf.calculate_crc(), 0x07);
     }
+
+    #[test]
+    fn numeric_formatting() {
+        let mut buf = Utf8Buffer::new(64);
+
+        for (value, width) in [
+            (0i64, 4),
+            (76, 3),
+            (-302, 11),
+            (9_999_999_999, 11),
+            (-9_999_999_999, 11),
+            (123_456, 4),
+            (i64::MIN, 0),
+        ] {
+            buf.clear();
+            buf.push_integer(value, width);
+            assert_eq!(
+                buf.to_utf8_ascii().unwrap(),
+                format!("{:>width$}", value, width = width)
+            );
+        }
+
+        for (value, width) in [(0u64, 2), (7, 2), (59, 2), (123, 2), (5, 4)] {
+            buf.clear();
+            buf.push_zero_padded(value, width);
+            assert_eq!(
+                buf.to_utf8_ascii().unwrap(),
+                format!("{:0width$}", value, width = width)
+            );
+        }
+
+        let mut values = vec![
+            0.0,
+            -0.0,
+            0.05,
+            0.15,
+            0.25,
+            0.35,
+            0.45,
+            2.5,
+            34.6,
+            155.2,
+            -1.5,
+            -0.04,
+            1.0E14,
+            1.0E20,
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+
+        for nth in 0..100_000 {
+            values.push(nth as f64 * 0.01);
+            values.push(nth as f64 * 0.037 - 1000.0);
+        }
+
+        for value in values {
+            for width in [0, 5, 6] {
+                buf.clear();
+                buf.push_tenths(value, width, false);
+                assert_eq!(
+                    buf.to_utf8_ascii().unwrap(),
+                    format!("{:width$.1}", value, width = width)
+                );
+
+                buf.clear();
+                buf.push_tenths(value, width, true);
+                assert_eq!(
+                    buf.to_utf8_ascii().unwrap(),
+                    format!("{:0width$.1}", value, width = width)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn large_content() {
+        let line = "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5\n";
+
+        let mut buf = Utf8Buffer::new(16);
+
+        for _ in 0..10_000 {
+            buf.push_str(line);
+        }
+
+        assert_eq!(buf.len(), 10_000 * line.len());
+        assert!(buf.capacity() >= buf.len());
+
+        let content = buf.to_utf8_ascii().unwrap();
+        assert_eq!(content.lines().count(), 10_000);
+        assert!(content.lines().all(|l| l == line.trim_end()));
+
+        // allocation is preserved
+        let capacity = buf.capacity();
+        buf.clear();
+        assert!(buf.is_empty());
+        assert_eq!(buf.capacity(), capacity);
+
+        buf.shrink_to(128);
+        assert!(buf.capacity() < capacity);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        let mut buf = Utf8Buffer::new(16);
+
+        buf.write_all(b"CKSUM = ").unwrap();
+        buf.write_all(&[0xff, 0xfe]).unwrap();
+        assert!(buf.to_utf8_ascii().is_err());
+
+        buf.clear();
+        buf.write_all(b"CKSUM = ").unwrap();
+        assert_eq!(buf.to_utf8_ascii(), Ok("CKSUM = "));
+    }
+
+    #[test]
+    fn crc_stability() {
+        let content = "R24 FF 57000 000600  780 347 394 +1186342 +0 163 +0 40 2 141 +22 23 -1 23 -1 29 +2 0 L3P";
+
+        let mut buf = Utf8Buffer::new(8);
+        buf.push_str(content);
+        let crc = buf.calculate_crc();
+
+        // reused buffer
+        buf.clear();
+        buf.push_str(content);
+        assert_eq!(buf.calculate_crc(), crc);
+
+        // any line termination
+        buf.push_str("\r\n");
+        assert_eq!(buf.calculate_crc(), crc);
+
+        // Write and push_str are equivalent
+        let mut other = Utf8Buffer::new(8);
+        writeln!(other, "{}", content).unwrap();
+        assert_eq!(other.calculate_crc(), crc);
+    }
 }
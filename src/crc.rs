@@ -1,44 +1,277 @@
-use thiserror::Error;
-
-#[derive(PartialEq, Debug, Error)]
-pub enum Error {
-    #[error("can only calculate over valid utf8 data")]
-    NonUtf8Data,
-    #[error("checksum error, got \"{0}\" but \"{1}\" locally computed")]
-    ChecksumError(u8, u8),
+//! CGGTTS checksum calculation.
+//!
+//! CGGTTS uses a simple 8 bit checksum: the modulo 256 sum of all bytes
+//! that are covered. Covered bytes are:
+//! - for the header section: every byte of every line, starting from the
+//!   VERSION line, up to and including the `CKSUM = ` pattern.
+//!   The checksum value itself is excluded. COMMENTS lines are not
+//!   special and contribute like any other line.
+//! - for a track: every byte of the line, up to and including the space that
+//!   precedes the CK column. The CK value itself is excluded.
+//!
+//! In both cases, carriage return (`\r`) and line feed (`\n`) bytes
+//! are ignored, so the checksum does not depend on line terminations.
+
+use crate::track::{line_checksum, Track};
+
+use std::io::{BufRead, BufReader, Read, Result, Write};
+
+/// [Crc8] is the incremental CGGTTS checksum calculator.
+/// ```
+/// use cggtts::crc::Crc8;
+///
+/// let mut crc = Crc8::new();
+/// crc.update(b"CGGTTS     GENERIC DATA FORMAT VERSION = 2E\n");
+/// crc.update(b"REV DATE = 2023-06-27\r\n");
+///
+/// // CR/LF do not contribute
+/// let mut other = Crc8::new();
+/// other.update(b"CGGTTS     GENERIC DATA FORMAT VERSION = 2E");
+/// other.update(b"REV DATE = 2023-06-27");
+///
+/// assert_eq!(crc.finalize(), other.finalize());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Crc8 {
+    value: u8,
 }
 
-/// computes crc for given str content
-pub(crate) fn calc_crc(content: &str) -> Result<u8, Error> {
-    match content.is_ascii() {
-        true => {
-            let mut ck: u8 = 0;
-            let mut ptr = content.encode_utf16();
-            for _ in 0..ptr.clone().count() {
-                ck = ck.wrapping_add(ptr.next().unwrap() as u8)
+impl Crc8 {
+    /// Creates a new [Crc8] calculator.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates this [Crc8] with new bytes.
+    /// `\r` and `\n` bytes are ignored.
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes.iter() {
+            if *byte != b'\r' && *byte != b'\n' {
+                self.value = self.value.wrapping_add(*byte);
             }
-            Ok(ck)
-        },
-        false => Err(Error::NonUtf8Data),
+        }
+    }
+
+    /// Returns the checksum value.
+    pub fn finalize(self) -> u8 {
+        self.value
     }
 }
 
+/// Repairs all checksums of a CGGTTS file, typically after a manual correction.
+/// The header CKSUM and the CK of every line that parses as a [Track] are
+/// re-derived from current content. Every other line (labels, day separators,
+/// footers..) and all line terminations are copied untouched.
+/// This will not verify the file is valid.
+/// ```
+/// use cggtts::crc::repair;
+/// use std::io::BufReader;
+///
+/// let content = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+/// REV DATE = 2023-06-27
+/// CKSUM = 00
+/// ";
+///
+/// let mut repaired = Vec::new();
+/// repair(BufReader::new(content.as_bytes()), &mut repaired).unwrap();
+///
+/// assert!(String::from_utf8(repaired).unwrap().ends_with("CKSUM = 5E\n"));
+/// ```
+pub fn repair<R: Read, W: Write>(reader: BufReader<R>, writer: &mut W) -> Result<()> {
+    const CKSUM_PATTERN: &str = "CKSUM = ";
+
+    let mut reader = reader;
+    let mut line = String::with_capacity(256);
+
+    let mut header_crc = Crc8::new();
+    let mut header = true;
+
+    loop {
+        line.clear();
+
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let content = line.trim_end_matches(['\r', '\n']);
+        let termination = &line[content.len()..];
+
+        if header {
+            // whatever the keyword case, which is preserved
+            let pattern = content
+                .get(..CKSUM_PATTERN.len())
+                .filter(|pattern| pattern.eq_ignore_ascii_case(CKSUM_PATTERN));
+
+            if let Some(pattern) = pattern {
+                header_crc.update(pattern.as_bytes());
+                write!(
+                    writer,
+                    "{}{:02X}{}",
+                    pattern,
+                    header_crc.finalize(),
+                    termination
+                )?;
+                header = false;
+            } else {
+                header_crc.update(content.as_bytes());
+                writer.write_all(line.as_bytes())?;
+            }
+        } else if content.parse::<Track>().is_ok() {
+            // CK is the last field, whatever its width
+            let trimmed = content.trim_end();
+            let track = trimmed.trim_end_matches(|c: char| !c.is_ascii_whitespace());
+            write!(
+                writer,
+                "{}{:02X}{}{}",
+                track,
+                line_checksum(track),
+                &content[trimmed.len()..],
+                termination
+            )?;
+        } else {
+            writer.write_all(line.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
-    use crate::crc::calc_crc;
+    use super::{repair, Crc8};
+    use crate::prelude::{Epoch, CGGTTS};
+    use crate::testing::fixture_cggtts;
+    use std::io::{BufReader, BufWriter};
+
+    #[test]
+    fn crc8() {
+        for (content, expected) in [
+            ("R24 FF 57000 000600  780 347 394 +1186342 +0 163 +0 40 2 141 +22 23 -1 23 -1 29 +2 0 L3P", 0x0F),
+            ("G99 99 59509 002200 0780 099 0099 +9999999999 +99999 +9999989831   -724    35 999 9999 +999 9999 +999 00 00 L1C", 0x71),
+        ] {
+            let mut crc = Crc8::new();
+            crc.update(content.as_bytes());
+            assert_eq!(crc.finalize(), expected, "failed for \"{}\"", content);
+
+            // incremental
+            let (a, b) = content.split_at(10);
+            let mut crc = Crc8::new();
+            crc.update(a.as_bytes());
+            crc.update(b"\r\n");
+            crc.update(b.as_bytes());
+            assert_eq!(crc.finalize(), expected, "failed for \"{}\"", content);
+        }
+    }
+
     #[test]
-    fn test_crc() {
-        let content = ["R24 FF 57000 000600  780 347 394 +1186342 +0 163 +0 40 2 141 +22 23 -1 23 -1 29 +2 0 L3P",
-            "G99 99 59509 002200 0780 099 0099 +9999999999 +99999 +9999989831   -724    35 999 9999 +999 9999 +999 00 00 L1C"];
-        let expected = [0x0F, 0x71];
-        for i in 0..content.len() {
-            let ck = calc_crc(content[i]).unwrap();
-            let expect = expected[i];
-            assert_eq!(
-                ck, expect,
-                "Failed for \"{}\", expect \"{}\" but \"{}\" locally computed",
-                content[i], expect, ck
-            )
+    fn checksum_repair() {
+        let mut cggtts = fixture_cggtts(2);
+
+        for (nth, track) in cggtts.tracks.iter_mut().enumerate() {
+            track.epoch = Epoch::from_mjd_utc(60258.0 + nth as f64 / 10.0);
+        }
+
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            cggtts.format(&mut writer).unwrap();
+        }
+
+        let content = String::from_utf8(content).unwrap().replace('\n', "\r\n");
+
+        // corrupt all checksums
+        let mut corrupted = String::new();
+        for line in content.split_inclusive("\r\n") {
+            if line.starts_with("CKSUM = ") {
+                corrupted.push_str("CKSUM = 00\r\n");
+            } else if line.starts_with("E0") {
+                corrupted.push_str(&line[..line.len() - 4]);
+                corrupted.push_str("00\r\n");
+            } else {
+                corrupted.push_str(line);
+            }
         }
+
+        assert_ne!(corrupted, content);
+        assert!(CGGTTS::parse(&mut BufReader::new(corrupted.as_bytes())).is_err());
+
+        let mut repaired = Vec::new();
+        repair(BufReader::new(corrupted.as_bytes()), &mut repaired).unwrap();
+
+        let repaired = String::from_utf8(repaired).unwrap();
+        assert_eq!(repaired, content);
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(repaired.as_bytes())).unwrap();
+        assert_eq!(parsed.tracks, cggtts.tracks);
+
+        // hand corrected field
+        let corrected = content
+            .split_inclusive("\r\n")
+            .map(|line| {
+                if line.starts_with("CAB DLY") {
+                    "CAB DLY =   12.5 ns\r\n"
+                } else {
+                    line
+                }
+            })
+            .collect::<String>();
+
+        assert!(CGGTTS::parse(&mut BufReader::new(corrected.as_bytes())).is_err());
+
+        let mut repaired = Vec::new();
+        repair(BufReader::new(corrected.as_bytes()), &mut repaired).unwrap();
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(repaired.as_slice())).unwrap();
+        assert_eq!(parsed.header.delay.antenna_cable_delay, 12.5);
+        assert_eq!(parsed.tracks, cggtts.tracks);
+    }
+
+    #[test]
+    fn checksum_repair_preserves_other_lines() {
+        let cggtts = fixture_cggtts(3);
+
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            cggtts.format(&mut writer).unwrap();
+        }
+
+        // units line is missing, day separator and footer lines
+        let content = String::from_utf8(content)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.contains("hhmmss"))
+            .flat_map(|line| {
+                if line.contains(" 002600 ") {
+                    vec!["# MJD 60258", line]
+                } else {
+                    vec![line]
+                }
+            })
+            .chain(["END OF FILE", "# 12"])
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+
+        let corrupted = content
+            .lines()
+            .map(|line| {
+                if line.starts_with("E0") {
+                    format!("{}00\n", &line[..line.len() - 2])
+                } else {
+                    format!("{}\n", line)
+                }
+            })
+            .collect::<String>();
+
+        assert_ne!(corrupted, content);
+
+        let mut repaired = Vec::new();
+        repair(BufReader::new(corrupted.as_bytes()), &mut repaired).unwrap();
+
+        let repaired = String::from_utf8(repaired).unwrap();
+        assert_eq!(repaired, content);
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(repaired.as_bytes())).unwrap();
+        assert_eq!(parsed.tracks, cggtts.tracks);
     }
 }
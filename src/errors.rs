@@ -3,6 +3,9 @@ use thiserror::Error;
 
 use crate::track::Error as TrackError;
 
+#[cfg(feature = "scheduler")]
+use crate::prelude::{Epoch, SV};
+
 /// Errors related to CRC parsing
 /// and calculations specifically.
 #[derive(PartialEq, Debug, Error)]
@@ -16,6 +19,8 @@ pub enum CrcError {
 /// Errors strictly related to file parsing.
 #[derive(Debug, Error)]
 pub enum ParsingError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
     #[error("only revision 2E is supported")]
     VersionMismatch,
     #[error("invalid version")]
@@ -30,6 +35,8 @@ pub enum ParsingError {
     ChannelNumber,
     #[error("non supported file revision")]
     NonSupportedRevision,
+    #[error("revision {0} is newer than 2E and not supported yet")]
+    NewerVersionUnsupported(String),
     #[error("delay calibration format")]
     CalibrationFormat,
     #[error("mixing constellations is not allowed in CGGTTS")]
@@ -48,12 +55,16 @@ pub enum ParsingError {
     ChecksumValue,
     #[error("missing crc field")]
     CrcMissing,
-    #[error("track parsing error")]
+    #[error("track parsing error: {0}")]
     TrackParsing(#[from] TrackError),
     #[error("antenna cable delay")]
     AntennaCableDelay,
     #[error("local ref delay")]
     LocalRefDelay,
+    #[error("merge error: {0}")]
+    Merge(#[from] MergeError),
+    #[error("file does not contain any track")]
+    EmptyFile,
 }
 
 /// Errors strictly related to CGGTTS formatting
@@ -63,4 +74,37 @@ pub enum FormattingError {
     Utf8(#[from] std::str::Utf8Error),
     #[error("i/o error: {0}")]
     Stdio(#[from] std::io::Error),
+    #[error("invalid compression level {0}, should be within 0..=9")]
+    InvalidCompressionLevel(u32),
+}
+
+/// Errors related to [crate::prelude::CGGTTS] merging
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum MergeError {
+    #[error("can only merge data from the same station")]
+    StationMismatch,
+    #[error("can only merge data expressed in the same reference time")]
+    ReferenceTimeMismatch,
+    #[error("mixing constellations is not allowed in CGGTTS")]
+    MixedConstellation,
+    #[error("can only append a day described by an identical header")]
+    HeaderMismatch,
+}
+
+/// Errors raised when a [crate::prelude::CGGTTS] builder is used
+/// in a state that would silently corrupt its content.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum StateError {
+    #[error("reference time cannot be modified once tracks exist")]
+    TracksPresent,
+}
+
+/// Errors related to aligning [crate::prelude::Track]s to a
+/// [crate::prelude::CommonViewCalendar]
+#[cfg(feature = "scheduler")]
+#[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CalendarError {
+    #[error("{0} would have two tracks starting at {1}")]
+    TrackCollision(SV, Epoch),
 }
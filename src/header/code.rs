@@ -33,6 +33,30 @@ impl std::fmt::Display for Code {
     }
 }
 
+impl Code {
+    /// Returns the [Code] matching this carrier frequency code (FRC),
+    /// as found in the tracks. Both the CGGTTS ("L1C", "L1P", "E5a"..)
+    /// and legacy ("C1", "P1"..) notations are supported.
+    pub fn from_frc(frc: &str) -> Option<Self> {
+        match frc.trim() {
+            "C1" | "L1C" => Some(Self::C1),
+            "P1" | "L1P" => Some(Self::P1),
+            "C2" | "L2C" => Some(Self::C2),
+            "P2" | "L2P" => Some(Self::P2),
+            "E1" => Some(Self::E1),
+            "E5" | "E5a" | "E5b" => Some(Self::E5),
+            "B1" | "B1i" | "B1I" | "B1C" => Some(Self::B1),
+            "B2" | "B2i" | "B2I" | "B2a" | "B2b" => Some(Self::B2),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this [Code] matches said carrier frequency code (FRC).
+    pub fn matches_frc(&self, frc: &str) -> bool {
+        Self::from_frc(frc) == Some(*self)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -46,4 +70,23 @@ mod test {
         assert_eq!(Code::from_str("P2").unwrap(), Code::P2);
         assert_eq!(Code::from_str("E5").unwrap(), Code::E5);
     }
+
+    #[test]
+    fn frc_equivalence() {
+        for (frc, code) in [
+            ("L1C", Some(Code::C1)),
+            ("C1", Some(Code::C1)),
+            ("L1P", Some(Code::P1)),
+            ("L2P", Some(Code::P2)),
+            ("E1", Some(Code::E1)),
+            ("E5a", Some(Code::E5)),
+            ("B1i", Some(Code::B1)),
+            ("L3P", None),
+        ] {
+            assert_eq!(Code::from_frc(frc), code, "failed for {}", frc);
+        }
+
+        assert!(Code::C1.matches_frc("L1C"));
+        assert!(!Code::C1.matches_frc("E1"));
+    }
 }
@@ -1,50 +1,93 @@
 use crate::{errors::ParsingError, header::Code};
 
+use smallvec::SmallVec;
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 #[cfg(docsrs)]
 use crate::prelude::CGGTTS;
 
+/// Maximal number of frequency dependent delays stored inline,
+/// without heap allocation. Files rarely describe more carriers.
+pub const MAX_INLINE_FREQUENCY_DEPENDENT_DELAYS: usize = 5;
+
+/// Carrier frequency dependent delays, stored inline
+/// (no heap allocation) up to [MAX_INLINE_FREQUENCY_DEPENDENT_DELAYS] carriers.
+pub type FrequencyDependentDelays =
+    SmallVec<[(Code, Delay); MAX_INLINE_FREQUENCY_DEPENDENT_DELAYS]>;
+
 /// Indication about precise system delay calibration process,
-/// as found in [CGGTTS].
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// as found in [CGGTTS]: "process-year", possibly followed
+/// by a laboratory code: "process-year-lab".
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CalibrationID {
     /// ID # of this calibration process
     pub process_id: u16,
     /// Year of calibration
     pub year: u16,
+    /// Possible laboratory code
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lab: Option<String>,
 }
 
 impl std::str::FromStr for CalibrationID {
     type Err = ParsingError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut parsed_items = 0;
-        let (mut process_id, mut year) = (0, 0);
-
-        for (nth, item) in s.trim().split('-').enumerate() {
-            if nth == 0 {
-                if let Ok(value) = item.parse::<u16>() {
-                    process_id = value;
-                    parsed_items += 1;
-                }
-            } else if nth == 1 {
-                if let Ok(value) = item.parse::<u16>() {
-                    year = value;
-                    parsed_items += 1;
-                }
-            }
-        }
+        let mut items = s.trim().splitn(3, '-');
+
+        let process_id = items
+            .next()
+            .and_then(|item| item.parse::<u16>().ok())
+            .ok_or(ParsingError::CalibrationFormat)?;
+
+        let year = items
+            .next()
+            .and_then(|item| item.parse::<u16>().ok())
+            .ok_or(ParsingError::CalibrationFormat)?;
+
+        let lab = match items.next() {
+            Some(lab) if lab.is_empty() || lab.contains(char::is_whitespace) => {
+                return Err(ParsingError::CalibrationFormat);
+            },
+            Some(lab) => Some(lab.to_string()),
+            None => None,
+        };
+
+        Ok(Self {
+            process_id,
+            year,
+            lab,
+        })
+    }
+}
 
-        if parsed_items == 2 {
-            Ok(Self { process_id, year })
-        } else {
-            Err(ParsingError::CalibrationFormat)
+impl std::fmt::Display for CalibrationID {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}-{}", self.process_id, self.year)?;
+        if let Some(lab) = &self.lab {
+            write!(f, "-{}", lab)?;
         }
+        Ok(())
     }
 }
 
+/// [DelayKind] describes the convention used to specify
+/// frequency dependent delays. They are related by:
+/// - SYS DLY = INT DLY + CAB DLY
+/// - TOT DLY = INT DLY + CAB DLY + REF DLY
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DelayKind {
+    /// Internal delay (INT DLY)
+    Internal,
+    /// System delay (SYS DLY)
+    System,
+    /// Total delay (TOT DLY)
+    Total,
+}
+
 /// [Delay] describes all supported types of propagation delay.
 /// NB: the specified value is always in nanoseconds.
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -54,6 +97,8 @@ pub enum Delay {
     Internal(f64),
     /// Systemic delay, in nanoseconds
     System(f64),
+    /// Total delay, in nanoseconds
+    Total(f64),
 }
 
 impl Default for Delay {
@@ -73,11 +118,26 @@ impl Delay {
         Self::System(nanos)
     }
 
+    /// Define new total [Delay]
+    pub fn new_total_nanos(nanos: f64) -> Self {
+        Self::Total(nanos)
+    }
+
+    /// Returns the [DelayKind] of this [Delay]
+    pub fn kind(&self) -> DelayKind {
+        match self {
+            Delay::Internal(_) => DelayKind::Internal,
+            Delay::System(_) => DelayKind::System,
+            Delay::Total(_) => DelayKind::Total,
+        }
+    }
+
     /// Returns total delay in nanoseconds, whatever its kind.
     pub fn total_nanoseconds(&self) -> f64 {
         match self {
             Delay::Internal(d) => *d,
             Delay::System(d) => *d,
+            Delay::Total(d) => *d,
         }
     }
 
@@ -92,6 +152,7 @@ impl Delay {
         match self {
             Delay::System(d) => Delay::System(*d + rhs),
             Delay::Internal(d) => Delay::Internal(*d + rhs),
+            Delay::Total(d) => Delay::Total(*d + rhs),
         }
     }
 }
@@ -127,28 +188,29 @@ pub struct SystemDelay {
     /// and local clock.
     pub local_ref_delay: f64,
     /// Carrier frequency dependend delays
-    pub freq_dependent_delays: Vec<(Code, Delay)>,
+    pub freq_dependent_delays: FrequencyDependentDelays,
     /// Possible calibration ID
     pub calibration_id: Option<CalibrationID>,
+    /// True when CAB DLY or REF DLY were parsed without unit (ns),
+    /// which is tolerated. Units are always formatted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub missing_unit: bool,
 }
 
 impl SystemDelay {
     /// Define new [SystemDelay] with desired readable calibration ID.
     /// This is usually the official ID of the calibration process.
-    pub fn with_calibration_id(&self, calibration: CalibrationID) -> Self {
-        Self {
-            antenna_cable_delay: self.antenna_cable_delay,
-            local_ref_delay: self.local_ref_delay,
-            freq_dependent_delays: self.freq_dependent_delays.clone(),
-            calibration_id: Some(calibration),
-        }
+    pub fn with_calibration_id(self, calibration: CalibrationID) -> Self {
+        let mut s = self;
+        s.calibration_id = Some(calibration);
+        s
     }
 
     /// Define new [SystemDelay] with desired
     /// RF cable delay in nanoseconds ie.,
     /// delay induced by the antenna cable length itself.
-    pub fn with_antenna_cable_delay(&self, nanos: f64) -> Self {
-        let mut s = self.clone();
+    pub fn with_antenna_cable_delay(self, nanos: f64) -> Self {
+        let mut s = self;
         s.antenna_cable_delay = nanos;
         s
     }
@@ -156,8 +218,8 @@ impl SystemDelay {
     /// Define new [SystemDelay] with REF delay in nanoseconds,
     /// ie., the delay induced by cable between the measurement
     /// system and the local clock.
-    pub fn with_ref_delay(&self, nanos: f64) -> Self {
-        let mut s = self.clone();
+    pub fn with_ref_delay(self, nanos: f64) -> Self {
+        let mut s = self;
         s.local_ref_delay = nanos;
         s
     }
@@ -167,30 +229,95 @@ impl SystemDelay {
         self.antenna_cable_delay + self.local_ref_delay
     }
 
-    /// Returns total system delay, in nanoseconds,
+    /// Returns total system delay (TOT DLY), in nanoseconds,
     /// for desired frequency represented by [Code], if we
-    /// do have specifications for it.
+    /// do have specifications for it. The cable delays are
+    /// taken into account, depending on the delay convention.
     ///
     /// ```
+    /// use cggtts::prelude::{Code, Delay, SystemDelay};
+    ///
+    /// let mut system_specs = SystemDelay::default()
+    ///     .with_antenna_cable_delay(237.0)
+    ///     .with_ref_delay(149.6);
+    ///
+    /// system_specs.freq_dependent_delays.push((Code::C1, Delay::Internal(53.9)));
+    ///
+    /// let total = system_specs.total_frequency_dependent_delay_nanos(&Code::C1)
+    ///     .unwrap();
+    ///
+    /// assert!((total - 440.5).abs() < 1.0E-9);
     /// ```
     pub fn total_frequency_dependent_delay_nanos(&self, code: &Code) -> Option<f64> {
         for (k, v) in self.freq_dependent_delays.iter() {
             if k == code {
-                return Some(v.total_nanoseconds() + self.total_cable_delay_nanos());
+                return Some(self.total_nanos(v));
             }
         }
         None
     }
 
+    /// Converts [Delay] to total delay value, in nanoseconds.
+    fn total_nanos(&self, delay: &Delay) -> f64 {
+        self.internal_nanos(delay) + self.total_cable_delay_nanos()
+    }
+
+    /// Returns the [DelayKind] (convention) used by the frequency dependent delays,
+    /// or None if no frequency dependent delays are defined.
+    pub fn kind(&self) -> Option<DelayKind> {
+        self.freq_dependent_delays
+            .first()
+            .map(|(_, delay)| delay.kind())
+    }
+
+    /// Converts [Delay] to internal delay value, in nanoseconds.
+    fn internal_nanos(&self, delay: &Delay) -> f64 {
+        match delay {
+            Delay::Internal(d) => *d,
+            Delay::System(d) => *d - self.antenna_cable_delay,
+            Delay::Total(d) => *d - self.antenna_cable_delay - self.local_ref_delay,
+        }
+    }
+
+    /// Converts all frequency dependent delays to internal delays (INT DLY),
+    /// using [Self::antenna_cable_delay] and [Self::local_ref_delay].
+    pub fn to_internal_delays(&self) -> Self {
+        let mut s = self.clone();
+        for (_, delay) in s.freq_dependent_delays.iter_mut() {
+            *delay = Delay::Internal(self.internal_nanos(delay));
+        }
+        s
+    }
+
+    /// Converts all frequency dependent delays to system delays (SYS DLY),
+    /// using [Self::antenna_cable_delay] and [Self::local_ref_delay].
+    pub fn to_system_delays(&self) -> Self {
+        let mut s = self.clone();
+        for (_, delay) in s.freq_dependent_delays.iter_mut() {
+            *delay = Delay::System(self.internal_nanos(delay) + self.antenna_cable_delay);
+        }
+        s
+    }
+
+    /// Converts all frequency dependent delays to total delays (TOT DLY),
+    /// using [Self::antenna_cable_delay] and [Self::local_ref_delay].
+    pub fn to_total_delays(&self) -> Self {
+        let mut s = self.clone();
+        for (_, delay) in s.freq_dependent_delays.iter_mut() {
+            *delay = Delay::Total(self.total_nanos(delay));
+        }
+        s
+    }
+
     /// Iterates over all frequency dependent delays, per carrier frequency,
-    /// in nanoseconds of propagation delay for said frequency.
+    /// in nanoseconds of total propagation delay for said frequency.
     pub fn frequency_dependent_nanos_delay_iter(
         &self,
     ) -> Box<dyn Iterator<Item = (&Code, f64)> + '_> {
         Box::new(
             self.freq_dependent_delays
                 .iter()
-                .map(move |(k, v)| (k, v.total_nanoseconds() + self.total_cable_delay_nanos())),
+                .map(move |(k, v)| (k, self.total_nanos(v))),
         )
     }
 }
@@ -199,6 +326,7 @@ impl SystemDelay {
 mod test {
 
     use super::*;
+    use smallvec::smallvec;
     use std::str::FromStr;
 
     #[test]
@@ -207,8 +335,27 @@ mod test {
         assert_eq!(calibration.process_id, 1015);
         assert_eq!(calibration.year, 2024);
 
+        assert_eq!(calibration.lab, None);
+
         assert!(CalibrationID::from_str("NA").is_err());
         assert!(CalibrationID::from_str("1nnn-2024").is_err());
+        assert!(CalibrationID::from_str("1015-2024-").is_err());
+        assert!(CalibrationID::from_str("1015").is_err());
+
+        assert_eq!(calibration.to_string(), "1015-2024");
+
+        let calibration = CalibrationID::from_str("1015-2021-OP").unwrap();
+        assert_eq!(calibration.process_id, 1015);
+        assert_eq!(calibration.year, 2021);
+        assert_eq!(calibration.lab, Some("OP".to_string()));
+        assert_eq!(calibration.to_string(), "1015-2021-OP");
+
+        assert_eq!(
+            CalibrationID::from_str(" 1015-2021-OP ")
+                .unwrap()
+                .to_string(),
+            "1015-2021-OP"
+        );
     }
 
     #[test]
@@ -267,4 +414,114 @@ mod test {
             .total_frequency_dependent_delay_nanos(&Code::P1)
             .is_none());
     }
+
+    #[test]
+    fn delay_conversions() {
+        // RZSY8257 like specifications
+        let delay = SystemDelay {
+            antenna_cable_delay: 237.0,
+            local_ref_delay: 149.6,
+            freq_dependent_delays: smallvec![(Code::C1, Delay::Internal(53.9))],
+            calibration_id: None,
+            missing_unit: false,
+        };
+
+        assert_eq!(delay.kind(), Some(DelayKind::Internal));
+        assert_eq!(SystemDelay::default().kind(), None);
+
+        let system = delay.to_system_delays();
+        assert_eq!(system.kind(), Some(DelayKind::System));
+        assert_eq!(system.antenna_cable_delay, 237.0);
+        assert_eq!(system.local_ref_delay, 149.6);
+
+        let (code, value) = system.freq_dependent_delays[0];
+        assert_eq!(code, Code::C1);
+        assert!((value.total_nanoseconds() - 290.9).abs() < 1.0E-9);
+
+        let total = delay.to_total_delays();
+        assert_eq!(total.kind(), Some(DelayKind::Total));
+
+        let (code, value) = total.freq_dependent_delays[0];
+        assert_eq!(code, Code::C1);
+        assert!((value.total_nanoseconds() - 440.5).abs() < 1.0E-9);
+
+        // round trips
+        for converted in [
+            system.to_internal_delays(),
+            total.to_internal_delays(),
+            total.to_system_delays().to_internal_delays(),
+            system.to_total_delays().to_internal_delays(),
+        ] {
+            assert_eq!(converted.kind(), Some(DelayKind::Internal));
+            let (code, value) = converted.freq_dependent_delays[0];
+            assert_eq!(code, Code::C1);
+            assert!((value.total_nanoseconds() - 53.9).abs() < 1.0E-9);
+        }
+
+        let (_, value) = total.to_system_delays().freq_dependent_delays[0];
+        assert_eq!(value.kind(), DelayKind::System);
+        assert!((value.total_nanoseconds() - 290.9).abs() < 1.0E-9);
+
+        // per code granularity
+        let delay = SystemDelay {
+            antenna_cable_delay: 10.0,
+            local_ref_delay: 20.0,
+            freq_dependent_delays: smallvec![
+                (Code::C1, Delay::System(40.0)),
+                (Code::P2, Delay::System(50.0)),
+            ],
+            calibration_id: None,
+            missing_unit: false,
+        };
+
+        for (_, total) in delay.frequency_dependent_nanos_delay_iter() {
+            assert!(total == 60.0 || total == 70.0);
+        }
+
+        let total = delay.to_total_delays();
+        assert_eq!(
+            total.total_frequency_dependent_delay_nanos(&Code::P2),
+            Some(70.0)
+        );
+        assert_eq!(
+            total.freq_dependent_delays[..],
+            [
+                (Code::C1, Delay::Total(60.0)),
+                (Code::P2, Delay::Total(70.0))
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_frequency_dependent_delays() {
+        let codes = [Code::C1, Code::C2, Code::P1, Code::P2, Code::E1, Code::E5];
+
+        let mut delay = SystemDelay::default();
+
+        for (nth, code) in codes.iter().enumerate() {
+            delay
+                .freq_dependent_delays
+                .push((*code, Delay::Internal(nth as f64)));
+
+            // stored inline up to 5 carriers
+            assert_eq!(delay.freq_dependent_delays.spilled(), nth >= 5);
+        }
+
+        // iteration is not affected
+        let total = delay
+            .freq_dependent_delays
+            .iter()
+            .map(|(_, delay)| delay.total_nanoseconds())
+            .sum::<f64>();
+
+        assert_eq!(total, 15.0);
+        assert_eq!(delay.freq_dependent_delays.len(), 6);
+        assert_eq!(
+            delay.total_frequency_dependent_delay_nanos(&Code::E5),
+            Some(5.0)
+        );
+
+        let converted = delay.to_total_delays();
+        assert_eq!(converted.freq_dependent_delays.len(), 6);
+    }
 }
@@ -1,17 +1,55 @@
 use crate::{
     buffer::Utf8Buffer,
     errors::FormattingError,
-    prelude::{Header, Version},
+    header::{Code, DelayKind},
+    prelude::{Constellation, Header, Version},
 };
 
 use std::io::{BufWriter, Write};
 
+#[cfg(docsrs)]
+use crate::prelude::CGGTTS;
+
+/// Returns the most likely [Constellation] for this [Code],
+/// used when the [Constellation] is not known.
+fn code_constellation(code: &Code) -> Constellation {
+    match code {
+        Code::E1 | Code::E5 => Constellation::Galileo,
+        Code::B1 | Code::B2 => Constellation::BeiDou,
+        _ => Constellation::GPS,
+    }
+}
+
+/// Pushes a cable or reference delay (in nanoseconds), with a single
+/// decimal and at least 3 zero padded integer digits ("012.3").
+/// Negative values are preceded by their sign ("-005.0"), while
+/// values above 999.9 ns simply expand the field ("12345.6"),
+/// the delay being the only content of its line (free format).
+fn push_system_delay(buf: &mut Utf8Buffer, value: f64) {
+    let width = if value < 0.0 { 6 } else { 5 };
+    buf.push_tenths(value, width, true);
+}
+
 impl Header {
     /// Formats this [CGGTTS] following standard specifications.
+    /// The [Constellation] described in the delay section is deduced
+    /// from the carrier [Code]s, prefer [CGGTTS::format] to format
+    /// a complete file.
     pub fn format<W: Write>(
         &self,
         writer: &mut BufWriter<W>,
         buf: &mut Utf8Buffer,
+    ) -> Result<(), FormattingError> {
+        self.format_with_constellation(writer, buf, self.constellation)
+    }
+
+    /// Formats this [CGGTTS] following standard specifications,
+    /// with possible [Constellation] to describe the delay section.
+    pub(crate) fn format_with_constellation<W: Write>(
+        &self,
+        writer: &mut BufWriter<W>,
+        buf: &mut Utf8Buffer,
+        constellation: Option<Constellation>,
     ) -> Result<(), FormattingError> {
         // clear potential past residues
         buf.clear();
@@ -26,8 +64,8 @@ impl Header {
         buf.push_str(&format!("RCVR = {:x}\n", &self.receiver));
         buf.push_str(&format!("CH = {}\n", self.nb_channels));
 
-        if let Some(ims) = &self.ims_hardware {
-            buf.push_str(&format!("IMS = {:x}\n", ims));
+        if !self.ims.is_unknown() {
+            buf.push_str(&format!("IMS = {:x}\n", self.ims));
         }
 
         buf.push_str(&format!("LAB = {}\n", self.station));
@@ -43,83 +81,24 @@ impl Header {
             buf.push_str(&format!("COMMENTS = NO COMMENTS\n"));
         }
 
-        // TODO system delay formatting
-        // let delays = self.delay.delays.clone();
-        // let constellation = if !self.tracks.is_empty() {
-        //     self.tracks[0].sv.constellation
-        // } else {
-        //     Constellation::default()
-        // };
-
-        // if delays.len() == 1 {
-        //     // Single frequency
-        //     let (code, value) = delays[0];
-        //     match value {
-        //         Delay::Internal(v) => {
-        //             content.push_str(&format!(
-        //                 "INT DLY = {:.1} ns ({:X} {})\n",
-        //                 v, constellation, code
-        //             ));
-        //         },
-        //         Delay::System(v) => {
-        //             content.push_str(&format!(
-        //                 "SYS DLY = {:.1} ns ({:X} {})\n",
-        //                 v, constellation, code
-        //             ));
-        //         },
-        //     }
-        //     if let Some(cal_id) = &self.delay.cal_id {
-        //         content.push_str(&format!("       CAL_ID = {}\n", cal_id));
-        //     } else {
-        //         content.push_str("       CAL_ID = NA\n");
-        //     }
-        // } else if delays.len() == 2 {
-        //     // Dual frequency
-        //     let (c1, v1) = delays[0];
-        //     let (c2, v2) = delays[1];
-        //     match v1 {
-        //         Delay::Internal(_) => {
-        //             content.push_str(&format!(
-        //                 "INT DLY = {:.1} ns ({:X} {}), {:.1} ns ({:X} {})\n",
-        //                 v1.value(),
-        //                 constellation,
-        //                 c1,
-        //                 v2.value(),
-        //                 constellation,
-        //                 c2
-        //             ));
-        //         },
-        //         Delay::System(_) => {
-        //             content.push_str(&format!(
-        //                 "SYS DLY = {:.1} ns ({:X} {}), {:.1} ns ({:X} {})\n",
-        //                 v1.value(),
-        //                 constellation,
-        //                 c1,
-        //                 v2.value(),
-        //                 constellation,
-        //                 c2
-        //             ));
-        //         },
-        //     }
-        //     if let Some(cal_id) = &self.delay.cal_id {
-        //         content.push_str(&format!("     CAL_ID = {}\n", cal_id));
-        //     } else {
-        //         content.push_str("     CAL_ID = NA\n");
-        //     }
-        // }
+        self.format_frequency_dependent_delays(buf, constellation)?;
 
-        buf.push_str(&format!(
-            "CAB DLY = {:05.1} ns\n",
-            self.delay.antenna_cable_delay,
-        ));
+        buf.push_str("CAB DLY = ");
+        push_system_delay(buf, self.delay.antenna_cable_delay);
+        buf.push_str(" ns\n");
 
-        buf.push_str(&format!(
-            "REF DLY = {:05.1} ns\n",
-            self.delay.local_ref_delay
-        ));
+        buf.push_str("REF DLY = ");
+        push_system_delay(buf, self.delay.local_ref_delay);
+        buf.push_str(" ns\n");
 
         buf.push_str(&format!("REF = {}\n", self.reference_time));
 
+        if self.format_extra_fields {
+            for (key, value) in self.extra_fields.iter() {
+                buf.push_str(&format!("{} = {}\n", key, value));
+            }
+        }
+
         // push last bytes contributing to CRC
         buf.push_str("CKSUM = ");
 
@@ -139,13 +118,64 @@ impl Header {
     }
 }
 
+impl Header {
+    /// Formats frequency dependent delays, one line per [DelayKind], like
+    /// "INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021"
+    fn format_frequency_dependent_delays(
+        &self,
+        buf: &mut Utf8Buffer,
+        constellation: Option<Constellation>,
+    ) -> std::io::Result<()> {
+        for (kind, label) in [
+            (DelayKind::Internal, "INT"),
+            (DelayKind::System, "SYS"),
+            (DelayKind::Total, "TOT"),
+        ] {
+            let mut delays = self
+                .delay
+                .freq_dependent_delays
+                .iter()
+                .filter(|(_, delay)| delay.kind() == kind)
+                .peekable();
+
+            if delays.peek().is_none() {
+                continue;
+            }
+
+            buf.push_str(label);
+            buf.push_str(" DLY =");
+
+            for (nth, (code, delay)) in delays.enumerate() {
+                let constellation = constellation.unwrap_or_else(|| code_constellation(code));
+
+                if nth > 0 {
+                    buf.push_str(",");
+                } else {
+                    buf.push_str(" ");
+                }
+
+                buf.push_tenths(delay.total_nanoseconds(), 6, false);
+                write!(buf, " ns ({:E} {})", constellation, code)?;
+            }
+
+            if let Some(cal_id) = &self.delay.calibration_id {
+                writeln!(buf, "     CAL_ID = {}", cal_id)?;
+            } else {
+                buf.push_str("     CAL_ID = NA\n");
+            }
+        }
+
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use std::io::BufWriter;
+    use std::io::{BufReader, BufWriter};
     use std::path::Path;
 
-    use crate::{buffer::Utf8Buffer, CGGTTS};
+    use crate::{buffer::Utf8Buffer, prelude::Header, CGGTTS};
 
     #[test]
     fn header_crc_buffering() {
@@ -165,7 +195,7 @@ mod test {
         assert_eq!(header.receiver.manufacturer, "GTR51");
         assert_eq!(header.receiver.model, "2204005");
 
-        let ims = header.ims_hardware.as_ref().expect("missing IMS");
+        let ims = header.ims_hardware().expect("missing IMS");
         assert_eq!(ims.manufacturer, "GTR51");
         assert_eq!(ims.model, "2204005");
 
@@ -174,7 +204,7 @@ mod test {
         let inner = buf.into_inner().unwrap_or_else(|_| panic!("oops"));
         let ascii_utf8 = inner.to_utf8_ascii().expect("generated invalid utf-8!");
 
-        // TODO: missing
+        // TODO: E6, E5b and E5a are not supported yet
         // INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5),   0.0 ns (GAL E6),   0.0 ns (GAL E5b),  25.6 ns (GAL E5a)     CAL_ID = 1015-2021
 
         let expected = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
@@ -188,13 +218,60 @@ Y =  1018888.020 m
 Z =  4870276.840 m
 FRAME = FRAME
 COMMENTS = NO COMMENTS
+INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021
 CAB DLY = 155.2 ns
 REF DLY = 000.0 ns
 REF = REF_IN
-CKSUM = 53";
+CKSUM = 37";
 
         for (content, expected) in ascii_utf8.lines().zip(expected.lines()) {
             assert_eq!(content, expected);
         }
     }
+
+    #[test]
+    fn system_delays_formatting() {
+        let mut utf8 = Utf8Buffer::new(1024);
+
+        for (cab, rf, expected_cab, expected_ref) in [
+            (155.2, 0.0, "CAB DLY = 155.2 ns", "REF DLY = 000.0 ns"),
+            (-5.0, 12345.6, "CAB DLY = -005.0 ns", "REF DLY = 12345.6 ns"),
+            (1234.5, -12.3, "CAB DLY = 1234.5 ns", "REF DLY = -012.3 ns"),
+            (7.3, -1234.5, "CAB DLY = 007.3 ns", "REF DLY = -1234.5 ns"),
+        ] {
+            let mut header = Header::default().with_station("LAB");
+            header.delay.antenna_cable_delay = cab;
+            header.delay.local_ref_delay = rf;
+
+            let mut buf = BufWriter::new(Vec::new());
+            header.format(&mut buf, &mut utf8).unwrap();
+
+            let content = String::from_utf8(buf.into_inner().unwrap()).unwrap();
+            assert!(
+                content.lines().any(|line| line == expected_cab),
+                "{}",
+                content
+            );
+            assert!(
+                content.lines().any(|line| line == expected_ref),
+                "{}",
+                content
+            );
+
+            let parsed = Header::parse(&mut BufReader::new(content.as_bytes())).unwrap();
+            assert_eq!(
+                parsed.delay.antenna_cable_delay,
+                expected_cab[10..expected_cab.len() - 3]
+                    .parse::<f64>()
+                    .unwrap()
+            );
+            assert_eq!(
+                parsed.delay.local_ref_delay,
+                expected_ref[10..expected_ref.len() - 3]
+                    .parse::<f64>()
+                    .unwrap()
+            );
+            assert!(!parsed.delay.missing_unit);
+        }
+    }
 }
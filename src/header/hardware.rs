@@ -17,38 +17,38 @@ pub struct Hardware {
 
 impl Hardware {
     /// Define a new [Hardware] with desired model name
-    pub fn with_model(&self, model: &str) -> Self {
-        let mut s = self.clone();
+    pub fn with_model(self, model: &str) -> Self {
+        let mut s = self;
         s.model = model.to_string();
         s
     }
 
     /// Define a new [Hardware] with desired manufacturer
-    pub fn with_manufacturer(&self, manufacturer: &str) -> Self {
-        let mut s = self.clone();
+    pub fn with_manufacturer(self, manufacturer: &str) -> Self {
+        let mut s = self;
         s.manufacturer = manufacturer.to_string();
         s
     }
 
     /// Define a new [Hardware] with desired serial number
-    pub fn with_serial_number(&self, serial_number: &str) -> Self {
-        let mut s = self.clone();
+    pub fn with_serial_number(self, serial_number: &str) -> Self {
+        let mut s = self;
         s.serial_number = serial_number.to_string();
         s
     }
 
     /// Define a new [Hardware] with desired year of production
     /// or release.
-    pub fn with_release_year(&self, y: u16) -> Self {
-        let mut s = self.clone();
+    pub fn with_release_year(self, y: u16) -> Self {
+        let mut s = self;
         s.year = y;
         s
     }
 
     /// Define a new [Hardware] with desired firmware or
     /// software release version.
-    pub fn with_release_version(&self, version: &str) -> Self {
-        let mut s = self.clone();
+    pub fn with_release_version(self, version: &str) -> Self {
+        let mut s = self;
         s.release = version.to_string();
         s
     }
@@ -0,0 +1,55 @@
+use crate::header::Hardware;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// IMS field value, when the measurement system is integrated
+/// in the GNSS receiver.
+pub(crate) const INTEGRATED_IMS: &str = "99999";
+
+/// [ImsField] describes the Ionospheric Measurement System (IMS)
+/// declared in the header.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImsField {
+    /// "IMS = 99999": the measurement system is integrated in the receiver.
+    Integrated,
+    /// Dedicated IMS [Hardware] description.
+    Hardware(Hardware),
+    /// IMS line omitted.
+    #[default]
+    Unknown,
+}
+
+impl ImsField {
+    /// Returns the IMS [Hardware], if described.
+    pub fn hardware(&self) -> Option<&Hardware> {
+        match self {
+            Self::Hardware(hardware) => Some(hardware),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the IMS line was omitted.
+    pub fn is_unknown(&self) -> bool {
+        *self == Self::Unknown
+    }
+}
+
+impl From<Hardware> for ImsField {
+    fn from(hardware: Hardware) -> Self {
+        Self::Hardware(hardware)
+    }
+}
+
+impl std::fmt::LowerHex for ImsField {
+    /// Formats the IMS field value, as used in a CGGTTS header.
+    /// Nothing is formatted when the line is omitted.
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Integrated => fmt.write_str(INTEGRATED_IMS),
+            Self::Hardware(hardware) => write!(fmt, "{:x}", hardware),
+            Self::Unknown => Ok(()),
+        }
+    }
+}
@@ -2,6 +2,7 @@ mod code;
 mod delay;
 mod formatting;
 mod hardware;
+mod ims;
 mod parsing;
 mod reference_time;
 mod version;
@@ -11,13 +12,21 @@ use crate::prelude::CGGTTS;
 
 pub use crate::header::{
     code::Code,
-    delay::{CalibrationID, Delay, SystemDelay},
+    delay::{
+        CalibrationID, Delay, DelayKind, FrequencyDependentDelays, SystemDelay,
+        MAX_INLINE_FREQUENCY_DEPENDENT_DELAYS,
+    },
     hardware::Hardware,
+    ims::ImsField,
     reference_time::ReferenceTime,
     version::Version,
 };
 
-use crate::prelude::{Epoch, TimeScale};
+use crate::prelude::{Constellation, Epoch, TimeScale};
+
+use hifitime::Unit;
+
+use std::str::FromStr;
 
 #[derive(PartialEq, Debug, Clone, Copy, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -27,13 +36,114 @@ pub struct Coordinates {
     pub z: f64,
 }
 
-#[derive(Debug, Clone)]
+/// WGS84 semi major axis, in meters
+const WGS84_SEMI_MAJOR_AXIS_M: f64 = 6_378_137.0;
+
+/// WGS84 flattening
+const WGS84_FLATTENING: f64 = 1.0 / 298.257_223_563;
+
+/// Julian year, in days, used to express station velocities
+const JULIAN_YEAR_DAYS: f64 = 365.25;
+
+/// Non standard header field describing the APC coordinates [Epoch]
+const APC_EPOCH_FIELD: &str = "APC EPOCH";
+
+/// Non standard header field describing the APC velocity
+const APC_VELOCITY_FIELD: &str = "APC VELOCITY";
+
+/// Tag of the reference time offsets, embedded in the comments
+const REFERENCE_OFFSET_TAG: &str = "REFOFFSET";
+
+/// Separates the reference time offsets (and free text) in the comments
+const COMMENTS_SEPARATOR: char = ';';
+
+impl Coordinates {
+    /// Converts these ECEF [Coordinates] to geodetic coordinates,
+    /// on the WGS84 ellipsoid. Returns (latitude, longitude, altitude)
+    /// in (degrees, degrees, meters), or None for the default (0, 0, 0)
+    /// coordinates, which describe an unknown position.
+    /// At the poles, longitude is arbitrarily reported as 0°.
+    /// ```
+    /// use cggtts::prelude::Coordinates;
+    ///
+    /// let apc = Coordinates {
+    ///     x: 4_202_777.4,
+    ///     y: 171_368.0,
+    ///     z: 4_778_660.2,
+    /// };
+    ///
+    /// let (lat_deg, lon_deg, _) = apc.to_geodetic().unwrap();
+    /// assert!((lat_deg - 48.836).abs() < 1.0E-3);
+    /// assert!((lon_deg - 2.335).abs() < 1.0E-3);
+    ///
+    /// assert!(Coordinates::default().to_geodetic().is_none());
+    /// ```
+    pub fn to_geodetic(&self) -> Option<(f64, f64, f64)> {
+        if self.x == 0.0 && self.y == 0.0 && self.z == 0.0 {
+            return None;
+        }
+
+        let e2 = WGS84_FLATTENING * (2.0 - WGS84_FLATTENING);
+        let p = self.x.hypot(self.y);
+        let lon = self.y.atan2(self.x);
+
+        let mut lat = self.z.atan2(p * (1.0 - e2));
+        let mut alt = 0.0;
+
+        for _ in 0..5 {
+            let (sin_lat, cos_lat) = lat.sin_cos();
+            let n = WGS84_SEMI_MAJOR_AXIS_M / (1.0 - e2 * sin_lat * sin_lat).sqrt();
+            // does not divide by cos(lat): remains valid at the poles
+            alt = p * cos_lat + self.z * sin_lat - n * (1.0 - e2 * sin_lat * sin_lat);
+            lat = self.z.atan2(p * (1.0 - e2 * n / (n + alt)));
+        }
+
+        Some((lat.to_degrees(), lon.to_degrees(), alt))
+    }
+
+    /// Propagates these [Coordinates], referenced to `from` [Epoch],
+    /// to the `to` [Epoch], with constant velocity expressed in m/yr
+    /// (Julian year). ITRF stations typically move by a few cm/yr.
+    /// ```
+    /// use cggtts::prelude::{Coordinates, Epoch};
+    /// use std::str::FromStr;
+    ///
+    /// let apc = Coordinates {
+    ///     x: 4_202_777.4,
+    ///     y: 171_368.0,
+    ///     z: 4_778_660.2,
+    /// };
+    ///
+    /// let velocity = Coordinates {
+    ///     x: -0.0135,
+    ///     y: 0.0180,
+    ///     z: 0.0105,
+    /// };
+    ///
+    /// let from = Epoch::from_str("2010-01-01T00:00:00 UTC").unwrap();
+    /// let to = Epoch::from_str("2011-01-01T06:00:00 UTC").unwrap();
+    ///
+    /// let propagated = apc.propagate(velocity, from, to);
+    /// assert!((propagated.x - 4_202_777.3865).abs() < 1.0E-4);
+    /// ```
+    pub fn propagate(&self, velocity_m_per_yr: Coordinates, from: Epoch, to: Epoch) -> Coordinates {
+        let dt_yr = (to - from).to_unit(Unit::Day) / JULIAN_YEAR_DAYS;
+        Coordinates {
+            x: self.x + velocity_m_per_yr.x * dt_yr,
+            y: self.y + velocity_m_per_yr.y * dt_yr,
+            z: self.z + velocity_m_per_yr.z * dt_yr,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Header {
     /// CGGTTS [Version] used at production time of this [CGGTTS].
     pub version: Version,
     /// Date this [Header] was revised. Updated each
     /// time a new field appears.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::epoch"))]
     pub revision_date: Epoch,
     /// Station name, usually the data producer (agency, laboratory..).
     pub station: String,
@@ -41,9 +151,9 @@ pub struct Header {
     pub receiver: Hardware,
     /// # of channels this GNSS receiver possesses
     pub nb_channels: u16,
-    /// Possible Ionospheric Measurement System (IMS) information.
-    /// Should always be attached to multi channel / modern [CGGTTS].
-    pub ims_hardware: Option<Hardware>,
+    /// Ionospheric Measurement System (IMS) information.
+    /// Should always be described in multi channel / modern [CGGTTS].
+    pub ims: ImsField,
     /// [ReferenceTime] used in the solving process of each [Track]
     pub reference_time: ReferenceTime,
     /// Name of the ECEF Coordinates system in which the APC
@@ -55,6 +165,29 @@ pub struct Header {
     pub comments: Option<String>,
     /// Measurement [SystemDelay]
     pub delay: SystemDelay,
+    /// [Constellation] this [CGGTTS] is declared to describe, if known
+    /// before any [crate::prelude::Track] exists. Not part of the file format, used
+    /// when generating file names and describing the delay section.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constellation: Option<Constellation>,
+    /// Nominal MJD this [CGGTTS] is declared to describe, if known
+    /// before any [crate::prelude::Track] exists. Not part of the file format,
+    /// used when generating file names.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nominal_mjd: Option<u32>,
+    /// Non standard "KEY = VALUE" lines found ahead of CKSUM, for example
+    /// proprietary fields added by laboratories, in order of appearance.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra_fields: Vec<(String, String)>,
+    /// Whether [Self::extra_fields] are formatted, after the standard fields.
+    /// They contribute to the CKSUM in any case. True by default.
+    #[cfg_attr(feature = "serde", serde(default = "default_true"))]
+    pub format_extra_fields: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_true() -> bool {
+    true
 }
 
 impl Default for Header {
@@ -66,20 +199,24 @@ impl Default for Header {
             nb_channels: Default::default(),
             apc_coordinates: Default::default(),
             receiver: Default::default(),
-            ims_hardware: Default::default(),
+            ims: Default::default(),
             comments: Default::default(),
             delay: Default::default(),
             reference_time: Default::default(),
             reference_frame: Default::default(),
             revision_date: Epoch::from_gregorian_utc_at_midnight(2014, 2, 20),
+            constellation: None,
+            nominal_mjd: None,
+            extra_fields: Vec::new(),
+            format_extra_fields: true,
         }
     }
 }
 
 impl Header {
     /// Returns [Header] with desired station name
-    pub fn with_station(&self, station: &str) -> Self {
-        let mut c = self.clone();
+    pub fn with_station(self, station: &str) -> Self {
+        let mut c = self;
         c.station = station.to_string();
         c
     }
@@ -87,60 +224,460 @@ impl Header {
     /// Adds readable comments to this [Header].
     /// Try to keep it short, because it will eventually be
     /// wrapped in a single line.
-    pub fn with_comment(&self, comment: &str) -> Self {
-        let mut s = self.clone();
+    pub fn with_comment(self, comment: &str) -> Self {
+        let mut s = self;
         s.comments = Some(comment.to_string());
         s
     }
 
     /// Returns a new [Header] with desired number of channels.
-    pub fn with_channels(&self, ch: u16) -> Self {
-        let mut c = self.clone();
+    pub fn with_channels(self, ch: u16) -> Self {
+        let mut c = self;
         c.nb_channels = ch;
         c
     }
 
     /// Returns a new [Header] with [Hardware] information about
     /// the GNSS receiver.
-    pub fn with_receiver_hardware(&self, receiver: Hardware) -> Self {
-        let mut c = self.clone();
+    pub fn with_receiver_hardware(self, receiver: Hardware) -> Self {
+        let mut c = self;
         c.receiver = receiver;
         c
     }
 
     /// Returns a new [Header] with [Hardware] information about
     /// the device that help estimate the Ionosphere parameters.
-    pub fn with_ims_hardware(&self, ims: Hardware) -> Self {
-        let mut c = self.clone();
-        c.ims_hardware = Some(ims);
+    pub fn with_ims_hardware(self, ims: Hardware) -> Self {
+        let mut c = self;
+        c.ims = ImsField::Hardware(ims);
         c
     }
 
+    /// Returns a new [Header] declaring that the Ionospheric
+    /// Measurement System is integrated in the receiver ("IMS = 99999").
+    pub fn with_integrated_ims(self) -> Self {
+        let mut c = self;
+        c.ims = ImsField::Integrated;
+        c
+    }
+
+    /// Returns the IMS [Hardware], if described. Refer to [Self::ims].
+    pub fn ims_hardware(&self) -> Option<&Hardware> {
+        self.ims.hardware()
+    }
+
     /// Returns new [Header] with desired APC coordinates in ECEF.
-    pub fn with_apc_coordinates(&self, apc: Coordinates) -> Self {
-        let mut c = self.clone();
+    pub fn with_apc_coordinates(self, apc: Coordinates) -> Self {
+        let mut c = self;
         c.apc_coordinates = apc;
         c
     }
 
+    /// Returns new [Header] with desired APC coordinates in ECEF, referenced
+    /// to `coord_epoch` and moving at `velocity` (in m/yr).
+    /// Both are stored as non standard fields (refer to [Self::extra_fields]),
+    /// so they are formatted and parsed back. Refer to [Self::apc_coordinates_at].
+    pub fn with_coordinates_at_epoch(
+        self,
+        coords: Coordinates,
+        velocity: Coordinates,
+        coord_epoch: Epoch,
+    ) -> Self {
+        let mut c = self.with_apc_coordinates(coords);
+
+        c.extra_fields
+            .retain(|(key, _)| key != APC_EPOCH_FIELD && key != APC_VELOCITY_FIELD);
+
+        c.extra_fields
+            .push((APC_EPOCH_FIELD.to_string(), coord_epoch.to_string()));
+
+        c.extra_fields.push((
+            APC_VELOCITY_FIELD.to_string(),
+            format!("{:.5} {:.5} {:.5} m/yr", velocity.x, velocity.y, velocity.z),
+        ));
+
+        c
+    }
+
+    /// Returns the [Epoch] the APC coordinates are referenced to, if declared.
+    /// Refer to [Self::with_coordinates_at_epoch].
+    pub fn apc_epoch(&self) -> Option<Epoch> {
+        let (_, value) = self
+            .extra_fields
+            .iter()
+            .find(|(key, _)| key == APC_EPOCH_FIELD)?;
+
+        Epoch::from_str(value).ok()
+    }
+
+    /// Returns the APC velocity (in m/yr), if declared.
+    /// Refer to [Self::with_coordinates_at_epoch].
+    pub fn apc_velocity(&self) -> Option<Coordinates> {
+        let (_, value) = self
+            .extra_fields
+            .iter()
+            .find(|(key, _)| key == APC_VELOCITY_FIELD)?;
+
+        let mut items = value.trim_end_matches("m/yr").split_ascii_whitespace();
+
+        let x = items.next()?.parse::<f64>().ok()?;
+        let y = items.next()?.parse::<f64>().ok()?;
+        let z = items.next()?.parse::<f64>().ok()?;
+
+        Some(Coordinates { x, y, z })
+    }
+
+    /// Returns APC coordinates propagated to desired [Epoch], when both the
+    /// coordinates [Epoch] and velocity are declared. Useful to compare
+    /// files produced years apart.
+    pub fn apc_coordinates_at(&self, t: Epoch) -> Option<Coordinates> {
+        let coord_epoch = self.apc_epoch()?;
+        let velocity = self.apc_velocity()?;
+        Some(self.apc_coordinates.propagate(velocity, coord_epoch, t))
+    }
+
+    /// Returns new [Header] embedding the offsets (in seconds) of the
+    /// [ReferenceTime] to the timescale it is steered to (for example UTC(k) - UTC),
+    /// so the receiving lab may correct for them.
+    /// They are encoded in the comments, as `REFOFFSET mjd value_ns` entries
+    /// separated by semicolons and following possible free text, for example
+    /// "COMMENTS = STEERED; REFOFFSET 60258.000000 1.200; REFOFFSET 60259.000000 -0.800".
+    /// The MJD is written with 6 decimals (about 0.1 s), the offset in nanoseconds with 3.
+    /// Previously declared offsets are replaced, any other text is preserved verbatim.
+    /// Refer to [Self::reference_offsets].
+    pub fn with_reference_offsets(self, offsets: &[(Epoch, f64)]) -> Self {
+        let mut c = self;
+
+        let mut text = String::new();
+
+        if let Some(comments) = &c.comments {
+            let mut stripped = false;
+
+            for item in comments.split_inclusive(COMMENTS_SEPARATOR) {
+                if item.trim_start().starts_with(REFERENCE_OFFSET_TAG) {
+                    stripped = true;
+                } else {
+                    text.push_str(item);
+                }
+            }
+
+            if stripped {
+                // drop the separator that introduced the removed entries
+                let len = text
+                    .trim_end_matches(|ch: char| ch == COMMENTS_SEPARATOR || ch.is_whitespace())
+                    .len();
+                text.truncate(len);
+            }
+        }
+
+        for (epoch, offset) in offsets.iter() {
+            if !text.is_empty() {
+                text.push(COMMENTS_SEPARATOR);
+                text.push(' ');
+            }
+
+            text.push_str(&format!(
+                "{} {:.6} {:.3}",
+                REFERENCE_OFFSET_TAG,
+                epoch.to_mjd_utc_days(),
+                offset * 1.0E9
+            ));
+        }
+
+        c.comments = if text.is_empty() { None } else { Some(text) };
+        c
+    }
+
+    /// Returns the [ReferenceTime] offsets (in seconds) embedded in the comments,
+    /// in order of appearance. Refer to [Self::with_reference_offsets].
+    pub fn reference_offsets(&self) -> Vec<(Epoch, f64)> {
+        let comments = match &self.comments {
+            Some(comments) => comments,
+            None => return Vec::new(),
+        };
+
+        comments
+            .split(COMMENTS_SEPARATOR)
+            .filter_map(|item| {
+                let mut items = item.split_ascii_whitespace();
+                if items.next()? != REFERENCE_OFFSET_TAG {
+                    return None;
+                }
+
+                let mjd = items.next()?.parse::<f64>().ok()?;
+                let offset_ns = items.next()?.parse::<f64>().ok()?;
+
+                Some((Epoch::from_mjd_utc(mjd), offset_ns * 1.0E-9))
+            })
+            .collect()
+    }
+
     /// Returns new [Header] with [TimeScale::UTC] reference system time.
-    pub fn with_utc_reference_time(&self) -> Self {
-        let mut c = self.clone();
+    pub fn with_utc_reference_time(self) -> Self {
+        let mut c = self;
         c.reference_time = TimeScale::UTC.into();
         c
     }
 
     /// Returns new [Header] with desired [ReferenceTime] system
-    pub fn with_reference_time(&self, reference: ReferenceTime) -> Self {
-        let mut c = self.clone();
+    pub fn with_reference_time(self, reference: ReferenceTime) -> Self {
+        let mut c = self;
         c.reference_time = reference;
         c
     }
 
     /// Returns new [Header] with desired Reference Frame
-    pub fn with_reference_frame(&self, reference: &str) -> Self {
-        let mut c = self.clone();
+    pub fn with_reference_frame(self, reference: &str) -> Self {
+        let mut c = self;
         c.reference_frame = reference.to_string();
         c
     }
+
+    /// Returns new [Header] declaring the [Constellation] to be tracked.
+    /// Useful to name a file before any [crate::prelude::Track] exists.
+    pub fn with_constellation(self, constellation: Constellation) -> Self {
+        let mut c = self;
+        c.constellation = Some(constellation);
+        c
+    }
+
+    /// Returns true if both [Header]s describe the same content, once formatted.
+    /// Unlike strict equality, this ignores the revision date, the [Version]
+    /// (always formatted as the latest revision) and the fields that are never
+    /// formatted (declared [Constellation] and nominal MJD, unit tolerance).
+    pub fn content_eq(&self, other: &Self) -> bool {
+        fn extra_fields(header: &Header) -> &[(String, String)] {
+            if header.format_extra_fields {
+                &header.extra_fields
+            } else {
+                &[]
+            }
+        }
+
+        self.station == other.station
+            && self.receiver == other.receiver
+            && self.nb_channels == other.nb_channels
+            && self.ims == other.ims
+            && self.reference_time == other.reference_time
+            && self.reference_frame == other.reference_frame
+            && self.apc_coordinates == other.apc_coordinates
+            && self.comments == other.comments
+            && self.delay.antenna_cable_delay == other.delay.antenna_cable_delay
+            && self.delay.local_ref_delay == other.delay.local_ref_delay
+            && self.delay.freq_dependent_delays == other.delay.freq_dependent_delays
+            && self.delay.calibration_id == other.delay.calibration_id
+            && extra_fields(self) == extra_fields(other)
+    }
+
+    /// Returns new [Header] with an additional non standard "KEY = VALUE" field.
+    pub fn with_extra_field(self, key: &str, value: &str) -> Self {
+        let mut c = self;
+        c.extra_fields.push((key.to_string(), value.to_string()));
+        c
+    }
+
+    /// Returns new [Header] with desired [Self::extra_fields] formatting.
+    pub fn with_extra_fields_formatting(self, format: bool) -> Self {
+        let mut c = self;
+        c.format_extra_fields = format;
+        c
+    }
+
+    /// Returns new [Header] declaring the nominal MJD to be described.
+    /// Useful to name a file before any [crate::prelude::Track] exists.
+    pub fn with_nominal_mjd(self, mjd: u32) -> Self {
+        let mut c = self;
+        c.nominal_mjd = Some(mjd);
+        c
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Coordinates, Header};
+    use crate::{buffer::Utf8Buffer, prelude::Epoch};
+    use hifitime::Unit;
+    use std::{
+        io::{BufReader, BufWriter},
+        str::FromStr,
+    };
+
+    #[test]
+    fn geodetic_coordinates() {
+        // equator, greenwich meridian
+        let apc = Coordinates {
+            x: 6_378_137.0,
+            y: 0.0,
+            z: 0.0,
+        };
+
+        let (lat, lon, alt) = apc.to_geodetic().unwrap();
+        assert!(lat.abs() < 1.0E-9);
+        assert!(lon.abs() < 1.0E-9);
+        assert!(alt.abs() < 1.0E-6);
+
+        // station used in our test data
+        let apc = Coordinates {
+            x: 3_970_727.80,
+            y: 1_018_888.02,
+            z: 4_870_276.84,
+        };
+
+        let (lat, lon, alt) = apc.to_geodetic().unwrap();
+        assert!((lat - 50.102).abs() < 1.0E-3, "lat={}", lat);
+        assert!((lon - 14.392).abs() < 1.0E-3, "lon={}", lon);
+        assert!(alt > 0.0 && alt < 1000.0, "alt={}", alt);
+
+        // poles, 100 m above the ellipsoid
+        let semi_minor_axis = 6_356_752.314_245;
+
+        for (z, expected_lat) in [
+            (semi_minor_axis + 100.0, 90.0),
+            (-semi_minor_axis - 100.0, -90.0),
+        ] {
+            let apc = Coordinates { x: 0.0, y: 0.0, z };
+
+            let (lat, lon, alt) = apc.to_geodetic().unwrap();
+            assert!((lat - expected_lat).abs() < 1.0E-9, "lat={}", lat);
+            assert!(lon.abs() < 1.0E-9, "lon={}", lon);
+            assert!((alt - 100.0).abs() < 1.0E-6, "alt={}", alt);
+        }
+
+        // unknown position
+        assert!(Coordinates::default().to_geodetic().is_none());
+    }
+
+    #[test]
+    fn coordinates_propagation() {
+        let apc = Coordinates {
+            x: 3_970_727.800,
+            y: 1_018_888.020,
+            z: 4_870_276.840,
+        };
+
+        let velocity = Coordinates {
+            x: -0.0152,
+            y: 0.0171,
+            z: 0.0098,
+        };
+
+        let t0 = Epoch::from_str("2015-01-01T00:00:00 UTC").unwrap();
+        let t1 = Epoch::from_str("2025-01-01T00:00:00 UTC").unwrap();
+
+        // 3653 days
+        let years = 3653.0 / 365.25;
+        let propagated = apc.propagate(velocity, t0, t1);
+
+        assert!((propagated.x - (apc.x - 0.0152 * years)).abs() < 1.0E-3);
+        assert!((propagated.y - (apc.y + 0.0171 * years)).abs() < 1.0E-3);
+        assert!((propagated.z - (apc.z + 0.0098 * years)).abs() < 1.0E-3);
+
+        // few cm after 10 years
+        assert!((propagated.x - 3_970_727.648).abs() < 1.0E-3);
+        assert!((propagated.y - 1_018_888.191).abs() < 1.0E-3);
+        assert!((propagated.z - 4_870_276.938).abs() < 1.0E-3);
+
+        // backwards
+        let back = propagated.propagate(velocity, t1, t0);
+        assert!((back.x - apc.x).abs() < 1.0E-6);
+        assert!((back.y - apc.y).abs() < 1.0E-6);
+        assert!((back.z - apc.z).abs() < 1.0E-6);
+
+        // header round trip
+        let header = Header::default()
+            .with_station("LAB")
+            .with_coordinates_at_epoch(apc, velocity, t0);
+
+        let mut buf = Utf8Buffer::new(1024);
+        let mut writer = BufWriter::new(Vec::new());
+        header.format(&mut writer, &mut buf).unwrap();
+        let bytes = writer.into_inner().unwrap();
+
+        let parsed = Header::parse(&mut BufReader::new(&bytes[..])).unwrap();
+
+        assert_eq!(parsed.apc_epoch(), Some(t0));
+        assert_eq!(parsed.apc_velocity(), Some(velocity));
+
+        let at_t1 = parsed.apc_coordinates_at(t1).unwrap();
+        assert!((at_t1.x - propagated.x).abs() < 1.0E-3);
+        assert!((at_t1.y - propagated.y).abs() < 1.0E-3);
+        assert!((at_t1.z - propagated.z).abs() < 1.0E-3);
+
+        assert!(Header::default().apc_coordinates_at(t1).is_none());
+    }
+
+    #[test]
+    fn reference_offsets() {
+        let t0 = Epoch::from_mjd_utc(60258.0);
+
+        let offsets = [
+            (t0, 1.2E-9),
+            (t0 + 1.0 * Unit::Day, -0.8E-9),
+            (t0 + 1.5 * Unit::Day, 12.345E-9),
+        ];
+
+        assert!(Header::default().reference_offsets().is_empty());
+
+        let header = Header::default()
+            .with_station("LAB")
+            .with_comment("STEERED")
+            .with_reference_offsets(&offsets);
+
+        assert_eq!(
+            header.comments,
+            Some(
+                "STEERED; REFOFFSET 60258.000000 1.200; REFOFFSET 60259.000000 -0.800; REFOFFSET 60259.500000 12.345"
+                    .to_string()
+            )
+        );
+
+        let mut buf = Utf8Buffer::new(1024);
+        let mut writer = BufWriter::new(Vec::new());
+        header.format(&mut writer, &mut buf).unwrap();
+        let bytes = writer.into_inner().unwrap();
+
+        let parsed = Header::parse(&mut BufReader::new(&bytes[..])).unwrap();
+        let parsed_offsets = parsed.reference_offsets();
+
+        assert_eq!(parsed_offsets.len(), 3);
+        for ((epoch, offset), (expected_epoch, expected)) in parsed_offsets.iter().zip(offsets) {
+            assert_eq!(*epoch, expected_epoch);
+            assert!((offset - expected).abs() < 1.0E-15);
+        }
+
+        // replaced, free text is preserved
+        let header = parsed.with_reference_offsets(&offsets[..1]);
+        assert_eq!(
+            header.comments,
+            Some("STEERED; REFOFFSET 60258.000000 1.200".to_string())
+        );
+
+        // free text is kept verbatim, wherever the offsets are
+        let header = Header::default()
+            .with_comment("A;B; REFOFFSET 60258 1.0;C")
+            .with_reference_offsets(&offsets[..1]);
+        assert_eq!(
+            header.comments,
+            Some("A;B;C; REFOFFSET 60258.000000 1.200".to_string())
+        );
+
+        let header = Header::default()
+            .with_comment("A;B")
+            .with_reference_offsets(&[]);
+        assert_eq!(header.comments, Some("A;B".to_string()));
+
+        // epoch away from midnight: fixed precision, no float noise
+        let t1 = t0 + 7.0 * Unit::Hour + 23.0 * Unit::Minute;
+        let header = Header::default().with_reference_offsets(&[(t1, 1.0E-9)]);
+        assert_eq!(
+            header.comments,
+            Some("REFOFFSET 60258.307639 1.000".to_string())
+        );
+
+        let (epoch, _) = header.reference_offsets()[0];
+        assert!((epoch - t1).abs() < 0.1 * Unit::Second);
+
+        let header = Header::default().with_reference_offsets(&[]);
+        assert_eq!(header.comments, None);
+    }
 }
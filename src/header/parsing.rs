@@ -1,38 +1,84 @@
 use crate::{
+    crc::Crc8,
     errors::ParsingError,
-    header::{CalibrationID, Code, Coordinates, Delay, SystemDelay},
-    prelude::{Epoch, Hardware, Header, ReferenceTime, Version},
+    header::{
+        ims::INTEGRATED_IMS, CalibrationID, Code, Coordinates, Delay, ImsField, SystemDelay,
+        MAX_INLINE_FREQUENCY_DEPENDENT_DELAYS,
+    },
+    prelude::{Epoch, Hardware, Header, ReferenceTime, Track, Version},
 };
 
-use scan_fmt::scan_fmt;
+use smallvec::SmallVec;
 
 use std::{
     io::{BufRead, BufReader, Read},
     str::FromStr,
 };
 
-fn parse_header_version(s: &str) -> Result<Version, ParsingError> {
-    const MARKER: &str = "CGGTTS     GENERIC DATA FORMAT VERSION = ";
-    const SIZE: usize = MARKER.len();
+/// Returns the remainder of this header line, if it starts with
+/// all the words of the desired key, whatever the whitespace
+/// (spaces, tabulations) that separates them, and whatever their case
+/// (hand edited files may use "Rev Date" or "cksum").
+fn strip_key<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let mut rest = line;
 
-    if !s.starts_with(MARKER) {
-        return Err(ParsingError::VersionFormat);
-    };
+    for word in key.split_ascii_whitespace() {
+        rest = rest.trim_start();
+
+        if !rest.get(..word.len())?.eq_ignore_ascii_case(word) {
+            return None;
+        }
+
+        rest = &rest[word.len()..];
+
+        // words must match completely
+        if rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+            return None;
+        }
+    }
+
+    Some(rest)
+}
+
+/// Returns the (trimmed) value of this "KEY = value" header line,
+/// if it matches the desired key.
+fn field_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let value = strip_key(line, key)?.trim_start().strip_prefix('=')?;
+    Some(value.trim())
+}
+
+fn parse_header_version(s: &str) -> Result<Version, ParsingError> {
+    let content =
+        field_value(s, "CGGTTS GENERIC DATA FORMAT VERSION").ok_or(ParsingError::VersionFormat)?;
 
-    let content = s[SIZE..].trim();
-    let version = Version::from_str(&content)?;
+    let version = Version::from_str(content)?;
     Ok(version)
 }
 
+/// Parses a system delay value (in nanoseconds), with optional unit.
+/// Returns the value and whether the unit was present.
+fn parse_delay(s: &str) -> Option<(f64, bool)> {
+    let mut items = s.split_ascii_whitespace();
+    let value = items.next()?.parse::<f64>().ok()?;
+    Some((value, items.next().is_some()))
+}
+
 fn parse_header_date(s: &str) -> Result<Epoch, ParsingError> {
-    const SIZE: usize = "REV DATE = ".len();
+    let value = field_value(s, "REV DATE").ok_or(ParsingError::RevisionDateFormat)?;
 
-    let t = Epoch::from_format_str(s[SIZE..].trim(), "%Y-%m-%d")
-        .or(Err(ParsingError::RevisionDateFormat))?;
+    let t = Epoch::from_format_str(value, "%Y-%m-%d").or(Err(ParsingError::RevisionDateFormat))?;
 
     Ok(t)
 }
 
+/// Parses one APC coordinate value, with optional unit
+fn parse_coordinate(s: &str) -> Result<f64, ParsingError> {
+    s.trim_end_matches('m')
+        .trim()
+        .parse::<f64>()
+        .or(Err(ParsingError::Coordinates))
+}
+
 fn parse_hardware(s: &str) -> Result<Hardware, ParsingError> {
     let mut hw = Hardware::default();
 
@@ -56,25 +102,110 @@ fn parse_hardware(s: &str) -> Result<Hardware, ParsingError> {
     Ok(hw)
 }
 
+/// Returns this line without its termination ("\n" or "\r\n"),
+/// like [BufRead::lines] does.
+fn trim_line_ending(line: &str) -> &str {
+    match line.strip_suffix('\n') {
+        Some(line) => line.strip_suffix('\r').unwrap_or(line),
+        None => line,
+    }
+}
+
+/// Returns the offset of `pattern` in `content`, whatever the case.
+fn find_ignore_ascii_case(content: &str, pattern: &str) -> Option<usize> {
+    content
+        .as_bytes()
+        .windows(pattern.len())
+        .position(|window| window.eq_ignore_ascii_case(pattern.as_bytes()))
+}
+
+/// Frequency dependent delays (in nanoseconds) and possible [CalibrationID]
+type ParsedDelays = (
+    SmallVec<[(Code, f64); MAX_INLINE_FREQUENCY_DEPENDENT_DELAYS]>,
+    Option<CalibrationID>,
+);
+
+/// Parses frequency dependent delays, described as
+/// "INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021",
+/// whatever the number of carriers and the delay kind.
+/// Unknown carrier codes are ignored.
+fn parse_frequency_dependent_delays(line: &str) -> Result<ParsedDelays, ParsingError> {
+    let offset = line.find('=').ok_or(ParsingError::InvalidFormat)?;
+    let content = &line[offset + 1..];
+
+    let (content, cal_id) = match find_ignore_ascii_case(content, "CAL_ID") {
+        Some(cal_offset) => {
+            let cal_id = &content[cal_offset..];
+            let offset = cal_id.find('=').ok_or(ParsingError::CalibrationFormat)?;
+            (
+                &content[..cal_offset],
+                CalibrationID::from_str(&cal_id[offset + 1..]).ok(),
+            )
+        },
+        None => (content, None),
+    };
+
+    let mut delays = SmallVec::new();
+
+    for item in content.split(',') {
+        let value = match item.split_ascii_whitespace().next() {
+            Some(value) => match f64::from_str(value) {
+                Ok(value) => value,
+                Err(_) => continue,
+            },
+            None => continue,
+        };
+
+        let (start, end) = match (item.find('('), item.find(')')) {
+            (Some(start), Some(end)) if end > start => (start, end),
+            _ => continue,
+        };
+
+        // (GAL E1): code comes last
+        if let Some(code) = item[start + 1..end].split_ascii_whitespace().last() {
+            if let Ok(code) = Code::from_str(code) {
+                delays.push((code, value));
+            }
+        }
+    }
+
+    Ok((delays, cal_id))
+}
+
 impl Header {
     /// Parse [Header] from any [Read]able input.
+    /// NB: in files that omit some of the label lines (that follow CKSUM),
+    /// the first [Track] line is consumed as well.
     pub fn parse<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
-        const CKSUM_PATTERN: &str = "CKSUM = ";
-        const CKSUM_LEN: usize = CKSUM_PATTERN.len();
+        let (header, _, _) = Self::parse_counting_lines(reader)?;
+        Ok(header)
+    }
 
-        let mut lines_iter = reader.lines();
+    /// Parse [Header] from any [Read]able input, and returns
+    /// the number of header lines that were consumed.
+    /// Label lines are expected after CKSUM, but some files omit
+    /// some of them: the first line that is a valid [Track] concludes
+    /// the header section. It is then returned (not counted) and should be
+    /// processed as the first [Track].
+    pub(crate) fn parse_counting_lines<R: Read>(
+        reader: &mut BufReader<R>,
+    ) -> Result<(Self, usize, Option<String>), ParsingError> {
+        // a single line buffer is reused: only the retained fields allocate
+        let mut buf = String::with_capacity(128);
 
         // init variables
-        let mut crc = 0u8;
+        let mut crc = Crc8::new();
         let mut system_delay = SystemDelay::default();
 
         let (mut blank, mut field_labels, mut unit_labels) = (false, false, false);
+        let mut crc_verified = false;
+        let mut first_track = Option::<String>::None;
 
         let mut revision_date = Epoch::default();
         let mut nb_channels: u16 = 0;
 
         let mut receiver = Hardware::default();
-        let mut ims_hardware: Option<Hardware> = None;
+        let mut ims = ImsField::Unknown;
 
         let mut station = String::from("LAB");
 
@@ -83,223 +214,131 @@ impl Header {
         let mut apc_coordinates = Coordinates::default();
 
         let mut reference_time = ReferenceTime::default();
+        let mut extra_fields = Vec::new();
 
         // VERSION must come first
-        let first_line = lines_iter.next().ok_or(ParsingError::VersionFormat)?;
-        let first_line = first_line.map_err(|_| ParsingError::VersionFormat)?;
-        let version = parse_header_version(&first_line)?;
-
-        // calculate first CRC contributions
-        for byte in first_line.as_bytes().iter() {
-            if *byte != b'\r' && *byte != b'\n' {
-                crc = crc.wrapping_add(*byte);
-            }
+        match reader.read_line(&mut buf) {
+            Ok(0) | Err(_) => return Err(ParsingError::VersionFormat),
+            Ok(_) => {},
         }
 
-        for line in lines_iter {
-            if line.is_err() {
-                continue;
+        let first_line = trim_line_ending(&buf);
+        let version = parse_header_version(first_line)?;
+        let mut nb_lines = 1;
+
+        // calculate first CRC contributions
+        crc.update(first_line.as_bytes());
+
+        loop {
+            buf.clear();
+
+            match reader.read_line(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => nb_lines += 1,
+                Err(_) => {
+                    nb_lines += 1;
+                    continue;
+                },
             }
 
-            let line = line.unwrap();
+            let line = trim_line_ending(&buf);
             let line_len = line.len();
 
-            // CRC contribution
-            let crc_max = if line.starts_with(CKSUM_PATTERN) {
-                CKSUM_LEN
-            } else {
-                line_len
+            if crc_verified && Track::from_str(line.trim_end()).is_ok() {
+                // missing label line(s): header section is over
+                first_track = Some(line.to_string());
+                nb_lines -= 1;
+                break;
+            }
+
+            // CRC contribution: up to "CKSUM = " included
+            let crc_max = match strip_key(line, "CKSUM").and(line.find('=')) {
+                Some(offset) if line[offset + 1..].starts_with(' ') => offset + 2,
+                Some(offset) => offset + 1,
+                None => line_len,
             };
 
-            for byte in line.as_bytes()[..crc_max].iter() {
-                if *byte != b'\r' && *byte != b'\n' {
-                    crc = crc.wrapping_add(*byte);
+            crc.update(&line.as_bytes()[..crc_max]);
+
+            if strip_key(line, "REV DATE").is_some() {
+                revision_date = parse_header_date(line)?;
+            } else if let Some(value) = field_value(line, "RCVR") {
+                receiver = parse_hardware(value)?;
+            } else if let Some(value) = field_value(line, "IMS") {
+                ims = if value == INTEGRATED_IMS {
+                    ImsField::Integrated
+                } else {
+                    ImsField::Hardware(parse_hardware(value)?)
+                };
+            } else if let Some(value) = field_value(line, "CH") {
+                nb_channels = value.parse::<u16>().or(Err(ParsingError::ChannelNumber))?;
+            } else if let Some(value) = field_value(line, "LAB") {
+                station = value.to_string();
+            } else if let Some(value) = field_value(line, "X") {
+                apc_coordinates.x = parse_coordinate(value)?;
+            } else if let Some(value) = field_value(line, "Y") {
+                apc_coordinates.y = parse_coordinate(value)?;
+            } else if let Some(value) = field_value(line, "Z") {
+                apc_coordinates.z = parse_coordinate(value)?;
+            } else if let Some(value) = field_value(line, "FRAME") {
+                reference_frame = value.to_string();
+            } else if let Some(value) = field_value(line, "COMMENTS") {
+                if !value.eq_ignore_ascii_case("NO COMMENTS") {
+                    comments = Some(value.to_string());
                 }
-            }
-
-            if line.starts_with("REV DATE = ") {
-                revision_date = parse_header_date(&line)?;
-            } else if line.starts_with("RCVR = ") {
-                receiver = parse_hardware(&line[7..])?;
-            } else if line.starts_with("IMS = ") {
-                ims_hardware = Some(parse_hardware(&line[6..])?);
-            } else if line.starts_with("CH = ") {
-                nb_channels = line[5..]
-                    .trim()
-                    .parse::<u16>()
-                    .or(Err(ParsingError::ChannelNumber))?;
-            } else if line.starts_with("LAB = ") {
-                station = line[5..].trim().to_string();
-            } else if line.starts_with("X = ") {
-                apc_coordinates.x = line[3..line_len - 1]
-                    .trim()
-                    .parse::<f64>()
-                    .or(Err(ParsingError::Coordinates))?;
-            } else if line.starts_with("Y = ") {
-                apc_coordinates.y = line[3..line_len - 1]
-                    .trim()
-                    .parse::<f64>()
-                    .or(Err(ParsingError::Coordinates))?;
-            } else if line.starts_with("Z = ") {
-                apc_coordinates.z = line[3..line_len - 1]
-                    .trim()
-                    .parse::<f64>()
-                    .or(Err(ParsingError::Coordinates))?;
-            } else if line.starts_with("FRAME = ") {
-                reference_frame = line[8..].trim().to_string();
-            } else if line.starts_with("COMMENTS = ") {
-                let c = line.strip_prefix("COMMENTS =").unwrap().trim();
-                if !c.eq("NO COMMENTS") {
-                    comments = Some(c.to_string());
+            } else if let Some(value) = field_value(line, "REF") {
+                reference_time = value.parse::<ReferenceTime>()?;
+            } else if let Some(value) = field_value(line, "CAB DLY") {
+                let (value, unit) = parse_delay(value).ok_or(ParsingError::AntennaCableDelay)?;
+                system_delay.antenna_cable_delay = value;
+                system_delay.missing_unit |= !unit;
+            } else if let Some(value) = field_value(line, "REF DLY") {
+                let (value, unit) = parse_delay(value).ok_or(ParsingError::LocalRefDelay)?;
+                system_delay.local_ref_delay = value;
+                system_delay.missing_unit |= !unit;
+            } else if let Some(kind) = ["INT DLY", "SYS DLY", "TOT DLY"]
+                .iter()
+                .position(|key| field_value(line, key).is_some())
+            {
+                let (delays, cal_id) = parse_frequency_dependent_delays(line)?;
+
+                if let Some(cal_id) = cal_id {
+                    system_delay = system_delay.with_calibration_id(cal_id);
                 }
-            } else if line.starts_with("REF = ") {
-                reference_time = line[5..].trim().parse::<ReferenceTime>()?;
-            } else if line.contains("DLY = ") {
-                let items: Vec<&str> = line.split_ascii_whitespace().collect();
-
-                let dual_carrier = line.contains(',');
 
-                if items.len() < 4 {
-                    continue; // format mismatch
+                for (code, value) in delays {
+                    let delay = match kind {
+                        0 => Delay::Internal(value),
+                        1 => Delay::System(value),
+                        _ => Delay::Total(value),
+                    };
+                    system_delay.freq_dependent_delays.push((code, delay));
                 }
-
-                match items[0] {
-                    "CAB" => {
-                        system_delay.antenna_cable_delay = items[3]
-                            .trim()
-                            .parse::<f64>()
-                            .or(Err(ParsingError::AntennaCableDelay))?;
-                    },
-                    "REF" => {
-                        system_delay.local_ref_delay = items[3]
-                            .trim()
-                            .parse::<f64>()
-                            .or(Err(ParsingError::LocalRefDelay))?;
-                    },
-                    "SYS" => {
-                        if line.contains("CAL_ID") {
-                            let offset = line.rfind('=').ok_or(ParsingError::CalibrationFormat)?;
-
-                            if let Ok(cal_id) = CalibrationID::from_str(&line[offset + 1..]) {
-                                system_delay = system_delay.with_calibration_id(cal_id);
-                            }
-                        }
-
-                        if dual_carrier {
-                            if let Ok(value) = f64::from_str(items[3]) {
-                                let code = items[6].replace("),", "");
-                                if let Ok(code) = Code::from_str(&code) {
-                                    system_delay
-                                        .freq_dependent_delays
-                                        .push((code, Delay::System(value)));
-                                }
-                            }
-                            if let Ok(value) = f64::from_str(items[7]) {
-                                let code = items[9].replace(')', "");
-                                if let Ok(code) = Code::from_str(&code) {
-                                    system_delay
-                                        .freq_dependent_delays
-                                        .push((code, Delay::System(value)));
-                                }
-                            }
-                        } else {
-                            let value = f64::from_str(items[3]).unwrap();
-                            let code = items[6].replace(')', "");
-                            if let Ok(code) = Code::from_str(&code) {
-                                system_delay
-                                    .freq_dependent_delays
-                                    .push((code, Delay::System(value)));
-                            }
-                        }
-                    },
-                    "INT" => {
-                        if line.contains("CAL_ID") {
-                            let offset = line.rfind('=').ok_or(ParsingError::CalibrationFormat)?;
-
-                            if let Ok(cal_id) = CalibrationID::from_str(&line[offset + 1..]) {
-                                system_delay = system_delay.with_calibration_id(cal_id);
-                            }
-                        }
-
-                        if dual_carrier {
-                            if let Ok(value) = f64::from_str(items[3]) {
-                                let code = items[6].replace("),", "");
-                                if let Ok(code) = Code::from_str(&code) {
-                                    system_delay
-                                        .freq_dependent_delays
-                                        .push((code, Delay::Internal(value)));
-                                }
-                            }
-                            if let Ok(value) = f64::from_str(items[7]) {
-                                let code = items[10].replace(')', "");
-                                if let Ok(code) = Code::from_str(&code) {
-                                    system_delay
-                                        .freq_dependent_delays
-                                        .push((code, Delay::Internal(value)));
-                                }
-                            }
-                        } else if let Ok(value) = f64::from_str(items[3]) {
-                            let code = items[6].replace(')', "");
-                            if let Ok(code) = Code::from_str(&code) {
-                                system_delay
-                                    .freq_dependent_delays
-                                    .push((code, Delay::Internal(value)));
-                            }
-                        }
-                    },
-                    "TOT" => {
-                        if line.contains("CAL_ID") {
-                            let offset = line.rfind('=').ok_or(ParsingError::CalibrationFormat)?;
-
-                            if let Ok(cal_id) = CalibrationID::from_str(&line[offset + 1..]) {
-                                system_delay = system_delay.with_calibration_id(cal_id);
-                            }
-                        }
-
-                        if dual_carrier {
-                            if let Ok(value) = f64::from_str(items[3]) {
-                                let code = items[6].replace("),", "");
-                                if let Ok(code) = Code::from_str(&code) {
-                                    system_delay
-                                        .freq_dependent_delays
-                                        .push((code, Delay::System(value)));
-                                }
-                            }
-                            if let Ok(value) = f64::from_str(items[7]) {
-                                let code = items[9].replace(')', "");
-                                if let Ok(code) = Code::from_str(&code) {
-                                    system_delay
-                                        .freq_dependent_delays
-                                        .push((code, Delay::System(value)));
-                                }
-                            }
-                        } else if let Ok(value) = f64::from_str(items[3]) {
-                            let code = items[6].replace(')', "");
-                            if let Ok(code) = Code::from_str(&code) {
-                                system_delay
-                                    .freq_dependent_delays
-                                    .push((code, Delay::System(value)));
-                            }
-                        }
-                    },
-                    _ => {}, // non recognized delay type
-                };
-            } else if line.starts_with("CKSUM = ") {
+            } else if let Some(rest) = strip_key(line, "CKSUM") {
                 // CRC verification
-                let value = match scan_fmt!(&line, "CKSUM = {x}", String) {
-                    Some(s) => match u8::from_str_radix(&s, 16) {
-                        Ok(hex) => hex,
-                        _ => return Err(ParsingError::ChecksumParsing),
-                    },
-                    _ => return Err(ParsingError::ChecksumFormat),
-                };
+                let value = rest
+                    .trim_start()
+                    .strip_prefix('=')
+                    .and_then(|value| value.split_ascii_whitespace().next())
+                    .ok_or(ParsingError::ChecksumFormat)?;
+
+                let value = u8::from_str_radix(value, 16).or(Err(ParsingError::ChecksumParsing))?;
 
-                if value != crc {
+                if value != crc.finalize() {
                     return Err(ParsingError::ChecksumValue);
                 }
 
                 // CKSUM initiates the end of header section
                 blank = true;
+                crc_verified = true;
+            } else if !crc_verified {
+                // non standard field, contributes to the CRC as well
+                if let Some((key, value)) = line.split_once('=') {
+                    let key = key.trim();
+                    if !key.is_empty() {
+                        extra_fields.push((key.to_string(), value.trim().to_string()));
+                    }
+                }
             } else if blank {
                 // Field labels expected next
                 blank = false;
@@ -314,27 +353,68 @@ impl Header {
             }
         }
 
-        Ok(Self {
-            version,
-            revision_date,
-            nb_channels,
-            receiver,
-            ims_hardware,
-            station,
-            reference_frame,
-            apc_coordinates,
-            comments,
-            delay: system_delay,
-            reference_time,
-        })
+        if !crc_verified {
+            return Err(ParsingError::CrcMissing);
+        }
+
+        Ok((
+            Self {
+                version,
+                revision_date,
+                nb_channels,
+                receiver,
+                ims,
+                station,
+                reference_frame,
+                apc_coordinates,
+                comments,
+                delay: system_delay,
+                reference_time,
+                constellation: None,
+                nominal_mjd: None,
+                extra_fields,
+                format_extra_fields: true,
+            },
+            nb_lines,
+            first_track,
+        ))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{parse_hardware, parse_header_date, parse_header_version};
-    use crate::prelude::Version;
+    use super::{
+        parse_frequency_dependent_delays, parse_hardware, parse_header_date, parse_header_version,
+    };
+    use crate::{
+        buffer::Utf8Buffer,
+        crc::Crc8,
+        errors::ParsingError,
+        prelude::{
+            Code, Delay, DelayKind, Hardware, Header, ImsField, ReferenceTime, Version, CGGTTS,
+        },
+        validation::WarningKind,
+    };
     use hifitime::Epoch;
+    use std::io::{BufReader, BufWriter};
+
+    /// Header that uses TOT DLY
+    const TOT_DLY_HEADER: &str = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+REV DATE = 2023-06-27
+RCVR = GTR51 2204005 1.12.0 0 
+CH = 20
+LAB = LAB
+X =  3970727.800 m
+Y =  1018888.020 m
+Z =  4870276.840 m
+FRAME = FRAME
+COMMENTS = NO COMMENTS
+TOT DLY =  440.5 ns (GPS C1), 450.0 ns (GPS P2)     CAL_ID = 1015-2021
+CAB DLY = 237.0 ns
+REF DLY = 149.6 ns
+REF = REF_IN
+CKSUM = DB
+";
 
     #[test]
     fn version_parsing() {
@@ -369,4 +449,361 @@ mod test {
             assert_eq!(parsed.serial_number, serial);
         }
     }
+
+    #[test]
+    fn frequency_dependent_delays_parsing() {
+        for (content, delays, cal_id) in [
+            (
+                "INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021",
+                vec![(Code::E1, 34.6), (Code::E5, 0.0)],
+                Some("1015-2021"),
+            ),
+            (
+                "SYS DLY =  290.9 ns (GLO C1)     CAL_ID = 1015-2021-OP",
+                vec![(Code::C1, 290.9)],
+                Some("1015-2021-OP"),
+            ),
+            (
+                "SYS DLY =  290.9 ns (GLO C1)     CAL_ID = NA",
+                vec![(Code::C1, 290.9)],
+                None,
+            ),
+            (
+                "TOT DLY =  440.5 ns (GPS C1),  450.0 ns (GPS P2),   0.0 ns (GPS L5)",
+                vec![(Code::C1, 440.5), (Code::P2, 450.0)],
+                None,
+            ),
+        ] {
+            let (parsed, parsed_cal_id) = parse_frequency_dependent_delays(content).unwrap();
+            assert_eq!(&parsed[..], &delays[..]);
+            assert_eq!(
+                parsed_cal_id.map(|cal| cal.to_string()),
+                cal_id.map(String::from)
+            );
+        }
+    }
+
+    #[test]
+    fn total_delay_parsing() {
+        let mut reader = BufReader::new(TOT_DLY_HEADER.as_bytes());
+        let header = Header::parse(&mut reader).unwrap();
+
+        // no allocation for so few carriers
+        assert!(!header.delay.freq_dependent_delays.spilled());
+
+        assert_eq!(header.delay.kind(), Some(DelayKind::Total));
+        assert_eq!(header.delay.antenna_cable_delay, 237.0);
+        assert_eq!(header.delay.local_ref_delay, 149.6);
+
+        assert_eq!(
+            header.delay.freq_dependent_delays[..],
+            [
+                (Code::C1, Delay::Total(440.5)),
+                (Code::P2, Delay::Total(450.0))
+            ]
+        );
+
+        let cal_id = header.delay.calibration_id.as_ref().unwrap();
+        assert_eq!(cal_id.process_id, 1015);
+        assert_eq!(cal_id.year, 2021);
+
+        // totals are not affected by cable delays
+        assert_eq!(
+            header
+                .delay
+                .total_frequency_dependent_delay_nanos(&Code::C1),
+            Some(440.5)
+        );
+
+        // reconstructed internal delays
+        let internal = header.delay.to_internal_delays();
+        let (_, c1) = internal.freq_dependent_delays[0];
+        assert!((c1.total_nanoseconds() - 53.9).abs() < 1.0E-9);
+
+        // format back: unchanged
+        let mut buf = Utf8Buffer::new(1024);
+        let mut writer = BufWriter::new(Utf8Buffer::new(1024));
+
+        header.format(&mut writer, &mut buf).unwrap();
+
+        let inner = writer.into_inner().unwrap_or_else(|_| panic!("oops"));
+        let ascii_utf8 = inner.to_utf8_ascii().unwrap();
+        assert_eq!(ascii_utf8, TOT_DLY_HEADER);
+    }
+
+    #[test]
+    fn whitespace_tolerance() {
+        let body = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+REV  DATE\t=\t2023-06-27
+RCVR  =  GTR51\t2204005   1.12.0 2022\t2.0
+CH\t= 20
+IMS =\tGTR51 2204005 1.12.0 2022 2.0
+LAB =  LAB
+X\t=\t+3970727.80\tm
+Y  =  +1018888.02  m
+Z = +4870276.84 m
+FRAME\t= ITRF
+COMMENTS  =  NO COMMENTS
+TOT  DLY =\t440.5 ns (GPS C1),  450.0 ns (GPS P2)\tCAL_ID = 1015-2021
+CAB\tDLY =  237.0 ns
+REF DLY\t=\t149.6\tns
+REF  =  UTC(LAB)
+";
+
+        let mut crc = Crc8::new();
+        for line in body.lines() {
+            crc.update(line.as_bytes());
+        }
+        crc.update("CKSUM\t= ".as_bytes());
+
+        let content = format!("{}CKSUM\t= {:02X}\n", body, crc.finalize());
+
+        let mut reader = BufReader::new(content.as_bytes());
+        let header = Header::parse(&mut reader).unwrap();
+
+        assert_eq!(header.version, Version::Version2E);
+        assert_eq!(
+            header.revision_date,
+            Epoch::from_gregorian_utc_at_midnight(2023, 6, 27)
+        );
+        assert_eq!(header.receiver.manufacturer, "GTR51");
+        assert_eq!(header.receiver.model, "2204005");
+        assert_eq!(header.receiver.serial_number, "1.12.0");
+        assert_eq!(header.receiver.year, 2022);
+        assert_eq!(header.receiver.release, "2.0");
+        assert_eq!(header.nb_channels, 20);
+        assert_eq!(header.ims_hardware(), Some(&header.receiver));
+        assert_eq!(header.station, "LAB");
+        assert_eq!(header.apc_coordinates.x, 3970727.80);
+        assert_eq!(header.apc_coordinates.y, 1018888.02);
+        assert_eq!(header.apc_coordinates.z, 4870276.84);
+        assert_eq!(header.reference_frame, "ITRF");
+        assert_eq!(header.comments, None);
+        assert_eq!(header.delay.antenna_cable_delay, 237.0);
+        assert_eq!(header.delay.local_ref_delay, 149.6);
+        assert_eq!(
+            header.delay.freq_dependent_delays[..],
+            [
+                (Code::C1, Delay::Total(440.5)),
+                (Code::P2, Delay::Total(450.0))
+            ]
+        );
+        assert_eq!(
+            header.reference_time,
+            ReferenceTime::UTCk("LAB".to_string())
+        );
+    }
+
+    #[test]
+    fn unitless_cable_delays() {
+        let parse = |cab: &str, rf: &str| {
+            let body = TOT_DLY_HEADER
+                .replace("CAB DLY = 237.0 ns", cab)
+                .replace("REF DLY = 149.6 ns", rf);
+
+            let body = &body[..body.find("CKSUM").unwrap()];
+
+            let mut crc = Crc8::new();
+            for line in body.lines() {
+                crc.update(line.as_bytes());
+            }
+            crc.update("CKSUM = ".as_bytes());
+
+            let content = format!("{}CKSUM = {:02X}\n", body, crc.finalize());
+            let mut reader = BufReader::new(content.as_bytes());
+            Header::parse(&mut reader).unwrap()
+        };
+
+        let standard = parse("CAB DLY = 237.0 ns", "REF DLY = 149.6 ns");
+        assert!(!standard.delay.missing_unit);
+
+        for (cab, rf) in [
+            ("CAB DLY = 237.0", "REF DLY = 149.6 ns"),
+            ("CAB DLY = 237.0 ns", "REF DLY = 149.6"),
+            ("CAB DLY = 237.0", "REF DLY = 149.6"),
+            ("CAB DLY =  237.0  ", "REF DLY =\t149.6"),
+        ] {
+            let header = parse(cab, rf);
+            assert_eq!(header.delay.antenna_cable_delay, 237.0);
+            assert_eq!(header.delay.local_ref_delay, 149.6);
+            assert!(header.delay.missing_unit, "{} / {}", cab, rf);
+
+            let cggtts = CGGTTS::default().with_header(header);
+            assert!(cggtts
+                .validate()
+                .iter()
+                .any(|w| w.kind == WarningKind::MissingDelayUnit));
+        }
+
+        let cggtts = CGGTTS::default().with_header(standard);
+        assert!(cggtts
+            .validate()
+            .iter()
+            .all(|w| w.kind != WarningKind::MissingDelayUnit));
+    }
+
+    #[test]
+    fn extra_fields() {
+        let body = TOT_DLY_HEADER.replace(
+            "REF = REF_IN\n",
+            "REF = REF_IN\nRINEX CLOCK = CLK_IN\nPPS OFFSET = 12.5 ns\n",
+        );
+
+        let body = &body[..body.find("CKSUM").unwrap()];
+
+        let mut crc = Crc8::new();
+        for line in body.lines() {
+            crc.update(line.as_bytes());
+        }
+        crc.update("CKSUM = ".as_bytes());
+
+        let content = format!("{}CKSUM = {:02X}\n", body, crc.finalize());
+
+        let mut reader = BufReader::new(content.as_bytes());
+        let header = Header::parse(&mut reader).unwrap();
+
+        assert_eq!(
+            header.extra_fields,
+            vec![
+                ("RINEX CLOCK".to_string(), "CLK_IN".to_string()),
+                ("PPS OFFSET".to_string(), "12.5 ns".to_string()),
+            ]
+        );
+
+        let format = |header: &Header| {
+            let mut buf = Utf8Buffer::new(1024);
+            let mut writer = BufWriter::new(Utf8Buffer::new(1024));
+            header.format(&mut writer, &mut buf).unwrap();
+            let inner = writer.into_inner().unwrap_or_else(|_| panic!("oops"));
+            inner.to_utf8_ascii().unwrap().to_string()
+        };
+
+        // round trip: preserved, with valid checksum
+        let formatted = format(&header);
+        assert_eq!(formatted, content);
+
+        let mut reader = BufReader::new(formatted.as_bytes());
+        let parsed = Header::parse(&mut reader).unwrap();
+        assert_eq!(parsed, header);
+
+        // standard fields only
+        let standard = format(&header.clone().with_extra_fields_formatting(false));
+        assert_eq!(standard, TOT_DLY_HEADER);
+
+        let mut reader = BufReader::new(standard.as_bytes());
+        let parsed = Header::parse(&mut reader).unwrap();
+        assert!(parsed.extra_fields.is_empty());
+    }
+
+    #[test]
+    fn ims_field() {
+        let format = |header: &Header| {
+            let mut buf = Utf8Buffer::new(1024);
+            let mut writer = BufWriter::new(Utf8Buffer::new(1024));
+            header.format(&mut writer, &mut buf).unwrap();
+            let inner = writer.into_inner().unwrap_or_else(|_| panic!("oops"));
+            inner.to_utf8_ascii().unwrap().to_string()
+        };
+
+        let hardware = Hardware::default()
+            .with_manufacturer("GTR51")
+            .with_model("2204005")
+            .with_serial_number("1.12.0")
+            .with_release_year(2022)
+            .with_release_version("2.0");
+
+        for (ims_line, expected) in [
+            ("", ImsField::Unknown),
+            ("IMS = 99999\n", ImsField::Integrated),
+            (
+                "IMS = GTR51 2204005 1.12.0 2022 2.0\n",
+                ImsField::Hardware(hardware.clone()),
+            ),
+        ] {
+            let body = TOT_DLY_HEADER.replace("CH = 20\n", &format!("CH = 20\n{}", ims_line));
+            let body = &body[..body.find("CKSUM").unwrap()];
+
+            let mut crc = Crc8::new();
+            for line in body.lines() {
+                crc.update(line.as_bytes());
+            }
+            crc.update("CKSUM = ".as_bytes());
+
+            let content = format!("{}CKSUM = {:02X}\n", body, crc.finalize());
+
+            let mut reader = BufReader::new(content.as_bytes());
+            let header = Header::parse(&mut reader).unwrap();
+
+            assert_eq!(header.ims, expected);
+            assert_eq!(header.ims_hardware(), expected.hardware());
+
+            // formatted back exactly
+            assert_eq!(format(&header), content);
+        }
+
+        // builders
+        let header = Header::default().with_integrated_ims();
+        assert!(format(&header).contains("\nIMS = 99999\n"));
+        assert!(header.ims_hardware().is_none());
+
+        let header = Header::default().with_ims_hardware(hardware.clone());
+        assert!(format(&header).contains("\nIMS = GTR51 2204005 1.12.0 2022 2.0\n"));
+        assert_eq!(header.ims_hardware(), Some(&hardware));
+
+        assert!(!format(&Header::default()).contains("IMS"));
+    }
+
+    #[test]
+    fn short_header_lines() {
+        for (short, expected) in [
+            ("X =", ParsingError::Coordinates),
+            ("Y = ", ParsingError::Coordinates),
+            ("Z = m", ParsingError::Coordinates),
+            ("CH =", ParsingError::ChannelNumber),
+            ("REV DATE =", ParsingError::RevisionDateFormat),
+            ("REV DATE", ParsingError::RevisionDateFormat),
+        ] {
+            let content = format!(
+                "CGGTTS     GENERIC DATA FORMAT VERSION = 2E\n{}\nCKSUM = 00\n",
+                short
+            );
+
+            let mut reader = BufReader::new(content.as_bytes());
+            let error = Header::parse(&mut reader).err().unwrap();
+
+            assert_eq!(
+                std::mem::discriminant(&error),
+                std::mem::discriminant(&expected),
+                "\"{}\": unexpected {}",
+                short,
+                error
+            );
+        }
+
+        // corpus: truncate each line of a valid header at each possible length
+        let lines = TOT_DLY_HEADER.lines().collect::<Vec<_>>();
+
+        for (nth, line) in lines.iter().enumerate() {
+            for len in 0..line.len() {
+                let mut truncated = lines.clone();
+                truncated[nth] = &line[..len];
+
+                let content = truncated.join("\n");
+                let mut reader = BufReader::new(content.as_bytes());
+
+                // 8 bit checksum cannot detect removed bytes that sum to zero
+                let mut removed = Crc8::new();
+                removed.update(&line.as_bytes()[len..]);
+
+                if Header::parse(&mut reader).is_ok() {
+                    assert_eq!(
+                        removed.finalize(),
+                        0,
+                        "\"{}\" should not be accepted",
+                        &line[..len]
+                    );
+                }
+            }
+        }
+    }
 }
@@ -14,7 +14,17 @@ impl std::str::FromStr for Version {
     type Err = ParsingError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.eq("2E") {
-            Ok(Self::Version2E)
+            return Ok(Self::Version2E);
+        }
+
+        // revisions are a digit followed by an uppercase letter,
+        // which allows telling newer revisions from malformed content
+        let bytes = s.as_bytes();
+        let is_revision =
+            bytes.len() == 2 && bytes[0].is_ascii_digit() && bytes[1].is_ascii_uppercase();
+
+        if is_revision && s > "2E" {
+            Err(ParsingError::NewerVersionUnsupported(s.to_string()))
         } else {
             Err(ParsingError::NonSupportedRevision)
         }
@@ -31,7 +41,7 @@ impl std::fmt::Display for Version {
 
 #[cfg(test)]
 mod test {
-    use crate::prelude::Version;
+    use crate::{errors::ParsingError, prelude::Version};
     use std::str::FromStr;
 
     #[test]
@@ -39,4 +49,21 @@ mod test {
         let version_2e = Version::from_str("2E").unwrap();
         assert_eq!(version_2e.to_string(), "2E");
     }
+
+    #[test]
+    fn newer_versions() {
+        for newer in ["2F", "2Z", "3A", "9E"] {
+            match Version::from_str(newer) {
+                Err(ParsingError::NewerVersionUnsupported(version)) => assert_eq!(version, newer),
+                other => panic!("{}: unexpected {:?}", newer, other),
+            }
+        }
+
+        for other in ["2A", "1E", "2e", "3", "3AA", "", "AB"] {
+            assert!(matches!(
+                Version::from_str(other),
+                Err(ParsingError::NonSupportedRevision)
+            ));
+        }
+    }
 }
@@ -14,9 +14,10 @@
 extern crate gnss_rs as gnss;
 
 use gnss::prelude::{Constellation, SV};
-use hifitime::{Duration, Epoch, TimeScale};
+use hifitime::{Duration, Epoch, TimeScale, Unit};
 
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::{BufRead, BufReader, BufWriter, Read, Write},
     path::Path,
@@ -26,7 +27,27 @@ use std::{
 #[cfg(feature = "flate2")]
 use flate2::{read::GzDecoder, write::GzEncoder, Compression as GzCompression};
 
+#[cfg(feature = "memmap2")]
+use memmap2::Mmap;
+
+#[cfg(any(feature = "memmap2", feature = "tokio"))]
+use std::io::Cursor;
+
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader as AsyncBufReader};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+
+#[cfg(feature = "scheduler")]
+use crate::{
+    errors::CalendarError,
+    prelude::{CommonViewCalendar, CommonViewPeriod},
+};
+
 mod header;
+mod merge;
+mod progress;
 
 #[cfg(feature = "scheduler")]
 #[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
@@ -39,9 +60,25 @@ mod tracker;
 #[cfg(test)]
 mod tests;
 
+#[cfg(any(test, feature = "test-utils"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-utils")))]
+pub mod testing;
+
+#[cfg(any(test, feature = "proptest"))]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod strategies;
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod serde_support;
+
 pub mod buffer;
+pub mod crc;
 pub mod errors;
+pub mod options;
+pub mod time_transfer;
 pub mod track;
+pub mod validation;
 
 #[cfg(feature = "serde")]
 #[macro_use]
@@ -51,7 +88,16 @@ pub mod prelude {
 
     pub use crate::{
         header::*,
-        track::{CommonViewClass, IonosphericData, Track, TrackData},
+        options::ParseOptions,
+        time_transfer::{ClockDifference, TimeTransferOptions, Weighting},
+        track::{
+            CarrierCode, CommonViewClass, EphemerisData, IoeKind, IonosphericData, Track,
+            TrackBuildError, TrackBuilder, TrackData,
+        },
+        validation::{
+            DelayCoverageReport, Severity, ValidationIssue, ValidationReport, ValidationWarning,
+            WarningKind,
+        },
         CGGTTS,
     };
 
@@ -59,7 +105,10 @@ pub mod prelude {
     pub use crate::scheduler::{calendar::CommonViewCalendar, period::CommonViewPeriod};
 
     #[cfg(feature = "tracker")]
-    pub use crate::tracker::{FitError, FittedData, Observation, SVTracker, SkyTracker};
+    pub use crate::tracker::{
+        FitError, FittedData, HatchFilter, MovingAverage, Observation, SVTracker, SkyTracker,
+        Smoother, SvTrackingStatus,
+    };
 
     // pub re-export
     pub use gnss::prelude::{Constellation, SV};
@@ -71,11 +120,83 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     buffer::Utf8Buffer,
-    errors::{FormattingError, ParsingError},
-    header::{Header, ReferenceTime},
-    track::{CommonViewClass, Track},
+    errors::{FormattingError, ParsingError, StateError},
+    header::{Code, Delay, Header, ReferenceTime},
+    options::ParseOptions,
+    progress::{CountingReader, DEFAULT_PROGRESS_INTERVAL},
+    track::{CarrierCode, CommonViewClass, Error as TrackError, IonosphericData, Track, TrackData},
 };
 
+/// Shortest [Track] line (without ionospheric data), including line termination.
+const MIN_TRACK_LINE_LEN: usize = 113;
+
+/// Estimates the maximal number of [Track]s, from the total file size.
+fn estimated_nb_tracks(size: usize) -> usize {
+    size / MIN_TRACK_LINE_LEN
+}
+
+/// Returns the [Duration] elapsed from `t0` to `t1`, both expressed
+/// in [TimeScale::TAI] beforehand, whatever their [TimeScale].
+fn elapsed(t0: Epoch, t1: Epoch) -> Duration {
+    t1.to_time_scale(TimeScale::TAI) - t0.to_time_scale(TimeScale::TAI)
+}
+
+/// Comment lines inserted between concatenated days start with this marker.
+const DAY_SEPARATOR: char = '#';
+
+/// Returns true if this line separates two concatenated days.
+fn is_day_separator(line: &str) -> bool {
+    line.trim_start().starts_with(DAY_SEPARATOR)
+}
+
+/// Returns true if this line is a field or unit label row, that some legacy
+/// concatenation tools repeat after the last [Track] (as a footer).
+fn is_label_row(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("SAT CL") || line.starts_with("hhmmss")
+}
+
+/// Returns true if this line is an end of file marker, like "EOF"
+/// or "*** END OF FILE ***", that some tools append after the last [Track].
+fn is_eof_marker(line: &str) -> bool {
+    let marker =
+        line.trim_matches(|c: char| c.is_ascii_whitespace() || matches!(c, '*' | '-' | '=' | '#'));
+    ["EOF", "END", "END OF FILE", "END OF DATA"]
+        .iter()
+        .any(|pattern| marker.eq_ignore_ascii_case(pattern))
+}
+
+/// Returns true if this line is a known footer line, only
+/// tolerated after the last [Track].
+fn is_footer_line(line: &str) -> bool {
+    is_label_row(line) || is_eof_marker(line)
+}
+
+/// Parses many local files in parallel, refer to [CGGTTS::from_file].
+/// Each file is parsed independently, results are returned in the
+/// order of `paths`. This is convenient when reprocessing archives.
+/// ```
+/// use cggtts::{parse_many, prelude::*};
+///
+/// let paths = ["/tmp/parse_many_1.txt", "/tmp/parse_many_2.txt"];
+///
+/// for (path, station) in paths.iter().zip(["LAB1", "LAB2"]) {
+///     CGGTTS::default()
+///         .with_header(Header::default().with_station(station))
+///         .to_file(path)
+///         .unwrap();
+/// }
+///
+/// let results = parse_many(&paths);
+/// assert_eq!(results.len(), 2);
+/// assert_eq!(results[1].as_ref().unwrap().header.station, "LAB2");
+/// ```
+#[cfg(feature = "rayon")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rayon")))]
+pub fn parse_many<P: AsRef<Path> + Sync>(paths: &[P]) -> Vec<Result<CGGTTS, ParsingError>> {
+    paths.par_iter().map(CGGTTS::from_file).collect()
+}
+
 /// [CGGTTS] is a structure split in two:
 /// - the [Header] section gives general information
 /// about the measurement system and context
@@ -97,6 +218,17 @@ pub struct CGGTTS {
     /// [Track]s describe the result of track fitting,
     /// in chronological order.
     pub tracks: Vec<Track>,
+    /// Lines that could not be interpreted as [Track]s, with their line
+    /// number (starting at 1). Only preserved when parsing with
+    /// [ParseOptions::preserve_unknown_lines], never formatted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub unknown_lines: Vec<(usize, String)>,
+    /// [Epoch] of the first [Track] of each new day, when several
+    /// days were concatenated (see [Self::append_day]).
+    /// A separator comment is formatted ahead of each boundary.
+    /// Only preserved when parsing with [ParseOptions::preserve_day_separators].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub day_boundaries: Vec<Epoch>,
 }
 
 impl CGGTTS {
@@ -114,8 +246,86 @@ impl CGGTTS {
         true
     }
 
+    /// Returns true if all [Track]s (measurements) seem compatible
+    /// with this custom [CommonViewPeriod], refer to [Track::follows_tracking].
+    #[cfg(feature = "scheduler")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
+    pub fn follows_tracking(&self, period: &CommonViewPeriod) -> bool {
+        self.tracks.iter().all(|trk| trk.follows_tracking(period))
+    }
+
+    /// Moves each [Track] that starts within `tolerance` of a [CommonViewPeriod]
+    /// start of this [CommonViewCalendar], onto that exact start.
+    /// This is useful when third-party software produced [Track]s slightly
+    /// off the calendar (for example 14:46:02 instead of 14:46:00), which
+    /// prevents exact [Epoch] matching in common view.
+    /// ## Returns
+    /// - number of adjusted [Track]s
+    /// - [CalendarError::TrackCollision] if two [Track]s of the same [SV]
+    ///   would start at the same [Epoch]. [Self] is not modified in that case.
+    #[cfg(feature = "scheduler")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
+    pub fn snap_to_calendar(
+        &mut self,
+        calendar: &CommonViewCalendar,
+        tolerance: Duration,
+    ) -> Result<usize, CalendarError> {
+        let snapped = self
+            .tracks
+            .iter()
+            .map(|trk| {
+                // first period start within tolerance (if any)
+                let lower = trk.epoch - tolerance - 1.0 * Unit::Nanosecond;
+                let start = calendar.next_period_start_after(lower);
+                if (start - trk.epoch).abs() <= tolerance {
+                    start
+                } else {
+                    trk.epoch
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // distinct tracks should not collapse onto the same slot
+        let mut slots = BTreeMap::<(SV, Epoch), Epoch>::new();
+
+        for (trk, epoch) in self.tracks.iter().zip(snapped.iter()) {
+            if let Some(prev) = slots.insert((trk.sv, *epoch), trk.epoch) {
+                if prev != trk.epoch {
+                    return Err(CalendarError::TrackCollision(trk.sv, *epoch));
+                }
+            }
+        }
+
+        let mut adjusted = 0;
+
+        for (trk, epoch) in self.tracks.iter_mut().zip(snapped) {
+            if trk.epoch != epoch {
+                trk.epoch = epoch;
+                adjusted += 1;
+            }
+        }
+
+        Ok(adjusted)
+    }
+
+    /// Returns true if this [CGGTTS] does not contain any [Track].
+    /// A header only file is valid and parsed by default, but the methods
+    /// describing the [Track]s then fall back to:
+    /// - None for [Self::first_epoch], [Self::last_epoch], [Self::first_sv]
+    ///   and [Self::last_sv]
+    /// - [Duration::ZERO] for [Self::total_duration] and [Self::span]
+    /// - [CommonViewClass::SingleChannel] for [Self::common_view_class]
+    /// - true for [Self::has_ionospheric_data]
+    /// - the [Header] declarations, or placeholders, for [Self::standardized_file_name]
+    ///
+    /// Use [ParseOptions::with_empty_rejection] to reject such files instead.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
     /// Returns true if all tracks (measurements) contained in this
     /// [CGGTTS] have ionospheric parameters estimate.
+    /// This is also true when [Self::is_empty].
     pub fn has_ionospheric_data(&self) -> bool {
         for track in self.tracks.iter() {
             if !track.has_ionospheric_data() {
@@ -130,7 +340,7 @@ impl CGGTTS {
     /// - [CommonViewClass::MultiChannel] if at least one track (measurement)
     /// is [CommonViewClass::MultiChannel] measurement
     /// - [CommonViewClass::SingleChannel] if all tracks (measurements)
-    /// are [CommonViewClass::SingleChannel] measurements
+    /// are [CommonViewClass::SingleChannel] measurements, or [Self::is_empty]
     pub fn common_view_class(&self) -> CommonViewClass {
         for trk in self.tracks.iter() {
             if trk.class != CommonViewClass::SingleChannel {
@@ -140,6 +350,40 @@ impl CGGTTS {
         CommonViewClass::SingleChannel
     }
 
+    /// Defines [Header::nb_channels] from the maximal number of [Track]s
+    /// sharing the same [Epoch], which is the minimal number of channels
+    /// the receiver used. Always at least 1, even when [Self::is_empty].
+    /// Use this when building [CGGTTS] from multi channel tracks, to
+    /// remain consistent with [CommonViewClass::MultiChannel].
+    pub fn infer_channels(&mut self) {
+        let mut epochs = BTreeMap::<Epoch, u16>::new();
+
+        for trk in self.tracks.iter() {
+            *epochs.entry(trk.epoch).or_default() += 1;
+        }
+
+        let max = epochs.values().copied().max().unwrap_or_default();
+        self.header.nb_channels = max.max(1);
+    }
+
+    /// Returns [CommonViewClass] used by this [Constellation] only.
+    /// This is useful when working with leniently parsed, multi constellation data.
+    /// ## Returns
+    /// - None if this [Constellation] was not tracked at all
+    /// - [CommonViewClass::MultiChannel] if at least one track (measurement)
+    ///   of this [Constellation] is [CommonViewClass::MultiChannel] measurement
+    /// - [CommonViewClass::SingleChannel] otherwise
+    pub fn common_view_class_of(&self, c: Constellation) -> Option<CommonViewClass> {
+        let mut class = Option::<CommonViewClass>::None;
+        for trk in self.tracks.iter().filter(|trk| trk.sv.constellation == c) {
+            if trk.class != CommonViewClass::SingleChannel {
+                return Some(CommonViewClass::MultiChannel);
+            }
+            class = Some(CommonViewClass::SingleChannel);
+        }
+        class
+    }
+
     /// Returns true if this [CGGTTS] is a single channel [CGGTTS],
     /// meaning, all tracks (measurements) are [CommonViewClass::SingleChannel] measurements
     pub fn single_channel(&self) -> bool {
@@ -227,6 +471,29 @@ impl CGGTTS {
         self.tracks.iter()
     }
 
+    /// Iterates over groups of [Track]s sharing the same [Epoch]
+    /// (common view slot, STTIME), in order of appearance.
+    /// NB: only consecutive [Track]s are grouped together, this
+    /// requires [Track]s to be sorted in chronological order
+    /// (which is the case of any valid CGGTTS), see [Self::sort_tracks].
+    /// Use [Self::grouped_by_epoch] otherwise.
+    pub fn epoch_groups(&self) -> impl Iterator<Item = (Epoch, &[Track])> {
+        self.tracks
+            .chunk_by(|a, b| a.epoch == b.epoch)
+            .map(|group| (group[0].epoch, group))
+    }
+
+    /// Groups [Track]s by [Epoch] (common view slot, STTIME),
+    /// regardless of their ordering. Unlike [Self::epoch_groups],
+    /// this does not require sorted [Track]s.
+    pub fn grouped_by_epoch(&self) -> BTreeMap<Epoch, Vec<&Track>> {
+        let mut groups = BTreeMap::<Epoch, Vec<&Track>>::new();
+        for trk in self.tracks.iter() {
+            groups.entry(trk.epoch).or_default().push(trk);
+        }
+        groups
+    }
+
     /// Iterate over [Track]s (measurements) that result from tracking
     /// this particular [SV] only.
     pub fn sv_tracks(&self, sv: SV) -> impl Iterator<Item = &Track> {
@@ -235,31 +502,358 @@ impl CGGTTS {
             .filter_map(move |trk| if trk.sv == sv { Some(trk) } else { None })
     }
 
-    /// Returns first Epoch contained in this file.
+    /// Returns a new [CGGTTS] with the same [Header], that only contains
+    /// the [Track]s matching the `predicate`. This allows arbitrary selection
+    /// criteria, for example:
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let cggtts = CGGTTS::default();
+    ///
+    /// let high_gps_l1c = cggtts.filter(|trk| {
+    ///     trk.elevation_deg > 30.0
+    ///         && trk.uses_constellation(Constellation::GPS)
+    ///         && trk.frc == "L1C"
+    /// });
+    /// ```
+    pub fn filter<F: Fn(&Track) -> bool>(&self, predicate: F) -> Self {
+        Self {
+            header: self.header.clone(),
+            tracks: self
+                .tracks
+                .iter()
+                .filter(|trk| predicate(trk))
+                .cloned()
+                .collect(),
+            unknown_lines: self.unknown_lines.clone(),
+            day_boundaries: self.day_boundaries.clone(),
+        }
+    }
+
+    /// Returns a new [CGGTTS] with the same [Header], that only contains
+    /// the [Track]s using this carrier frequency code (FRC).
+    /// Like [Self::filter], this may be chained with other criteria.
+    pub fn filter_frc(&self, frc: &str) -> Self {
+        self.filter(|trk| trk.frc == frc)
+    }
+
+    /// Returns a new [CGGTTS] with the same [Header], that only contains
+    /// the [Track]s of this [CommonViewClass].
+    pub fn filter_class(&self, class: CommonViewClass) -> Self {
+        self.filter(|trk| trk.class == class)
+    }
+
+    /// Iterate over [Track]s (measurements) using this carrier frequency code (FRC).
+    pub fn tracks_with_frc<'a>(&'a self, frc: &'a str) -> impl Iterator<Item = &'a Track> {
+        self.tracks.iter().filter(move |trk| trk.frc == frc)
+    }
+
+    /// Drops the [Track]s observed below the local horizon, described by
+    /// `horizon`: the minimal elevation (in degrees) as a function of
+    /// azimuth (in degrees). This generalizes a flat elevation mask
+    /// to stations with obstructions:
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let mut cggtts = CGGTTS::default();
+    ///
+    /// // building obstructing the eastern sky up to 30°
+    /// cggtts.retain_above_horizon(|azimuth_deg| {
+    ///     if (45.0..135.0).contains(&azimuth_deg) {
+    ///         30.0
+    ///     } else {
+    ///         10.0
+    ///     }
+    /// });
+    /// ```
+    pub fn retain_above_horizon<F: Fn(f64) -> f64>(&mut self, horizon: F) {
+        self.tracks
+            .retain(|trk| trk.elevation_deg >= horizon(trk.azimuth_deg));
+    }
+
+    /// Removes an a priori model of the local clock from all [Track]s,
+    /// for example from the BIPM Circular T. The model is linear:
+    /// `offset_ns + drift_ns_per_day * (t - reference_epoch)`, evaluated
+    /// at the track midpoint, where REFSYS and REFSV are defined.
+    /// The model is subtracted from both REFSYS and REFSV, while the drift
+    /// term is subtracted from their derivatives (SRSYS and SRSV).
+    /// Use [Self::retrend] to restore the original content.
+    /// ```
+    /// use cggtts::prelude::*;
+    /// use std::str::FromStr;
+    ///
+    /// let mut cggtts = CGGTTS::default();
+    /// let reference = Epoch::from_str("2023-01-01T00:00:00 UTC").unwrap();
+    ///
+    /// // remove a 5 ns offset drifting by 0.1 ns/day
+    /// cggtts.detrend(5.0, 0.1, reference);
+    /// ```
+    pub fn detrend(&mut self, offset_ns: f64, drift_ns_per_day: f64, reference_epoch: Epoch) {
+        self.apply_clock_model(-offset_ns, -drift_ns_per_day, reference_epoch);
+    }
+
+    /// Adds the linear model of the local clock back to all [Track]s.
+    /// This is the inverse operation of [Self::detrend].
+    pub fn retrend(&mut self, offset_ns: f64, drift_ns_per_day: f64, reference_epoch: Epoch) {
+        self.apply_clock_model(offset_ns, drift_ns_per_day, reference_epoch);
+    }
+
+    /// Adds the linear clock model to REFSYS, REFSV and their derivatives.
+    fn apply_clock_model(&mut self, offset_ns: f64, drift_ns_per_day: f64, reference_epoch: Epoch) {
+        let drift_s_s = drift_ns_per_day * 1.0E-9 / 86400.0;
+
+        for trk in self.tracks.iter_mut() {
+            let midtrack = trk.epoch + trk.duration / 2;
+            let dt_days = (midtrack - reference_epoch).to_unit(Unit::Day);
+            let model_s = (offset_ns + drift_ns_per_day * dt_days) * 1.0E-9;
+
+            trk.data.refsys += model_s;
+            trk.data.refsv += model_s;
+            trk.data.srsys += drift_s_s;
+            trk.data.srsv += drift_s_s;
+        }
+    }
+
+    /// Iterate over [Track]s (measurements) of [SV]s whose PRN number
+    /// lies within `lo..=hi` (both inclusive), whatever their [Constellation].
+    /// For example, 1..=5 isolates BeiDou GEO from MEO/IGSO vehicles.
+    pub fn tracks_with_prn_range(&self, lo: u8, hi: u8) -> impl Iterator<Item = &Track> {
+        self.tracks
+            .iter()
+            .filter(move |trk| trk.sv.prn >= lo && trk.sv.prn <= hi)
+    }
+
+    /// Lists all (SV, Epoch) pairs that appear more than once in this [CGGTTS],
+    /// with their number of occurrences, in chronological order.
+    /// This usually indicates a receiver (or processing) bug.
+    pub fn duplicate_tracks(&self) -> Vec<(SV, Epoch, usize)> {
+        let mut occurrences = BTreeMap::<(Epoch, SV), usize>::new();
+        for trk in self.tracks.iter() {
+            *occurrences.entry((trk.epoch, trk.sv)).or_insert(0) += 1;
+        }
+        occurrences
+            .into_iter()
+            .filter_map(|((epoch, sv), count)| {
+                if count > 1 {
+                    Some((sv, epoch, count))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Verifies the checksum of every parsed [Track]: the checksum declared
+    /// in the file ([Track::ck]) is compared to the checksum of the raw line
+    /// it was parsed from, so any column alignment is supported.
+    /// ## Returns
+    /// - (index, declared, computed) for each [Track] whose checksum disagrees.
+    ///   [Track]s that were not parsed (without declared checksum) are not verified.
+    pub fn verify_track_crcs(&self) -> Vec<(usize, u8, u8)> {
+        self.tracks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, trk)| match trk.verify_ck() {
+                Err(TrackError::ChecksumMismatch { found, computed }) => {
+                    Some((index, found, computed))
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sorts [Track]s in chronological order, then by [SV].
+    pub fn sort_tracks(&mut self) {
+        self.tracks.sort_by_key(|trk| (trk.epoch, trk.sv));
+    }
+
+    /// Sorts [Track]s like [Self::sort_tracks] and removes duplicated
+    /// [Track]s: only the first [Track] of each (SV, Epoch) pair is preserved.
+    pub fn dedup_tracks(&mut self) {
+        let mut tracks = BTreeMap::<(Epoch, SV), Track>::new();
+
+        for trk in std::mem::take(&mut self.tracks) {
+            tracks.entry((trk.epoch, trk.sv)).or_insert(trk);
+        }
+
+        self.tracks = tracks.into_values().collect();
+    }
+
+    /// Generates the "melting pot" of this [CGGTTS]: for each common view [Epoch],
+    /// all [Track]s of the same [Constellation] are averaged into a single
+    /// combined [Track]. The combined [Track] uses the PRN 99 marker ("G99"),
+    /// and [CommonViewClass::MultiChannel].
+    /// - REFSV, SRSV, REFSYS, SRSYS, modeled delays, elevation and
+    ///   ionospheric terms are averaged.
+    /// - DSG is the spread (standard deviation) of REFSYS across all [SV]s.
+    /// - FRC is preserved when all [Track]s use the same carrier.
+    /// - Azimuth has no meaning for a combination and is set to 0.
+    pub fn melting_pot(&self) -> Self {
+        const MARKER_PRN: u8 = 99;
+
+        let mut pots = BTreeMap::<(Epoch, Constellation), Vec<&Track>>::new();
+
+        for trk in self.tracks.iter() {
+            pots.entry((trk.epoch, trk.sv.constellation))
+                .or_default()
+                .push(trk);
+        }
+
+        let tracks = pots
+            .into_iter()
+            .map(|((epoch, constellation), tracks)| {
+                let n = tracks.len() as f64;
+                let mean =
+                    |f: &dyn Fn(&Track) -> f64| tracks.iter().map(|trk| f(trk)).sum::<f64>() / n;
+
+                let refsys = mean(&|trk| trk.data.refsys);
+
+                let dsg = (tracks
+                    .iter()
+                    .map(|trk| (trk.data.refsys - refsys).powi(2))
+                    .sum::<f64>()
+                    / n)
+                    .sqrt();
+
+                let iono = if tracks.iter().all(|trk| trk.iono.is_some()) {
+                    Some(IonosphericData {
+                        msio: mean(&|trk| trk.iono.unwrap().msio),
+                        smsi: mean(&|trk| trk.iono.unwrap().smsi),
+                        isg: mean(&|trk| trk.iono.unwrap().isg),
+                    })
+                } else {
+                    None
+                };
+
+                let frc = if tracks.iter().all(|trk| trk.frc == tracks[0].frc) {
+                    tracks[0].frc
+                } else {
+                    CarrierCode::default()
+                };
+
+                Track {
+                    class: CommonViewClass::MultiChannel,
+                    epoch,
+                    duration: tracks[0].duration,
+                    sv: SV::new(constellation, MARKER_PRN),
+                    elevation_deg: mean(&|trk| trk.elevation_deg),
+                    azimuth_deg: 0.0,
+                    data: TrackData {
+                        refsv: mean(&|trk| trk.data.refsv),
+                        srsv: mean(&|trk| trk.data.srsv),
+                        refsys,
+                        srsys: mean(&|trk| trk.data.srsys),
+                        dsg,
+                        ioe: 0,
+                        mdtr: mean(&|trk| trk.data.mdtr),
+                        smdt: mean(&|trk| trk.data.smdt),
+                        mdio: mean(&|trk| trk.data.mdio),
+                        smdi: mean(&|trk| trk.data.smdi),
+                    },
+                    iono,
+                    fdma_channel: None,
+                    hc: 0,
+                    frc,
+                    provenance: None,
+                    ck: None,
+                    computed_ck: None,
+                }
+            })
+            .collect();
+
+        Self {
+            header: self.header.clone(),
+            tracks,
+            unknown_lines: Vec::new(),
+            day_boundaries: Vec::new(),
+        }
+    }
+
+    /// Returns first Epoch contained in this file, None if [Self::is_empty].
     pub fn first_epoch(&self) -> Option<Epoch> {
         self.tracks.first().map(|trk| trk.epoch)
     }
 
-    /// Returns last Epoch contained in this file.
+    /// Returns last Epoch contained in this file, which is the start
+    /// of the last [Track], None if [Self::is_empty].
+    /// Refer to [Track::end_epoch] for its end.
     pub fn last_epoch(&self) -> Option<Epoch> {
         self.tracks.last().map(|trk| trk.epoch)
     }
 
-    /// Returns total [Duration] of this [CGGTTS].
+    /// Returns true if both [CGGTTS] describe the same content, once formatted:
+    /// the [Header]s are semantically equal (refer to [Header::content_eq]) and
+    /// the [Track]s are identical, whatever their [Track::provenance].
+    /// Unlike a strict comparison, this is not affected by volatile fields
+    /// like the revision date, so it is suited to round trip verifications.
+    pub fn content_eq(&self, other: &Self) -> bool {
+        let content = |trk: &Track| {
+            (
+                trk.class,
+                trk.epoch,
+                trk.duration,
+                trk.sv,
+                trk.elevation_deg,
+                trk.azimuth_deg,
+                trk.data,
+                trk.iono,
+                trk.fdma_channel,
+                trk.hc,
+                trk.frc,
+            )
+        };
+
+        self.header.content_eq(&other.header)
+            && self.tracks.len() == other.tracks.len()
+            && self
+                .tracks
+                .iter()
+                .zip(other.tracks.iter())
+                .all(|(lhs, rhs)| content(lhs) == content(rhs))
+    }
+
+    /// Returns the [SV] of the first [Track] contained in this file.
+    pub fn first_sv(&self) -> Option<SV> {
+        self.tracks.first().map(|trk| trk.sv)
+    }
+
+    /// Returns the [SV] of the last [Track] contained in this file.
+    pub fn last_sv(&self) -> Option<SV> {
+        self.tracks.last().map(|trk| trk.sv)
+    }
+
+    /// Returns the [Duration] between the start of the first [Track]
+    /// and the start of the last [Track]. This does not account for
+    /// the last tracking duration: prefer [Self::span] to
+    /// describe the period covered by this [CGGTTS].
+    /// Both [Epoch]s are expressed in [TimeScale::TAI] beforehand, so the result
+    /// does not depend on the [TimeScale] of the [Track]s, and accounts for
+    /// possible leap seconds.
     pub fn total_duration(&self) -> Duration {
-        if let Some(t1) = self.last_epoch() {
-            if let Some(t0) = self.first_epoch() {
-                return t1 - t0;
-            }
+        match (self.first_epoch(), self.last_epoch()) {
+            (Some(t0), Some(t1)) => elapsed(t0, t1),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Returns the [Duration] covered by this [CGGTTS], from the start
+    /// of the first [Track] to the end of the last [Track].
+    /// Like [Self::total_duration], this does not depend on the [TimeScale]
+    /// of the [Track]s.
+    pub fn span(&self) -> Duration {
+        match (self.tracks.first(), self.tracks.last()) {
+            (Some(first), Some(last)) => elapsed(first.epoch, last.end_epoch()),
+            _ => Duration::ZERO,
         }
-        Duration::ZERO
     }
 
     /// Generates a standardized file name that would describes
     /// this [CGGTTS] correctly according to naming conventions.
     /// This method is infaillible, but might generate incomplete
-    /// results. In particular, this [CGGTTS] should not be empty
-    /// and must contain [Track]s measurements for this to work correctly.
+    /// results. In particular, this [CGGTTS] should contain [Track]s,
+    /// otherwise the [Constellation] and MJD should be declared in the [Header]
+    /// (see [Header::with_constellation] and [Header::with_nominal_mjd]).
+    /// Placeholders are used when neither is available.
     /// ## Inputs
     /// - custom_lab: Possible LAB ID overwrite and customization.
     /// Two characters are expected here, the result will not
@@ -275,12 +869,39 @@ impl CGGTTS {
         &self,
         custom_lab: Option<&str>,
         custom_id: Option<&str>,
+    ) -> String {
+        self.standardized_file_name_with_letter(custom_lab, custom_id, None)
+    }
+
+    /// Generates a file name like [Self::standardized_file_name], with possible
+    /// `constellation_letter` customization. The first letter is normally
+    /// deduced from the [Constellation] of the first [Track], but some
+    /// conventions differ, for example `X` for combined (mixed) files.
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let cggtts = CGGTTS::default()
+    ///     .with_header(Header::default().with_station("LAB"));
+    ///
+    /// let name = cggtts.standardized_file_name_with_letter(None, Some("01"), Some('X'));
+    /// assert!(name.starts_with('X'));
+    /// assert_eq!(&name[2..6], "LA01");
+    /// ```
+    pub fn standardized_file_name_with_letter(
+        &self,
+        custom_lab: Option<&str>,
+        custom_id: Option<&str>,
+        constellation_letter: Option<char>,
     ) -> String {
         let mut ret = String::new();
 
         // Grab first letter of constellation
-        if let Some(first) = self.tracks.first() {
+        if let Some(letter) = constellation_letter {
+            ret.push(letter);
+        } else if let Some(first) = self.tracks.first() {
             ret.push_str(&format!("{:x}", first.sv.constellation));
+        } else if let Some(constellation) = self.header.constellation {
+            ret.push_str(&format!("{:x}", constellation));
         } else {
             ret.push('X');
         }
@@ -315,6 +936,8 @@ impl CGGTTS {
         if let Some(epoch) = self.first_epoch() {
             let mjd = epoch.to_mjd_utc_days();
             ret.push_str(&format!("{:02.3}", (mjd / 1000.0)));
+        } else if let Some(mjd) = self.header.nominal_mjd {
+            ret.push_str(&format!("{:02.3}", (mjd as f64 / 1000.0)));
         } else {
             ret.push_str("dd.ddd");
         }
@@ -341,8 +964,57 @@ impl CGGTTS {
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
         let fd = File::open(path).unwrap_or_else(|e| panic!("File open error: {}", e));
 
+        let capacity = fd
+            .metadata()
+            .map(|meta| estimated_nb_tracks(meta.len() as usize))
+            .unwrap_or_default();
+
         let mut reader = BufReader::new(fd);
-        Self::parse(&mut reader)
+        Self::parse_with_capacity(&mut reader, capacity)
+    }
+
+    /// Parse and merge several local files into a single [CGGTTS],
+    /// following [ParseOptions], refer to [Self::merge].
+    /// Each [Track] is tagged with the name of its source file
+    /// when [ParseOptions::tag_provenance] is set, see [Self::tag_provenance].
+    pub fn from_files<P: AsRef<Path>>(
+        paths: &[P],
+        options: ParseOptions,
+    ) -> Result<Self, ParsingError> {
+        let mut merged = Option::<Self>::None;
+
+        for path in paths {
+            let path = path.as_ref();
+            let mut reader = BufReader::new(File::open(path)?);
+            let mut cggtts = Self::parse_with_options(&mut reader, options)?;
+
+            if options.tag_provenance {
+                let name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_else(|| path.to_string_lossy());
+
+                cggtts.tag_provenance(&name);
+            }
+
+            match merged.as_mut() {
+                Some(merged) => merged.merge_mut(&cggtts)?,
+                None => merged = Some(cggtts),
+            }
+        }
+
+        Ok(merged.unwrap_or_default())
+    }
+
+    /// Tags all [Track]s that do not have a [Track::provenance] yet,
+    /// with this source name. This is typically the source file name,
+    /// to remember where each [Track] came from once merged.
+    pub fn tag_provenance(&mut self, source: &str) {
+        for trk in self.tracks.iter_mut() {
+            if trk.provenance.is_none() {
+                trk.provenance = Some(source.to_string());
+            }
+        }
     }
 
     /// Parse a new [CGGTTS] from any [Read]able interface.
@@ -350,41 +1022,307 @@ impl CGGTTS {
     /// - Any critical standard violation
     /// - If file revision is not 2E (latest)
     /// - If following [Track]s do not contain the same [Constellation]
+    ///
+    /// [Track]s that cannot be parsed are silently dropped,
+    /// prefer [Self::parse_lossy] to obtain the list of errors,
+    /// or [Self::parse_with_options] to modify this behavior.
     pub fn parse<R: Read>(reader: &mut BufReader<R>) -> Result<Self, ParsingError> {
+        Self::parse_tracks(reader, 0, ParseOptions::default(), None, None)
+    }
+
+    /// Parse a new [CGGTTS] from any [Read]able interface, like [Self::parse],
+    /// pre-allocating `nb_tracks` [Track]s. Use this when the expected
+    /// number of [Track]s is known, to avoid growing the [Track] list
+    /// repeatedly when parsing large files.
+    pub fn parse_with_capacity<R: Read>(
+        reader: &mut BufReader<R>,
+        nb_tracks: usize,
+    ) -> Result<Self, ParsingError> {
+        Self::parse_tracks(reader, nb_tracks, ParseOptions::default(), None, None)
+    }
+
+    /// Parse a new [CGGTTS] from any [Read]able interface,
+    /// following desired [ParseOptions]. Refer to [ParseOptions]
+    /// for each possible option.
+    pub fn parse_with_options<R: Read>(
+        reader: &mut BufReader<R>,
+        options: ParseOptions,
+    ) -> Result<Self, ParsingError> {
+        Self::parse_tracks(reader, 0, options, None, None)
+    }
+
+    /// Parse a new [CGGTTS] from any [Read]able interface,
+    /// collecting all [Track] errors instead of silently dropping them.
+    /// This will only fail on [Header] errors.
+    /// ## Returns
+    /// - [CGGTTS] containing all valid [Track]s
+    /// - list of (line number, [ParsingError]) for each invalid [Track].
+    ///   Line numbers start at 1. [Track]s that do not use the same
+    ///   [Constellation] as the first one are reported as well.
+    ///   Blank lines are ignored. Repeated label rows and end of file markers
+    ///   (footer appended by some concatenation tools) are ignored after the last [Track],
+    ///   but reported when a [Track] follows them. Any other footer line is reported.
+    pub fn parse_lossy<R: Read>(
+        reader: &mut BufReader<R>,
+    ) -> Result<(Self, Vec<(usize, ParsingError)>), ParsingError> {
+        let mut errors = Vec::new();
+        let cggtts =
+            Self::parse_tracks(reader, 0, ParseOptions::default(), Some(&mut errors), None)?;
+        Ok((cggtts, errors))
+    }
+
+    /// Parse a new [CGGTTS] from any [Read]able interface, like [Self::parse],
+    /// reporting progress every 1000 lines,
+    /// refer to [Self::parse_with_progress_interval].
+    pub fn parse_with_progress<R: Read, F: FnMut(u64)>(
+        reader: R,
+        total_size_hint: u64,
+        callback: F,
+    ) -> Result<Self, ParsingError> {
+        Self::parse_with_progress_interval(
+            reader,
+            total_size_hint,
+            DEFAULT_PROGRESS_INTERVAL,
+            callback,
+        )
+    }
+
+    /// Parse a new [CGGTTS] from any [Read]able interface, like [Self::parse],
+    /// invoking `callback` with the number of bytes consumed so far,
+    /// every `nb_lines` [Track] lines, and once the input is fully consumed.
+    /// Reported values are strictly increasing, and the last one is the
+    /// total input size. This is convenient to display a progress indication
+    /// when loading very large files.
+    /// ## Inputs
+    /// - reader: [Read]able input
+    /// - total_size_hint: expected input size (in bytes), used to pre-allocate the
+    ///   [Track]s. Use zero when unknown.
+    /// - nb_lines: number of lines between two reports
+    /// - callback: progress report
+    /// ```
+    /// use cggtts::prelude::{Header, Track, CGGTTS};
+    /// use std::{io::BufWriter, str::FromStr};
+    ///
+    /// let mut cggtts = CGGTTS::default()
+    ///     .with_header(Header::default().with_station("LAB"));
+    ///
+    /// cggtts.tracks.push(Track::from_str(
+    ///     "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5"
+    /// ).unwrap());
+    ///
+    /// let mut writer = BufWriter::new(Vec::new());
+    /// cggtts.format(&mut writer).unwrap();
+    ///
+    /// let content = writer.into_inner().unwrap();
+    /// let size = content.len() as u64;
+    ///
+    /// let mut last = 0;
+    /// let cggtts = CGGTTS::parse_with_progress_interval(&content[..], size, 10, |consumed| {
+    ///     println!("{:.1}%", consumed as f64 * 100.0 / size as f64);
+    ///     last = consumed;
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(last, size);
+    /// ```
+    pub fn parse_with_progress_interval<R: Read, F: FnMut(u64)>(
+        reader: R,
+        total_size_hint: u64,
+        nb_lines: usize,
+        mut callback: F,
+    ) -> Result<Self, ParsingError> {
+        let nb_lines = nb_lines.max(1);
+        let (reader, count) = CountingReader::new(reader);
+        let mut reader = BufReader::new(reader);
+
+        let mut nth = 0;
+        let mut reported = 0;
+
+        let mut report = |consumed: u64| {
+            if consumed > reported {
+                reported = consumed;
+                callback(consumed);
+            }
+        };
+
+        // consumed bytes are the bytes pulled from the input, minus the buffered ones
+        let mut progress = |buffered: usize| {
+            nth += 1;
+            if nth % nb_lines == 0 {
+                report(count.get() - buffered as u64);
+            }
+        };
+
+        let capacity = estimated_nb_tracks(total_size_hint as usize);
+
+        let cggtts = Self::parse_tracks(
+            &mut reader,
+            capacity,
+            ParseOptions::default(),
+            None,
+            Some(&mut progress),
+        )?;
+
+        report(count.get() - reader.buffer().len() as u64);
+        Ok(cggtts)
+    }
+
+    /// Parse [CGGTTS], pre-allocating (at least) `nb_tracks`,
+    /// following [ParseOptions], with possible errors collection
+    /// and progress report. The progress report is invoked with the number
+    /// of buffered bytes, once per line.
+    fn parse_tracks<R: Read>(
+        reader: &mut BufReader<R>,
+        nb_tracks: usize,
+        options: ParseOptions,
+        mut errors: Option<&mut Vec<(usize, ParsingError)>>,
+        mut progress: Option<&mut dyn FnMut(usize)>,
+    ) -> Result<Self, ParsingError> {
         // Parse header section
-        let header = Header::parse(reader)?;
+        let (header, header_lines, mut first_track) = Header::parse_counting_lines(reader)?;
 
         // Parse tracks:
         // consumes all remaning lines and attempt parsing on each new line.
-        // Line CRC is internally verified for each line.
+        // Line CRC is only verified in strict mode.
         // We abort if Constellation content is not constant, as per standard conventions.
-        let mut tracks = Vec::with_capacity(16);
-        let lines = reader.lines();
+        let mut tracks = Vec::with_capacity(nb_tracks.max(16));
+        let mut unknown_lines = Vec::new();
+        let mut day_boundaries = Vec::new();
+
+        // a separator was encountered, next track starts a new day
+        let mut new_day = false;
+
+        // a single line buffer is reused, to avoid one allocation per track
+        let mut bytes = Vec::<u8>::with_capacity(256);
 
         let mut constellation = Option::<Constellation>::None;
 
-        for line in lines {
-            if line.is_err() {
+        // known footer lines, since the last track
+        let mut footer = Vec::<(usize, String)>::new();
+
+        for nth in 0.. {
+            let line_number = header_lines + nth + 1;
+
+            bytes.clear();
+
+            if let Some(first_track) = first_track.take() {
+                // already consumed by the header parser
+                bytes.extend_from_slice(first_track.as_bytes());
+            } else {
+                match reader.read_until(b'\n', &mut bytes) {
+                    Ok(0) => break,
+                    Ok(_) => {},
+                    Err(e) => {
+                        if let Some(errors) = errors.as_mut() {
+                            errors.push((line_number, ParsingError::Io(e)));
+                        }
+                        continue;
+                    },
+                }
+            }
+
+            if let Some(progress) = progress.as_mut() {
+                progress(reader.buffer().len());
+            }
+
+            let line = match std::str::from_utf8(&bytes) {
+                Ok(line) => line,
+                Err(e) => {
+                    if let Some(errors) = errors.as_mut() {
+                        let e = std::io::Error::new(std::io::ErrorKind::InvalidData, e);
+                        errors.push((line_number, ParsingError::Io(e)));
+                    }
+                    continue;
+                },
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let line = line.trim_end();
+
+            if is_day_separator(line) {
+                new_day = options.preserve_day_separators;
                 continue;
             }
 
-            let line = line.unwrap();
+            // known footer: tolerated (never reported) unless a track follows
+            if is_footer_line(line) {
+                footer.push((line_number, line.to_string()));
+                continue;
+            }
 
-            if let Ok(track) = Track::from_str(&line) {
-                // constellation content verification
-                if let Some(constellation) = &constellation {
-                    if track.sv.constellation != *constellation {
-                        return Err(ParsingError::MixedConstellation);
+            let track = if options.strict_crc {
+                Track::from_str_checked(line)
+            } else {
+                Track::from_str(line)
+            };
+
+            match track {
+                Ok(track) => {
+                    // footer lines followed by a track are invalid track lines
+                    for (line_number, line) in footer.drain(..) {
+                        let e = TrackError::InvalidFormat;
+                        if options.strict_tracks {
+                            return Err(ParsingError::TrackParsing(e));
+                        }
+                        if options.preserve_unknown_lines {
+                            unknown_lines.push((line_number, line));
+                        }
+                        if let Some(errors) = errors.as_mut() {
+                            errors.push((line_number, ParsingError::TrackParsing(e)));
+                        }
+                    }
+
+                    // constellation content verification
+                    if let Some(constellation) = &constellation {
+                        if track.sv.constellation != *constellation
+                            && !options.allow_mixed_constellation
+                        {
+                            match errors.as_mut() {
+                                Some(errors) => {
+                                    errors.push((line_number, ParsingError::MixedConstellation));
+                                    continue;
+                                },
+                                None => return Err(ParsingError::MixedConstellation),
+                            }
+                        }
+                    } else {
+                        constellation = Some(track.sv.constellation);
+                    }
+
+                    if new_day {
+                        day_boundaries.push(track.epoch);
+                        new_day = false;
                     }
-                } else {
-                    constellation = Some(track.sv.constellation);
-                }
 
-                tracks.push(track);
+                    tracks.push(track);
+                },
+                Err(e) => {
+                    if options.strict_tracks {
+                        return Err(ParsingError::TrackParsing(e));
+                    }
+                    if options.preserve_unknown_lines {
+                        unknown_lines.push((line_number, line.to_string()));
+                    }
+                    if let Some(errors) = errors.as_mut() {
+                        errors.push((line_number, ParsingError::TrackParsing(e)));
+                    }
+                },
             }
         }
 
-        Ok(Self { header, tracks })
+        if tracks.is_empty() && options.reject_empty {
+            return Err(ParsingError::EmptyFile);
+        }
+
+        Ok(Self {
+            header,
+            tracks,
+            unknown_lines,
+            day_boundaries,
+        })
     }
 
     /// Parse [CGGTTS] from gzip compressed local path.
@@ -399,6 +1337,42 @@ impl CGGTTS {
         Self::parse(&mut reader)
     }
 
+    /// Parse [CGGTTS] from a memory mapped local file.
+    /// This avoids loading the complete file content and reduces
+    /// peak memory usage, when processing large archives.
+    /// The file should not be modified while being parsed.
+    #[cfg(feature = "memmap2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "memmap2")))]
+    pub fn from_mmap<P: AsRef<Path>>(path: P) -> Result<Self, ParsingError> {
+        let fd = File::open(path)?;
+
+        // Safety: the mapped file is only read, and is expected
+        // not to be modified by other processes during parsing.
+        let mmap = unsafe { Mmap::map(&fd)? };
+
+        let capacity = estimated_nb_tracks(mmap.len());
+
+        let mut reader = BufReader::new(Cursor::new(&mmap[..]));
+        Self::parse_with_capacity(&mut reader, capacity)
+    }
+
+    /// Parse [CGGTTS] from any asynchronous reader, for example a network stream.
+    /// Lines are collected without blocking, then parsed exactly like [CGGTTS::parse].
+    #[cfg(feature = "tokio")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(reader: R) -> Result<Self, ParsingError> {
+        let mut content = String::new();
+        let mut lines = AsyncBufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            content.push_str(&line);
+            content.push('\n');
+        }
+
+        let mut reader = BufReader::new(Cursor::new(content.as_bytes()));
+        Self::parse(&mut reader)
+    }
+
     /// Format [CGGTTS] following standard specifications.
     ///
     /// To produce valid advanced CGGTTS, one should specify:
@@ -523,6 +1497,19 @@ impl CGGTTS {
     ///   .unwrap();
     /// ```
     pub fn format<W: Write>(&self, writer: &mut BufWriter<W>) -> Result<(), FormattingError> {
+        // create local (tiny) Utf-8 buffer
+        let mut buf = Utf8Buffer::new(1024);
+        self.format_with_buffer(writer, &mut buf)
+    }
+
+    /// Format [CGGTTS] following standard specifications, like [Self::format],
+    /// using the provided [Utf8Buffer]. This allows bulk producers to reuse
+    /// a single buffer when writing many files, instead of allocating one per file.
+    pub fn format_with_buffer<W: Write>(
+        &self,
+        writer: &mut BufWriter<W>,
+        buf: &mut Utf8Buffer,
+    ) -> Result<(), FormattingError> {
         const TRACK_LABELS_WITH_IONOSPHERIC_DATA: &str =
         "SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK";
 
@@ -533,11 +1520,15 @@ impl CGGTTS {
 
         const UNIT_LABELS_WITHOUT_IONOSPHERIC :&str = "             hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s";
 
-        // create local (tiny) Utf-8 buffer
-        let mut buf = Utf8Buffer::new(1024);
-
         // format header
-        self.header.format(writer, &mut buf)?;
+        let constellation = self
+            .tracks
+            .first()
+            .map(|trk| trk.sv.constellation)
+            .or(self.header.constellation);
+
+        self.header
+            .format_with_constellation(writer, buf, constellation)?;
 
         // BLANK at end of header section
         write!(writer, "\n")?;
@@ -551,15 +1542,52 @@ impl CGGTTS {
             writeln!(writer, "{}", UNIT_LABELS_WITHOUT_IONOSPHERIC)?;
         }
 
-        // format all tracks
+        // format all tracks, with possible day separators
+        let mut boundaries = self.day_boundaries.iter().peekable();
+
         for track in self.tracks.iter() {
-            track.format(writer, &mut buf)?;
+            if boundaries
+                .next_if(|boundary| track.epoch >= **boundary)
+                .is_some()
+            {
+                // a single separator, even if several boundaries were crossed
+                while boundaries
+                    .next_if(|boundary| track.epoch >= **boundary)
+                    .is_some()
+                {}
+
+                let mjd = track.epoch.to_mjd_utc_days().floor() as u32;
+                writeln!(writer, "{} MJD {}", DAY_SEPARATOR, mjd)?;
+            }
+
+            track.format(writer, buf)?;
             write!(writer, "\n")?;
         }
 
         Ok(())
     }
 
+    /// Formats this [CGGTTS] into an in-memory buffer,
+    /// which is the readable file content.
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let cggtts = CGGTTS::default()
+    ///     .with_header(Header::default().with_station("LAB"));
+    ///
+    /// let content = cggtts.to_vec().unwrap();
+    /// assert!(content.starts_with(b"CGGTTS     GENERIC DATA FORMAT VERSION = 2E"));
+    /// ```
+    pub fn to_vec(&self) -> Result<Vec<u8>, FormattingError> {
+        let mut writer = BufWriter::new(Vec::with_capacity(
+            MIN_TRACK_LINE_LEN * (self.tracks.len() + 32),
+        ));
+        self.format(&mut writer)?;
+        writer
+            .into_inner()
+            .map_err(|e| FormattingError::Stdio(e.into_error()))
+    }
+
     /// Writes this [CGGTTS] into readable local file
     pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FormattingError> {
         let fd = File::create(path)?;
@@ -567,26 +1595,104 @@ impl CGGTTS {
         self.format(&mut writer)
     }
 
-    /// Writes this [CGGTTS] into gzip compressed local file
+    /// Writes this [CGGTTS] into gzip compressed local file,
+    /// using the default compression level (5).
     #[cfg(feature = "flate2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "flate2")))]
     pub fn to_gzip_file<P: AsRef<Path>>(&self, path: P) -> Result<(), FormattingError> {
+        self.to_gzip_file_with_level(path, 5)
+    }
+
+    /// Writes this [CGGTTS] into gzip compressed local file,
+    /// with desired compression level, from 0 (no compression, fastest)
+    /// to 9 (best compression, slowest).
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let cggtts = CGGTTS::default()
+    ///     .with_header(Header::default().with_station("LAB"));
+    ///
+    /// assert!(cggtts.to_gzip_file_with_level("/tmp/LAB.gz", 10).is_err());
+    /// ```
+    #[cfg(feature = "flate2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "flate2")))]
+    pub fn to_gzip_file_with_level<P: AsRef<Path>>(
+        &self,
+        path: P,
+        level: u32,
+    ) -> Result<(), FormattingError> {
+        if level > 9 {
+            return Err(FormattingError::InvalidCompressionLevel(level));
+        }
         let fd = File::create(path)?;
-        let compression = GzCompression::new(5);
+        let compression = GzCompression::new(level);
         let mut writer = BufWriter::new(GzEncoder::new(fd, compression));
         self.format(&mut writer)
     }
 
+    /// Returns a new dual frequency [CGGTTS], with frequency dependent
+    /// delays defined for both carriers. Delays that were already defined
+    /// in the [Header] for either [Code] are preserved, others are initialized
+    /// to zero and should be updated with your calibration values.
+    /// Other frequency dependent delays are dropped.
+    /// NB: all [Track]s should then carry [IonosphericData], for this [CGGTTS]
+    /// to be correctly described as dual frequency ('Z' capability letter, in the
+    /// [Self::standardized_file_name]).
+    /// ```
+    /// use cggtts::prelude::*;
+    /// use std::io::BufWriter;
+    ///
+    /// let header = Header::default()
+    ///     .with_station("LAB");
+    ///
+    /// let cggtts = CGGTTS::new_dual_frequency(header, Code::E1, Code::E5);
+    /// assert_eq!(cggtts.header.delay.freq_dependent_delays.len(), 2);
+    ///
+    /// let mut content = Vec::new();
+    /// cggtts.format(&mut BufWriter::new(&mut content)).unwrap();
+    ///
+    /// let content = String::from_utf8(content).unwrap();
+    /// assert!(content.contains("INT DLY =    0.0 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = NA"));
+    ///
+    /// assert!(cggtts.standardized_file_name(Some("LA"), Some("01")).starts_with("XZ"));
+    /// ```
+    pub fn new_dual_frequency(header: Header, c1: Code, c2: Code) -> Self {
+        let mut header = header;
+
+        let freq_dependent_delays = [c1, c2]
+            .iter()
+            .map(|code| {
+                let delay = header
+                    .delay
+                    .freq_dependent_delays
+                    .iter()
+                    .find(|(k, _)| k == code)
+                    .map(|(_, delay)| *delay)
+                    .unwrap_or(Delay::Internal(0.0));
+                (*code, delay)
+            })
+            .collect();
+
+        header.delay.freq_dependent_delays = freq_dependent_delays;
+
+        Self {
+            header,
+            tracks: Vec::new(),
+            unknown_lines: Vec::new(),
+            day_boundaries: Vec::new(),
+        }
+    }
+
     /// Returns a new [CGGTTS] ready to track in [TimeScale::UTC].
     /// This is the most (most) general use case, for the simple reason
     /// that UTC is a worldwide constant, hence, allows worldwide common-view.
     /// You can use our other method for exotic contexts.
-    /// NB: use this prior solving any [Track]s, otherwise
-    /// it will corrupt previously solved content, because
-    /// it does not perform the time shift for you.
-    pub fn with_utc_reference_time(&self) -> Self {
-        let mut s = self.clone();
-        s.header = s.header.with_reference_time(TimeScale::UTC.into());
-        s
+    /// NB: this does not perform the time shift of previously solved
+    /// [Track]s, it will therefore fail with [StateError::TracksPresent]
+    /// if this [CGGTTS] already contains some. Use [Self::force_reference_time]
+    /// if you know what you are doing.
+    pub fn with_utc_reference_time(self) -> Result<Self, StateError> {
+        self.with_reference_time(TimeScale::UTC.into())
     }
 
     /// Returns a new [CGGTTS] ready to track in custom UTC-replica.
@@ -594,28 +1700,22 @@ impl CGGTTS {
     /// NB(1): we differentiate UTC-replica (unofficial or local UTC)
     /// from custom reference time system (exotic or private),
     /// for which you have [Self::with_custom_reference_time].
-    /// NB(2): use this prior solving any [Track]s, otherwise
-    /// it will corrupt previously solved content, because
-    /// it does not perform the time shift for you.
+    /// NB(2): this does not perform the time shift of previously solved
+    /// [Track]s, it will therefore fail with [StateError::TracksPresent]
+    /// if this [CGGTTS] already contains some.
     /// ## Inputs
     /// - name: name of your UTC replica (also referred to, as UTCk).
-    pub fn with_utc_replica_reference_time(&self, name: &str) -> Self {
-        let mut s = self.clone();
-        s.header = s
-            .header
-            .with_reference_time(ReferenceTime::UTCk(name.to_string()));
-        s
+    pub fn with_utc_replica_reference_time(self, name: &str) -> Result<Self, StateError> {
+        self.with_reference_time(ReferenceTime::UTCk(name.to_string()))
     }
 
     /// Returns a new [CGGTTS] ready to track in [TimeScale::TAI].
     /// Use this method when setting up a [CGGTTS] production context.
-    /// NB: use this prior solving any [Track]s, otherwise
-    /// it will corrupt previously solved content, because
-    /// it does not perform the time shift for you.
-    pub fn with_tai_reference_time(&self) -> Self {
-        let mut s = self.clone();
-        s.header = s.header.with_reference_time(TimeScale::TAI.into());
-        s
+    /// NB: this does not perform the time shift of previously solved
+    /// [Track]s, it will therefore fail with [StateError::TracksPresent]
+    /// if this [CGGTTS] already contains some.
+    pub fn with_tai_reference_time(self) -> Result<Self, StateError> {
+        self.with_reference_time(TimeScale::TAI.into())
     }
 
     /// Returns a new [CGGTTS] ready to track in custom timescale
@@ -624,29 +1724,44 @@ impl CGGTTS {
     /// NB(1): we differentiate custom reference time systems from
     /// UTC-replica (unofficial or local UTC),
     /// for which you have [Self::with_utc_replica_reference_time].
-    /// NB(2): use this prior solving any [Track]s, otherwise
-    /// it will corrupt previously solved content, because
-    /// it does not perform the time shift for you.
+    /// NB(2): this does not perform the time shift of previously solved
+    /// [Track]s, it will therefore fail with [StateError::TracksPresent]
+    /// if this [CGGTTS] already contains some.
     /// ## Inputs
     /// - name: name of your custom timescale
-    pub fn with_custom_reference_time(&self, name: &str) -> Self {
-        let mut s = self.clone();
-        s.header = s
-            .header
-            .with_reference_time(ReferenceTime::UTCk(name.to_string()));
+    pub fn with_custom_reference_time(self, name: &str) -> Result<Self, StateError> {
+        self.with_reference_time(ReferenceTime::UTCk(name.to_string()))
+    }
+
+    /// Updates the [ReferenceTime], only if no [Track]s exist yet.
+    fn with_reference_time(self, reference_time: ReferenceTime) -> Result<Self, StateError> {
+        if !self.tracks.is_empty() {
+            return Err(StateError::TracksPresent);
+        }
+        Ok(self.force_reference_time(reference_time))
+    }
+
+    /// Returns a new [CGGTTS] expressed in given [ReferenceTime],
+    /// regardless of the [Track]s it may already contain.
+    /// NB: no time shift is performed, only use this when
+    /// the existing [Track]s were already solved against this
+    /// [ReferenceTime] (for example, relabeling a mislabeled file).
+    pub fn force_reference_time(self, reference_time: ReferenceTime) -> Self {
+        let mut s = self;
+        s.header = s.header.with_reference_time(reference_time);
         s
     }
 
-    /// Copies and returns new [CGGTTS] with updated [Header] section.
-    pub fn with_header(&self, header: Header) -> Self {
-        let mut s = self.clone();
+    /// Returns new [CGGTTS] with updated [Header] section.
+    pub fn with_header(self, header: Header) -> Self {
+        let mut s = self;
         s.header = header;
         s
     }
 
-    /// Copies and returns new [CGGTTS] with updated [Track] list.
-    pub fn with_tracks(&self, tracks: Vec<Track>) -> Self {
-        let mut s = self.clone();
+    /// Returns new [CGGTTS] with updated [Track] list.
+    pub fn with_tracks(self, tracks: Vec<Track>) -> Self {
+        let mut s = self;
         s.tracks = tracks;
         s
     }
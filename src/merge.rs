@@ -0,0 +1,105 @@
+//! [CGGTTS] concatenation
+use std::ops::{Add, AddAssign};
+
+use crate::{errors::MergeError, prelude::CGGTTS};
+
+impl CGGTTS {
+    /// Merges (concatenates) rhs [CGGTTS] into a new [CGGTTS], typically
+    /// to produce a longer observation period from consecutive files.
+    /// The [crate::prelude::Header] of self is preserved and [crate::prelude::Track]s are
+    /// sorted in chronological order. Both [CGGTTS] must come from the same station,
+    /// be expressed in the same reference time and use the same [crate::prelude::Constellation].
+    pub fn merge(&self, rhs: &Self) -> Result<Self, MergeError> {
+        let mut s = self.clone();
+        s.merge_mut(rhs)?;
+        Ok(s)
+    }
+
+    /// Merges (concatenates) rhs [CGGTTS] into self, see [Self::merge].
+    pub fn merge_mut(&mut self, rhs: &Self) -> Result<(), MergeError> {
+        if self.header.station != rhs.header.station {
+            return Err(MergeError::StationMismatch);
+        }
+
+        if self.header.reference_time != rhs.header.reference_time {
+            return Err(MergeError::ReferenceTimeMismatch);
+        }
+
+        let lhs_constellation = self.tracks.first().map(|trk| trk.sv.constellation);
+        let rhs_constellation = rhs.tracks.first().map(|trk| trk.sv.constellation);
+
+        if let (Some(lhs), Some(rhs)) = (lhs_constellation, rhs_constellation) {
+            if lhs != rhs {
+                return Err(MergeError::MixedConstellation);
+            }
+        }
+
+        self.tracks.extend_from_slice(&rhs.tracks);
+        self.tracks.sort_by_key(|trk| trk.epoch);
+        Ok(())
+    }
+
+    /// Appends the following day to self, as labs do when submitting
+    /// several days in a single file: only one [crate::prelude::Header] is kept,
+    /// so both [crate::prelude::Header]s must be identical. The first [crate::prelude::Track]
+    /// of the appended day is recorded in [Self::day_boundaries], so a separator
+    /// comment is formatted ahead of it.
+    pub fn append_day(&mut self, other: Self) -> Result<(), MergeError> {
+        if self.header != other.header {
+            return Err(MergeError::HeaderMismatch);
+        }
+
+        let lhs_constellation = self.tracks.first().map(|trk| trk.sv.constellation);
+        let rhs_constellation = other.tracks.first().map(|trk| trk.sv.constellation);
+
+        if let (Some(lhs), Some(rhs)) = (lhs_constellation, rhs_constellation) {
+            if lhs != rhs {
+                return Err(MergeError::MixedConstellation);
+            }
+        }
+
+        if let Some(first) = other.first_epoch() {
+            self.day_boundaries.push(first);
+        }
+
+        self.day_boundaries.extend(other.day_boundaries);
+        self.tracks.extend(other.tracks);
+        self.tracks.sort_by_key(|trk| trk.epoch);
+        self.day_boundaries.sort();
+        Ok(())
+    }
+}
+
+/// Concatenates two [CGGTTS], see [CGGTTS::merge].
+/// Panics if both [CGGTTS] are not compatible:
+/// prefer [CGGTTS::merge] when that may happen.
+/// ```
+/// use cggtts::prelude::*;
+///
+/// let header = Header::default().with_station("LAB");
+///
+/// let day1 = CGGTTS::default().with_header(header.clone());
+/// let day2 = CGGTTS::default().with_header(header.clone());
+/// let day3 = CGGTTS::default().with_header(header);
+///
+/// let week = day1 + day2 + day3;
+/// assert_eq!(week.header.station, "LAB");
+/// ```
+impl Add for CGGTTS {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut s = self;
+        s += rhs;
+        s
+    }
+}
+
+/// Concatenates rhs into self, see [CGGTTS::merge_mut].
+/// Panics if both [CGGTTS] are not compatible.
+impl AddAssign for CGGTTS {
+    fn add_assign(&mut self, rhs: Self) {
+        self.merge_mut(&rhs)
+            .unwrap_or_else(|e| panic!("cggtts merge error: {}", e));
+    }
+}
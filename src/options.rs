@@ -0,0 +1,126 @@
+//! [CGGTTS] parsing options
+#[cfg(doc)]
+use crate::{
+    errors::ParsingError,
+    prelude::{Constellation, Track, CGGTTS},
+};
+
+/// [ParseOptions] define how strictly [CGGTTS::parse_with_options] follows
+/// the standard. The default options correspond to [CGGTTS::parse]:
+/// invalid [Track]s are silently dropped, track checksums are not verified,
+/// and mixing [Constellation]s aborts the parsing process.
+/// ```
+/// use cggtts::prelude::*;
+/// use std::io::BufReader;
+///
+/// let options = ParseOptions::default()
+///     .with_strict_tracks(true)
+///     .with_strict_crc(true);
+///
+/// let content = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+/// REV DATE = 2023-06-27
+/// RCVR = GTR51 2204005 1.12.0 2022 2.0
+/// CH = 20
+/// IMS = GTR51 2204005 1.12.0 2022 2.0
+/// LAB = LAB
+/// X = +3970727.80 m
+/// Y = +1018888.02 m
+/// Z = +4870276.84 m
+/// FRAME = ITRF
+/// COMMENTS = NO COMMENTS
+/// INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021
+/// CAB DLY =  155.2 ns
+/// REF DLY =    0.0 ns
+/// REF = UTC(LAB)
+/// CKSUM = 37
+///
+/// SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK
+///              hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s.1ns.1ps/s.1ns
+/// E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 00
+/// ";
+///
+/// // the track checksum is not correct
+/// let mut reader = BufReader::new(content.as_bytes());
+/// assert!(CGGTTS::parse_with_options(&mut reader, options).is_err());
+///
+/// // which is tolerated by default
+/// let mut reader = BufReader::new(content.as_bytes());
+/// let cggtts = CGGTTS::parse(&mut reader).unwrap();
+/// assert_eq!(cggtts.tracks.len(), 1);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Keep [Track]s that do not use the same [Constellation] as the
+    /// first one, instead of aborting. The resulting [CGGTTS] does not
+    /// follow the standard.
+    pub allow_mixed_constellation: bool,
+    /// Verify the checksum (CK) of each [Track] line. [Track]s with
+    /// an invalid checksum are then treated like any other invalid [Track].
+    pub strict_crc: bool,
+    /// Abort on the first invalid [Track], instead of dropping it.
+    pub strict_tracks: bool,
+    /// Preserve the lines that are not valid [Track]s in
+    /// [CGGTTS::unknown_lines], instead of discarding them.
+    pub preserve_unknown_lines: bool,
+    /// Record the separator comments found between concatenated days
+    /// in [CGGTTS::day_boundaries]. Separators are always tolerated,
+    /// but discarded by default.
+    pub preserve_day_separators: bool,
+    /// Tag each [Track] with the name of the file it was parsed from,
+    /// in [Track::provenance]. Only applies to [CGGTTS::from_files].
+    pub tag_provenance: bool,
+    /// Abort with [ParsingError::EmptyFile] when the file does not contain
+    /// any [Track]. By default, an empty [CGGTTS] is returned, refer to [CGGTTS::is_empty].
+    pub reject_empty: bool,
+}
+
+impl ParseOptions {
+    /// Returns [ParseOptions] with desired mixed [Constellation] tolerance.
+    pub fn with_mixed_constellation(self, allowed: bool) -> Self {
+        let mut s = self;
+        s.allow_mixed_constellation = allowed;
+        s
+    }
+
+    /// Returns [ParseOptions] with desired [Track] checksum verification.
+    pub fn with_strict_crc(self, strict: bool) -> Self {
+        let mut s = self;
+        s.strict_crc = strict;
+        s
+    }
+
+    /// Returns [ParseOptions] with desired [Track] strictness.
+    pub fn with_strict_tracks(self, strict: bool) -> Self {
+        let mut s = self;
+        s.strict_tracks = strict;
+        s
+    }
+
+    /// Returns [ParseOptions] with desired unknown lines preservation.
+    pub fn with_unknown_lines(self, preserve: bool) -> Self {
+        let mut s = self;
+        s.preserve_unknown_lines = preserve;
+        s
+    }
+
+    /// Returns [ParseOptions] with desired [Track::provenance] tagging.
+    pub fn with_provenance(self, tag: bool) -> Self {
+        let mut s = self;
+        s.tag_provenance = tag;
+        s
+    }
+
+    /// Returns [ParseOptions] with desired empty file rejection.
+    pub fn with_empty_rejection(self, reject: bool) -> Self {
+        let mut s = self;
+        s.reject_empty = reject;
+        s
+    }
+
+    /// Returns [ParseOptions] with desired day separators preservation.
+    pub fn with_day_separators(self, preserve: bool) -> Self {
+        let mut s = self;
+        s.preserve_day_separators = preserve;
+        s
+    }
+}
@@ -0,0 +1,37 @@
+//! Parsing progress report
+use std::{
+    cell::Cell,
+    io::{Read, Result},
+    rc::Rc,
+};
+
+/// Default number of lines between two progress reports
+pub(crate) const DEFAULT_PROGRESS_INTERVAL: usize = 1000;
+
+/// [CountingReader] counts the bytes pulled from the underlying [Read]er.
+/// The counter is shared, so the progress can be observed while
+/// the [CountingReader] is owned by the parser.
+pub(crate) struct CountingReader<R: Read> {
+    inner: R,
+    count: Rc<Cell<u64>>,
+}
+
+impl<R: Read> CountingReader<R> {
+    /// Wraps this [Read]er, returns the shared counter as well.
+    pub fn new(inner: R) -> (Self, Rc<Cell<u64>>) {
+        let count = Rc::new(Cell::new(0));
+        let reader = Self {
+            inner,
+            count: Rc::clone(&count),
+        };
+        (reader, count)
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let size = self.inner.read(buf)?;
+        self.count.set(self.count.get() + size as u64);
+        Ok(size)
+    }
+}
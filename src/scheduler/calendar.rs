@@ -108,6 +108,32 @@ impl CommonViewCalendar {
         Self::new(reference_epoch, period).unwrap()
     }
 
+    /// Builds the [CommonViewCalendar] recommended to track geostationary
+    /// satellites (SBAS). It follows the BIPM specifications (16' periods,
+    /// aligned to 50_722 MJD + 2'), without the -4' daily offset:
+    /// geostationary satellites are continuously visible and do not repeat
+    /// a sideral ground track, so periods remain aligned to UTC midnight.
+    /// This is [Self::bipm_unaliged_gps_sideral].
+    ///
+    /// ```
+    /// use cggtts::prelude::{CommonViewCalendar, Duration, Epoch};
+    ///
+    /// let calendar = CommonViewCalendar::sbas();
+    /// assert_eq!(calendar.periods_per_day(), 90);
+    ///
+    /// // same schedule every day
+    /// let t0 = Epoch::from_mjd_utc(60_000.0);
+    /// let t1 = t0 + Duration::from_days(1.0);
+    ///
+    /// assert_eq!(
+    ///     calendar.next_period_start_after(t1) - calendar.next_period_start_after(t0),
+    ///     Duration::from_days(1.0),
+    /// );
+    /// ```
+    pub fn sbas() -> Self {
+        Self::bipm_unaliged_gps_sideral()
+    }
+
     /// Returns the total number of complete [CommonViewPeriod]s per day.
     pub const fn periods_per_day(&self) -> u16 {
         self.periods_per_day
@@ -122,8 +148,8 @@ impl CommonViewCalendar {
     /// A default [CommonViewCalendar] is calculated from the original reference point.
     /// It is possible to add (either positive or negative) offset to apply each day.
     /// Strict CGGTTS uses a -4' offset to remain aligned to GPS sideral time.
-    pub fn with_daily_offset(&self, offset: Duration) -> Self {
-        let mut s = self.clone();
+    pub fn with_daily_offset(self, offset: Duration) -> Self {
+        let mut s = self;
         s.daily_offset = offset;
         s
     }
@@ -266,6 +292,38 @@ mod test {
         scheduler::{calendar::CommonViewCalendar, period::BIPM_REFERENCE_MJD},
     };
 
+    #[test]
+    fn sbas_calendar() {
+        let calendar = CommonViewCalendar::sbas();
+
+        assert_eq!(calendar.periods_per_day, 90);
+        assert_eq!(calendar.daily_offset, Duration::ZERO);
+        assert_eq!(calendar, CommonViewCalendar::bipm_unaliged_gps_sideral());
+
+        let bipm = CommonViewCalendar::bipm();
+
+        for mjd in [BIPM_REFERENCE_MJD + 1, 60_000, 60_001] {
+            let t = Epoch::from_mjd_utc(mjd as f64);
+
+            // 2' after midnight, every day
+            assert_eq!(
+                calendar.next_period_start_after(t),
+                t + Duration::from_seconds(120.0),
+            );
+
+            // while BIPM drifts -4' per day
+            let days = (mjd - BIPM_REFERENCE_MJD) as f64;
+            let drift = Duration::from_seconds(120.0 - 240.0 * days)
+                .to_seconds()
+                .rem_euclid(960.0);
+
+            assert_eq!(
+                bipm.next_period_start_after(t),
+                t + Duration::from_seconds(drift),
+            );
+        }
+    }
+
     #[test]
     fn test_bipm() {
         const MJD0_OFFSET_NANOS: i128 = 120_000_000_000;
@@ -54,16 +54,16 @@ impl CommonViewPeriod {
 
     /// Returns a new [CommonViewPeriod] with desired setup [Duration]
     /// for which data should not be collected (at the beginning of each period)
-    pub fn with_setup_duration(&self, setup_duration: Duration) -> Self {
-        let mut s = self.clone();
+    pub fn with_setup_duration(self, setup_duration: Duration) -> Self {
+        let mut s = self;
         s.setup_duration = setup_duration;
         s
     }
 
     /// Returns a new [CommonViewPeriod] with desired setup duration in seconds,
     /// for which data should not be collected (at the beginning of each period)
-    pub fn with_setup_duration_s(&self, setup_s: f64) -> Self {
-        let mut s = self.clone();
+    pub fn with_setup_duration_s(self, setup_s: f64) -> Self {
+        let mut s = self;
         s.setup_duration = Duration::from_seconds(setup_s);
         s
     }
@@ -71,8 +71,8 @@ impl CommonViewPeriod {
     /// Returns a new [CommonViewPeriod] with desired tracking [Duration]
     /// for which data should be collected (at the end of each period, after possible
     /// setup [Duration]).
-    pub fn with_tracking_duration(&self, tracking_duration: Duration) -> Self {
-        let mut s = self.clone();
+    pub fn with_tracking_duration(self, tracking_duration: Duration) -> Self {
+        let mut s = self;
         s.tracking_duration = tracking_duration;
         s
     }
@@ -80,8 +80,8 @@ impl CommonViewPeriod {
     /// Returns a new [CommonViewPeriod] with desired tracking duration (in seconds)
     /// for which data should be collected (at the end of each period, after possible
     /// setup [Duration]).
-    pub fn with_tracking_duration_s(&self, tracking_s: f64) -> Self {
-        let mut s = self.clone();
+    pub fn with_tracking_duration_s(self, tracking_s: f64) -> Self {
+        let mut s = self;
         s.tracking_duration = Duration::from_seconds(tracking_s);
         s
     }
@@ -0,0 +1,120 @@
+//! Stable serde representations of foreign types.
+//! [SV] and [Epoch] are serialized as their readable string
+//! representation (for example `"G08"` and `"2023-11-10T14:46:00 UTC"`),
+//! so the wire format does not depend on the internal structure
+//! of the [gnss] and [hifitime] crates.
+//! ```
+//! use cggtts::prelude::{Epoch, SV};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Measurement {
+//!     #[serde(with = "cggtts::serde_support::sv")]
+//!     sv: SV,
+//!     #[serde(with = "cggtts::serde_support::epoch")]
+//!     epoch: Epoch,
+//! }
+//! ```
+#[cfg(doc)]
+use crate::prelude::{Epoch, SV};
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+use std::{fmt::Display, str::FromStr};
+
+/// Serializes any type using its [Display] implementation
+fn serialize_display<T: Display, S: Serializer>(t: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(t)
+}
+
+/// Deserializes any type using its [FromStr] implementation
+fn deserialize_from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    T::from_str(&s).map_err(Error::custom)
+}
+
+/// [SV] (de)serialization, as its readable representation (`"G08"`)
+pub mod sv {
+    use super::{deserialize_from_str, serialize_display};
+    use gnss::prelude::SV;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(sv: &SV, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(sv, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SV, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+/// [Epoch] (de)serialization, as its readable representation,
+/// expressed in its own timescale (`"2023-11-10T14:46:00 UTC"`).
+pub mod epoch {
+    use super::{deserialize_from_str, serialize_display};
+    use hifitime::Epoch;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(epoch: &Epoch, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_display(epoch, serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Epoch, D::Error> {
+        deserialize_from_str(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{Epoch, SV};
+    use serde::{Deserialize, Serialize};
+    use std::str::FromStr;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pinned {
+        #[serde(with = "super::sv")]
+        sv: SV,
+        #[serde(with = "super::epoch")]
+        epoch: Epoch,
+    }
+
+    #[test]
+    fn pinned_representation() {
+        for (sv, epoch, json) in [
+            (
+                "G08",
+                "2023-11-10T14:46:00 UTC",
+                r#"{"sv":"G08","epoch":"2023-11-10T14:46:00 UTC"}"#,
+            ),
+            (
+                "R24",
+                "2020-01-01T00:02:00 UTC",
+                r#"{"sv":"R24","epoch":"2020-01-01T00:02:00 UTC"}"#,
+            ),
+            (
+                "C01",
+                "2023-01-01T22:20:00 BDT",
+                r#"{"sv":"C01","epoch":"2023-01-01T22:20:00 BDT"}"#,
+            ),
+        ] {
+            let pinned = Pinned {
+                sv: SV::from_str(sv).unwrap(),
+                epoch: Epoch::from_str(epoch).unwrap(),
+            };
+
+            assert_eq!(serde_json::to_string(&pinned).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Pinned>(json).unwrap(), pinned);
+        }
+
+        assert!(serde_json::from_str::<Pinned>(
+            r#"{"sv":"X99","epoch":"2023-11-10T14:46:00 UTC"}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<Pinned>(r#"{"sv":"G08","epoch":"not an epoch"}"#).is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! [proptest] strategies, to generate arbitrary (yet valid) CGGTTS content.
+//! Each numerical field is generated within the range and
+//! resolution of its column.
+use crate::prelude::{
+    CalibrationID, Code, CommonViewClass, Constellation, Coordinates, Delay, Duration, Epoch,
+    Hardware, Header, ImsField, IonosphericData, ReferenceTime, SystemDelay, Track, TrackData,
+    CGGTTS, SV,
+};
+
+use proptest::{collection::vec, option, prelude::*, sample::select};
+
+/// Alphanumeric token, as used in header fields
+fn token(max_len: usize) -> impl Strategy<Value = String> {
+    proptest::string::string_regex(&format!("[A-Za-z0-9]{{1,{}}}", max_len)).unwrap()
+}
+
+/// Scaled integer, within the resolution of a column
+fn scaled(min: i64, max: i64, scaling: f64) -> impl Strategy<Value = f64> {
+    (min..=max).prop_map(move |value| value as f64 * scaling)
+}
+
+/// Legal carriers (FRC) for this [Constellation]
+fn carriers(constellation: Constellation) -> Vec<&'static str> {
+    match constellation {
+        Constellation::Galileo => vec!["E1", "E5", "E5a", "E5b", "E6", "L3E"],
+        Constellation::BeiDou => vec!["B1i", "B1C", "B2i", "B2a", "B2b", "B3i", "L3B"],
+        Constellation::Glonass => vec!["L1C", "L1P", "L2C", "L2P", "L3P"],
+        _ => vec!["L1C", "L1P", "L2C", "L2P", "L5", "L3P"],
+    }
+}
+
+/// Arbitrary [Hardware]
+pub fn hardware() -> impl Strategy<Value = Hardware> {
+    (token(8), token(8), token(8), 1980u16..=2099, token(8)).prop_map(
+        |(manufacturer, model, serial_number, year, release)| Hardware {
+            manufacturer,
+            model,
+            serial_number,
+            year,
+            release,
+        },
+    )
+}
+
+/// Arbitrary [SystemDelay]
+pub fn system_delay() -> impl Strategy<Value = SystemDelay> {
+    (
+        scaled(0, 9_999, 0.1),
+        scaled(0, 9_999, 0.1),
+        vec(
+            (
+                select(vec![Code::C1, Code::P1, Code::C2, Code::P2]),
+                scaled(0, 9_999, 0.1),
+            ),
+            0..=2,
+        ),
+        option::of((1u16..=9999, 1990u16..=2099, option::of(token(4)))),
+    )
+        .prop_map(|(cab, rf, delays, cal_id)| SystemDelay {
+            antenna_cable_delay: cab,
+            local_ref_delay: rf,
+            freq_dependent_delays: delays
+                .into_iter()
+                .map(|(code, value)| (code, Delay::Internal(value)))
+                .collect(),
+            calibration_id: cal_id.map(|(process_id, year, lab)| CalibrationID {
+                process_id,
+                year,
+                lab,
+            }),
+            missing_unit: false,
+        })
+}
+
+/// Arbitrary [Header]
+pub fn header() -> impl Strategy<Value = Header> {
+    (
+        (0u32..=30_000).prop_map(|days| {
+            Epoch::from_gregorian_utc_at_midnight(2000, 1, 1) + Duration::from_days(days as f64)
+        }),
+        0u16..=999,
+        hardware(),
+        prop_oneof![
+            Just(ImsField::Integrated),
+            Just(ImsField::Unknown),
+            hardware().prop_map(ImsField::Hardware),
+        ],
+        token(8),
+        token(8),
+        (
+            scaled(-7_000_000_000, 7_000_000_000, 1.0E-3),
+            scaled(-7_000_000_000, 7_000_000_000, 1.0E-3),
+            scaled(-7_000_000_000, 7_000_000_000, 1.0E-3),
+        ),
+        option::of(token(32)),
+        system_delay(),
+        prop_oneof![
+            Just(ReferenceTime::UTC),
+            Just(ReferenceTime::TAI),
+            token(8).prop_map(ReferenceTime::UTCk),
+        ],
+    )
+        .prop_map(
+            |(
+                revision_date,
+                nb_channels,
+                receiver,
+                ims,
+                station,
+                reference_frame,
+                (x, y, z),
+                comments,
+                delay,
+                reference_time,
+            )| Header {
+                version: Default::default(),
+                revision_date,
+                nb_channels,
+                receiver,
+                ims,
+                station,
+                reference_frame,
+                apc_coordinates: Coordinates { x, y, z },
+                comments,
+                delay,
+                reference_time,
+                constellation: None,
+                nominal_mjd: None,
+                extra_fields: Vec::new(),
+                format_extra_fields: true,
+            },
+        )
+}
+
+/// Arbitrary [TrackData]
+pub fn track_data() -> impl Strategy<Value = TrackData> {
+    (
+        scaled(-999_999_999, 999_999_999, 1E-10),
+        scaled(-99_999, 99_999, 1E-13),
+        scaled(-999_999_999, 999_999_999, 1E-10),
+        scaled(-99_999, 99_999, 1E-13),
+        scaled(0, 9_999, 1E-10),
+        0u16..=999,
+        scaled(0, 9_999, 1E-10),
+        scaled(-999, 999, 1E-13),
+        scaled(0, 9_999, 1E-10),
+        scaled(-999, 999, 1E-13),
+    )
+        .prop_map(
+            |(refsv, srsv, refsys, srsys, dsg, ioe, mdtr, smdt, mdio, smdi)| TrackData {
+                refsv,
+                srsv,
+                refsys,
+                srsys,
+                dsg,
+                ioe,
+                mdtr,
+                smdt,
+                mdio,
+                smdi,
+            },
+        )
+}
+
+/// Arbitrary [IonosphericData]
+pub fn ionospheric_data() -> impl Strategy<Value = IonosphericData> {
+    (
+        scaled(0, 9_999, 1E-10),
+        scaled(-999, 999, 1E-13),
+        scaled(0, 999, 1E-10),
+    )
+        .prop_map(|(msio, smsi, isg)| IonosphericData { msio, smsi, isg })
+}
+
+/// Arbitrary [Track] of this [Constellation], starting at desired [Epoch].
+fn track_at(constellation: Constellation, epoch: Epoch) -> impl Strategy<Value = Track> {
+    (
+        1u8..=32,
+        prop_oneof![
+            Just(CommonViewClass::SingleChannel),
+            Just(CommonViewClass::MultiChannel)
+        ],
+        1u64..=9_999,
+        scaled(0, 900, 0.1),
+        scaled(0, 3_599, 0.1),
+        track_data(),
+        1u8..=24,
+        0u8..=99,
+        select(carriers(constellation)),
+    )
+        .prop_map(
+            move |(prn, class, duration, elevation_deg, azimuth_deg, data, fdma, hc, frc)| Track {
+                class,
+                epoch,
+                duration: Duration::from_seconds(duration as f64),
+                sv: SV::new(constellation, prn),
+                elevation_deg,
+                azimuth_deg,
+                data,
+                iono: None,
+                fdma_channel: if constellation == Constellation::Glonass {
+                    Some(fdma)
+                } else {
+                    None
+                },
+                hc,
+                frc: frc.parse().unwrap(),
+                provenance: None,
+                ck: None,
+                computed_ck: None,
+            },
+        )
+}
+
+/// Arbitrary [Constellation] that may be used in CGGTTS
+pub fn constellation() -> impl Strategy<Value = Constellation> {
+    select(vec![
+        Constellation::GPS,
+        Constellation::Galileo,
+        Constellation::BeiDou,
+        Constellation::Glonass,
+    ])
+}
+
+/// Arbitrary [Track] of this [Constellation]
+pub fn track_of(constellation: Constellation) -> impl Strategy<Value = Track> {
+    (50_000u32..=60_000, 0u32..1440).prop_flat_map(move |(mjd, minutes)| {
+        let epoch = Epoch::from_mjd_utc(mjd as f64) + Duration::from_seconds(minutes as f64 * 60.0);
+        track_at(constellation, epoch)
+    })
+}
+
+/// Arbitrary [Track]
+pub fn track() -> impl Strategy<Value = Track> {
+    constellation().prop_flat_map(track_of)
+}
+
+/// Arbitrary [CGGTTS], made of up to `max_tracks` chronological [Track]s
+/// of a single [Constellation], that either all have [IonosphericData], or none.
+pub fn cggtts(max_tracks: usize) -> impl Strategy<Value = CGGTTS> {
+    (
+        header(),
+        constellation(),
+        50_000u32..=60_000,
+        0..=max_tracks,
+        any::<bool>(),
+    )
+        .prop_flat_map(move |(header, constellation, mjd, n_tracks, with_iono)| {
+            let t0 = Epoch::from_mjd_utc(mjd as f64) + Duration::from_seconds(120.0);
+
+            let tracks = (0..n_tracks)
+                .map(|nth| {
+                    let epoch = t0 + Duration::from_seconds(960.0 * nth as f64);
+                    (track_at(constellation, epoch), ionospheric_data()).prop_map(
+                        move |(mut track, iono)| {
+                            if with_iono {
+                                track.iono = Some(iono);
+                            }
+                            track
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            (Just(header), tracks).prop_map(|(header, tracks)| CGGTTS {
+                header,
+                tracks,
+                unknown_lines: Vec::new(),
+                day_boundaries: Vec::new(),
+            })
+        })
+}
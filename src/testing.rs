@@ -0,0 +1,331 @@
+//! Testing toolkit, to generate and compare CGGTTS content.
+use crate::prelude::{
+    CommonViewClass, Constellation, Duration, Epoch, Hardware, Header, Track, TrackData, CGGTTS, SV,
+};
+
+use rand::{distributions::Alphanumeric, Rng};
+
+/// [Tolerances] used when comparing [Track]s.
+/// Each field is compared within the absolute tolerance,
+/// or not compared at all when set to None.
+/// Epoch, SV, duration, class, IOE, channels and FRC are always exactly compared.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerances {
+    /// Elevation tolerance (degrees)
+    pub elevation_deg: Option<f64>,
+    /// Azimuth tolerance (degrees)
+    pub azimuth_deg: Option<f64>,
+    /// REFSV tolerance (s)
+    pub refsv: Option<f64>,
+    /// SRSV tolerance (s/s)
+    pub srsv: Option<f64>,
+    /// REFSYS tolerance (s)
+    pub refsys: Option<f64>,
+    /// SRSYS tolerance (s/s)
+    pub srsys: Option<f64>,
+    /// DSG tolerance (s)
+    pub dsg: Option<f64>,
+    /// MDTR tolerance (s)
+    pub mdtr: Option<f64>,
+    /// SMDT tolerance (s/s)
+    pub smdt: Option<f64>,
+    /// MDIO tolerance (s)
+    pub mdio: Option<f64>,
+    /// SMDI tolerance (s/s)
+    pub smdi: Option<f64>,
+}
+
+impl Default for Tolerances {
+    /// Default [Tolerances]: exact angles, REFSV within 1E-11 s,
+    /// other fields are not compared.
+    fn default() -> Self {
+        Self {
+            elevation_deg: Some(0.0),
+            azimuth_deg: Some(0.0),
+            refsv: Some(1E-11),
+            srsv: None,
+            refsys: None,
+            srsys: None,
+            dsg: None,
+            mdtr: None,
+            smdt: None,
+            mdio: None,
+            smdi: None,
+        }
+    }
+}
+
+impl Tolerances {
+    /// [Tolerances] that any format/parse round trip should respect:
+    /// each field within half its formatting resolution.
+    pub fn resolution() -> Self {
+        Self {
+            elevation_deg: Some(0.05),
+            azimuth_deg: Some(0.05),
+            refsv: Some(0.05E-9),
+            srsv: Some(0.05E-12),
+            refsys: Some(0.05E-9),
+            srsys: Some(0.05E-12),
+            dsg: Some(0.05E-9),
+            mdtr: Some(0.05E-9),
+            smdt: Some(0.05E-12),
+            mdio: Some(0.05E-9),
+            smdi: Some(0.05E-12),
+        }
+    }
+}
+
+fn assert_within(t: Epoch, field: &str, dut: f64, model: f64, tolerance: Option<f64>) {
+    if let Some(tolerance) = tolerance {
+        assert!(
+            (dut - model).abs() <= tolerance,
+            "bad {} @ {:?}: {} vs {}",
+            field,
+            t,
+            dut,
+            model
+        );
+    }
+}
+
+/// Compares both [CGGTTS], panics on any difference.
+/// [Track]s are compared with [assert_tracks_equal].
+pub fn assert_cggtts_equal(dut: &CGGTTS, model: &CGGTTS, tolerances: &Tolerances) {
+    assert_eq!(dut.header.version, model.header.version, "wrong version");
+
+    assert_eq!(
+        dut.header.revision_date, model.header.revision_date,
+        "wrong release date"
+    );
+
+    assert_eq!(
+        dut.header.station, model.header.station,
+        "invalid station name"
+    );
+
+    assert_eq!(
+        dut.header.receiver, model.header.receiver,
+        "invalid receiver data"
+    );
+
+    assert_eq!(
+        dut.header.nb_channels, model.header.nb_channels,
+        "invalid receiver channels"
+    );
+
+    assert_eq!(
+        dut.header.reference_time, model.header.reference_time,
+        "wrong reference time"
+    );
+
+    assert_eq!(
+        dut.header.apc_coordinates, model.header.apc_coordinates,
+        "wrong apc coordinates"
+    );
+
+    assert_eq!(
+        dut.header.comments, model.header.comments,
+        "wrong comments content"
+    );
+
+    assert_eq!(dut.header.delay, model.header.delay, "wrong delay values");
+
+    assert_eq!(
+        dut.tracks.len(),
+        model.tracks.len(),
+        "wrong amount of tracks"
+    );
+
+    for (dut_trk, model_trk) in dut.tracks.iter().zip(model.tracks.iter()) {
+        assert_tracks_equal(dut_trk, model_trk, tolerances);
+    }
+}
+
+/// Compares both [Track]s, panics on any difference
+/// that exceeds the [Tolerances].
+pub fn assert_tracks_equal(dut: &Track, model: &Track, tolerances: &Tolerances) {
+    let t = dut.epoch;
+
+    assert_eq!(dut.epoch, model.epoch, "bad track epoch");
+    assert_eq!(dut.class, model.class, "bad common view class @ {:?}", t);
+    assert_eq!(
+        dut.duration, model.duration,
+        "bad tracking duration @ {:?}",
+        t
+    );
+    assert_eq!(dut.sv, model.sv, "bad sv description @ {:?}", t);
+    assert_eq!(dut.hc, model.hc, "bad hardware channel @ {:?}", t);
+
+    assert_eq!(
+        dut.fdma_channel, model.fdma_channel,
+        "invalid glonass FDMA channel @ {:?}",
+        t
+    );
+
+    assert_eq!(dut.frc, model.frc, "bad carrier code @ {:?}", t);
+
+    assert_within(
+        t,
+        "elevation",
+        dut.elevation_deg,
+        model.elevation_deg,
+        tolerances.elevation_deg,
+    );
+
+    assert_within(
+        t,
+        "azimuth",
+        dut.azimuth_deg,
+        model.azimuth_deg,
+        tolerances.azimuth_deg,
+    );
+
+    let (dut, model) = (&dut.data, &model.data);
+
+    assert_eq!(dut.ioe, model.ioe, "bad IOE @ {:?}", t);
+
+    assert_within(t, "REFSV", dut.refsv, model.refsv, tolerances.refsv);
+    assert_within(t, "SRSV", dut.srsv, model.srsv, tolerances.srsv);
+    assert_within(t, "REFSYS", dut.refsys, model.refsys, tolerances.refsys);
+    assert_within(t, "SRSYS", dut.srsys, model.srsys, tolerances.srsys);
+    assert_within(t, "DSG", dut.dsg, model.dsg, tolerances.dsg);
+    assert_within(t, "MDTR", dut.mdtr, model.mdtr, tolerances.mdtr);
+    assert_within(t, "SMDT", dut.smdt, model.smdt, tolerances.smdt);
+    assert_within(t, "MDIO", dut.mdio, model.mdio, tolerances.mdio);
+    assert_within(t, "SMDI", dut.smdi, model.smdi, tolerances.smdi);
+}
+
+/// Galileo [Track] lines (E1 and E5a carriers), 16 minutes apart and
+/// canonically formatted (as [Track::format] does), with correct checksums.
+pub const TRACK_LINES: [&str; 3] = [
+    "E03 FF 60258 001000  780 139  548      723788     14        -302    -14    2  76  325  -36   32   -3   20   20   3  0  0  E1 74",
+    "E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 30",
+    "E03 FF 60258 004200  780 139  548      723788     14        -302    -14    2  76  325  -36   32   -3   20   20   3  0  0  E1 77",
+];
+
+/// Returns a [CGGTTS] fixture of station "LAB", with default [Header]
+/// otherwise, made of the first `n_tracks` (up to 3) [TRACK_LINES].
+pub fn fixture_cggtts(n_tracks: usize) -> CGGTTS {
+    assert!(n_tracks <= TRACK_LINES.len(), "only 3 fixture tracks");
+
+    CGGTTS::default()
+        .with_header(Header::default().with_station("LAB"))
+        .with_tracks(
+            TRACK_LINES[..n_tracks]
+                .iter()
+                .map(|line| line.parse().unwrap())
+                .collect(),
+        )
+}
+
+/// Generates a random name, used in file production testing
+pub fn random_name(size: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(size)
+        .map(char::from)
+        .collect()
+}
+
+/// Generates a random GPS [Track] at desired [Epoch].
+/// All fields are within the range and resolution of their column.
+fn random_track_at(epoch: Epoch) -> Track {
+    let mut rng = rand::thread_rng();
+
+    let class = if rng.gen_bool(0.5) {
+        CommonViewClass::SingleChannel
+    } else {
+        CommonViewClass::MultiChannel
+    };
+
+    Track {
+        class,
+        epoch,
+        duration: Duration::from_seconds(780.0),
+        sv: SV::new(Constellation::GPS, rng.gen_range(1..=32)),
+        elevation_deg: rng.gen_range(0..=900) as f64 * 0.1,
+        azimuth_deg: rng.gen_range(0..3600) as f64 * 0.1,
+        data: TrackData {
+            refsv: rng.gen_range(-999_999_999..=999_999_999) as f64 * 1E-10,
+            srsv: rng.gen_range(-99_999..=99_999) as f64 * 1E-13,
+            refsys: rng.gen_range(-999_999_999..=999_999_999) as f64 * 1E-10,
+            srsys: rng.gen_range(-99_999..=99_999) as f64 * 1E-13,
+            dsg: rng.gen_range(0..=9_999) as f64 * 1E-10,
+            ioe: rng.gen_range(0..=999),
+            mdtr: rng.gen_range(0..=9_999) as f64 * 1E-10,
+            smdt: rng.gen_range(-999..=999) as f64 * 1E-13,
+            mdio: rng.gen_range(0..=9_999) as f64 * 1E-10,
+            smdi: rng.gen_range(-999..=999) as f64 * 1E-13,
+        },
+        iono: None,
+        fdma_channel: None,
+        hc: rng.gen_range(0..=99),
+        frc: "L1C".parse().unwrap(),
+        provenance: None,
+        ck: None,
+        computed_ck: None,
+    }
+}
+
+/// Generates a random, yet valid, GPS [Track].
+pub fn random_track() -> Track {
+    let mut rng = rand::thread_rng();
+
+    let mjd = rng.gen_range(50_000..=60_000) as f64;
+    let epoch =
+        Epoch::from_mjd_utc(mjd) + Duration::from_seconds(rng.gen_range(0..1440) as f64 * 60.0);
+
+    random_track_at(epoch)
+}
+
+/// Generates a random, yet valid, GPS [CGGTTS]
+/// made of `n_tracks` chronological [Track]s.
+pub fn random_cggtts(n_tracks: usize) -> CGGTTS {
+    let mut rng = rand::thread_rng();
+
+    let mjd = rng.gen_range(50_000..=60_000) as f64;
+    let t0 = Epoch::from_mjd_utc(mjd) + Duration::from_seconds(120.0);
+
+    let tracks = (0..n_tracks)
+        .map(|nth| random_track_at(t0 + Duration::from_seconds(960.0 * nth as f64)))
+        .collect();
+
+    CGGTTS {
+        header: Header::default()
+            .with_station(&random_name(4))
+            .with_receiver_hardware(
+                Hardware::default()
+                    .with_manufacturer(&random_name(4))
+                    .with_model(&random_name(6))
+                    .with_serial_number(&random_name(8))
+                    .with_release_year(rng.gen_range(2000..=2030))
+                    .with_release_version(&random_name(4)),
+            ),
+        tracks,
+        unknown_lines: Vec::new(),
+        day_boundaries: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{assert_cggtts_equal, random_cggtts, random_name, Tolerances};
+    use crate::prelude::CGGTTS;
+    use std::fs::remove_file;
+
+    #[test]
+    fn random_cggtts_round_trip() {
+        for n_tracks in [0, 1, 16] {
+            let cggtts = random_cggtts(n_tracks);
+
+            let file_name = random_name(8);
+            cggtts.to_file(&file_name).unwrap();
+
+            let parsed = CGGTTS::from_file(&file_name);
+            let _ = remove_file(&file_name);
+
+            let parsed = parsed.unwrap();
+            assert_cggtts_equal(&parsed, &cggtts, &Tolerances::resolution());
+        }
+    }
+}
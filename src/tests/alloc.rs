@@ -0,0 +1,49 @@
+//! Allocation counter, to verify the allocation behavior of the parser.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+thread_local! {
+    /// Allocations performed by this thread, when counting
+    static ALLOCATIONS: Cell<Option<usize>> = const { Cell::new(None) };
+}
+
+/// [System] allocator, that counts the allocations of the measured thread
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| {
+            if let Some(n) = count.get() {
+                count.set(Some(n + 1));
+            }
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| {
+            if let Some(n) = count.get() {
+                count.set(Some(n + 1));
+            }
+        });
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Runs `f` and returns its result, with the number of
+/// allocations (and reallocations) it performed on this thread.
+pub fn count_allocations<T, F: FnOnce() -> T>(f: F) -> (T, usize) {
+    ALLOCATIONS.with(|count| count.set(Some(0)));
+    let t = f();
+    let count = ALLOCATIONS.with(|count| count.replace(None));
+    (t, count.unwrap_or_default())
+}
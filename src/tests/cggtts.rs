@@ -0,0 +1,1522 @@
+#[cfg(test)]
+mod test {
+    use crate::prelude::{
+        Code, CommonViewClass, Constellation, Delay, Duration, Epoch, Hardware, Header,
+        ParseOptions, ReferenceTime, SystemDelay, TimeScale, Track, CGGTTS, SV,
+    };
+    use crate::{
+        errors::{MergeError, StateError},
+        testing::fixture_cggtts,
+        track::Error as TrackError,
+        ParsingError,
+    };
+    use std::io::{BufReader, BufWriter};
+    use std::str::FromStr;
+
+    #[test]
+    fn common_view_class_of() {
+        let mut cggtts = CGGTTS::default();
+
+        let track = Track::default();
+
+        for (sv, class) in [
+            ("G01", CommonViewClass::SingleChannel),
+            ("G02", CommonViewClass::SingleChannel),
+            ("E01", CommonViewClass::SingleChannel),
+            ("E02", CommonViewClass::MultiChannel),
+        ] {
+            let mut track = track.clone().with_sv(SV::from_str(sv).unwrap());
+            track.class = class;
+            cggtts.tracks.push(track);
+        }
+
+        assert_eq!(cggtts.common_view_class(), CommonViewClass::MultiChannel);
+
+        assert_eq!(
+            cggtts.common_view_class_of(Constellation::GPS),
+            Some(CommonViewClass::SingleChannel)
+        );
+
+        assert_eq!(
+            cggtts.common_view_class_of(Constellation::Galileo),
+            Some(CommonViewClass::MultiChannel)
+        );
+
+        assert_eq!(cggtts.common_view_class_of(Constellation::BeiDou), None);
+    }
+
+    #[test]
+    fn duplicate_tracks() {
+        let mut cggtts = CGGTTS::default();
+
+        let g01 = SV::from_str("G01").unwrap();
+        let g02 = SV::from_str("G02").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let t1 = t0 + Duration::from_seconds(960.0);
+
+        for (sv, epoch) in [(g01, t0), (g02, t0), (g01, t1), (g02, t1)] {
+            let mut track = Track::default().with_sv(sv);
+            track.epoch = epoch;
+            cggtts.tracks.push(track);
+        }
+
+        assert!(cggtts.duplicate_tracks().is_empty());
+
+        // receiver emitted some tracks more than once
+        let duplicated = cggtts.tracks[3].clone();
+        cggtts.tracks.push(duplicated.clone());
+        cggtts.tracks.push(duplicated);
+
+        let duplicated = cggtts.tracks[0].clone();
+        cggtts.tracks.push(duplicated);
+
+        assert_eq!(cggtts.duplicate_tracks(), vec![(g01, t0, 2), (g02, t1, 3)]);
+
+        cggtts.dedup_tracks();
+        assert!(cggtts.duplicate_tracks().is_empty());
+
+        let content = cggtts
+            .tracks
+            .iter()
+            .map(|trk| (trk.sv, trk.epoch))
+            .collect::<Vec<_>>();
+
+        assert_eq!(content, vec![(g01, t0), (g02, t0), (g01, t1), (g02, t1)]);
+    }
+
+    #[test]
+    fn sort_and_dedup_tracks() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+        let t1 = t0 + Duration::from_seconds(960.0);
+
+        let (g01, g02) = (SV::from_str("G01").unwrap(), SV::from_str("G02").unwrap());
+
+        let mut cggtts = CGGTTS::default();
+
+        for (epoch, sv, refsv) in [
+            (t1, g02, 1.0),
+            (t0, g02, 2.0),
+            (t1, g01, 3.0),
+            (t0, g01, 4.0),
+            (t0, g01, 5.0), // duplicate, with different data
+        ] {
+            let mut track = Track::default().with_sv(sv);
+            track.epoch = epoch;
+            track.data.refsv = refsv;
+            cggtts.tracks.push(track);
+        }
+
+        let content = |cggtts: &CGGTTS| {
+            cggtts
+                .tracks
+                .iter()
+                .map(|trk| (trk.epoch, trk.sv, trk.data.refsv))
+                .collect::<Vec<_>>()
+        };
+
+        // sorting is stable: duplicates are preserved, in order of appearance
+        let mut sorted = cggtts.clone();
+        sorted.sort_tracks();
+        assert_eq!(
+            content(&sorted),
+            vec![
+                (t0, g01, 4.0),
+                (t0, g01, 5.0),
+                (t0, g02, 2.0),
+                (t1, g01, 3.0),
+                (t1, g02, 1.0)
+            ]
+        );
+
+        // first track of each (Epoch, SV) pair is preserved
+        cggtts.dedup_tracks();
+        assert_eq!(
+            content(&cggtts),
+            vec![
+                (t0, g01, 4.0),
+                (t0, g02, 2.0),
+                (t1, g01, 3.0),
+                (t1, g02, 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_with_prn_range() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+
+        let mut cggtts = CGGTTS::default();
+
+        for prn in 1..=10 {
+            let mut track = Track::default().with_sv(SV::new(Constellation::BeiDou, prn));
+            track.epoch = t0;
+            track.duration = Duration::from_seconds(780.0);
+            track.frc = "B1i".parse().unwrap();
+            cggtts.tracks.push(track);
+        }
+
+        // format, then parse a BeiDou file
+        let mut content = Vec::new();
+        cggtts.format(&mut BufWriter::new(&mut content)).unwrap();
+
+        let cggtts = CGGTTS::parse(&mut BufReader::new(content.as_slice())).unwrap();
+        assert!(cggtts.is_beidou_cggtts());
+
+        let geo = cggtts
+            .tracks_with_prn_range(1, 5)
+            .map(|trk| trk.sv.prn)
+            .collect::<Vec<_>>();
+
+        assert_eq!(geo, vec![1, 2, 3, 4, 5]);
+
+        let meo = cggtts
+            .tracks_with_prn_range(6, 63)
+            .map(|trk| trk.sv.prn)
+            .collect::<Vec<_>>();
+
+        assert_eq!(meo, vec![6, 7, 8, 9, 10]);
+
+        assert_eq!(cggtts.tracks_with_prn_range(11, 63).count(), 0);
+        assert_eq!(cggtts.tracks_with_prn_range(5, 1).count(), 0);
+    }
+
+    #[test]
+    fn concatenation() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+        let header = Header::default().with_station("LAB");
+
+        // three daily files
+        let days = (0..3)
+            .map(|day| {
+                let mut cggtts = CGGTTS::default().with_header(header.clone());
+
+                for nth in 0..4 {
+                    let mut track = Track::default().with_sv(SV::from_str("G01").unwrap());
+                    track.epoch = t0
+                        + Duration::from_days(day as f64)
+                        + Duration::from_seconds(960.0 * nth as f64);
+                    track.duration = Duration::from_seconds(780.0);
+                    track.frc = "L1C".parse().unwrap();
+                    cggtts.tracks.push(track);
+                }
+
+                let mut content = Vec::new();
+                cggtts.format(&mut BufWriter::new(&mut content)).unwrap();
+                CGGTTS::parse(&mut BufReader::new(content.as_slice())).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let (day1, day2, day3) = (days[0].clone(), days[1].clone(), days[2].clone());
+
+        // order of the operands does not matter
+        let week = day3 + day1 + day2;
+
+        assert_eq!(week.header.station, "LAB");
+        assert_eq!(week.tracks.len(), 12);
+
+        let epochs = week.tracks.iter().map(|trk| trk.epoch).collect::<Vec<_>>();
+        let mut sorted = epochs.clone();
+        sorted.sort();
+        assert_eq!(epochs, sorted);
+
+        assert_eq!(week.tracks[0].epoch, t0);
+        assert_eq!(
+            week.tracks[11].epoch,
+            t0 + Duration::from_days(2.0) + Duration::from_seconds(2880.0)
+        );
+
+        let mut merged = days[0].clone();
+        merged += days[1].clone();
+        assert_eq!(merged.tracks.len(), 8);
+
+        // incompatible files
+        let other = days[0]
+            .clone()
+            .with_header(Header::default().with_station("OTHER"));
+        assert_eq!(
+            days[0].merge(&other).err(),
+            Some(MergeError::StationMismatch)
+        );
+
+        let other = days[0]
+            .clone()
+            .force_reference_time(ReferenceTime::UTCk("UTC(LAB)".to_string()));
+        assert_eq!(
+            days[0].merge(&other).err(),
+            Some(MergeError::ReferenceTimeMismatch)
+        );
+
+        let mut other = days[0].clone();
+        for track in other.tracks.iter_mut() {
+            track.sv = SV::from_str("E01").unwrap();
+        }
+        assert_eq!(
+            days[0].merge(&other).err(),
+            Some(MergeError::MixedConstellation)
+        );
+    }
+
+    #[test]
+    fn chained_builders() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+
+        let tracks = (0..10_000)
+            .map(|nth| {
+                let mut track = Track::default()
+                    .with_sv(SV::new(Constellation::GPS, (nth % 32) as u8 + 1))
+                    .with_elevation_deg(45.0)
+                    .with_azimuth_deg(120.0)
+                    .with_carrier_code("L1C");
+                track.epoch = t0 + Duration::from_seconds(960.0 * (nth / 32) as f64);
+                track
+            })
+            .collect::<Vec<_>>();
+
+        let header = Header::default()
+            .with_station("LAB")
+            .with_channels(32)
+            .with_comment("test")
+            .with_reference_frame("ITRF");
+
+        let cloned = tracks.clone();
+        let ptr = cloned.as_ptr();
+
+        let cggtts = CGGTTS::default()
+            .with_header(header.clone())
+            .with_tai_reference_time()
+            .unwrap()
+            .with_utc_replica_reference_time("UTC(LAB)")
+            .unwrap()
+            .with_utc_reference_time()
+            .unwrap()
+            .with_tracks(cloned);
+
+        // tracks were moved, not copied
+        assert_eq!(cggtts.tracks.as_ptr(), ptr);
+        assert_eq!(cggtts.tracks, tracks);
+
+        assert_eq!(cggtts.header.station, "LAB");
+        assert_eq!(cggtts.header.nb_channels, 32);
+        assert_eq!(cggtts.header.comments, Some("test".to_string()));
+        assert_eq!(cggtts.header.reference_frame, "ITRF");
+        assert_eq!(cggtts.header.reference_time, TimeScale::UTC.into());
+
+        // builders do not modify the original
+        assert_eq!(header.reference_time, Header::default().reference_time);
+    }
+
+    #[test]
+    fn reference_time_guard() {
+        let cggtts = CGGTTS::default();
+        assert!(cggtts.clone().with_utc_reference_time().is_ok());
+        assert!(cggtts.clone().with_tai_reference_time().is_ok());
+        assert!(cggtts
+            .clone()
+            .with_utc_replica_reference_time("UTC(LAB)")
+            .is_ok());
+        assert!(cggtts.clone().with_custom_reference_time("LAB").is_ok());
+
+        let cggtts = cggtts.with_tracks(vec![Track::default()]);
+        assert_eq!(
+            cggtts.clone().with_utc_reference_time().err(),
+            Some(StateError::TracksPresent)
+        );
+        assert_eq!(
+            cggtts.clone().with_tai_reference_time().err(),
+            Some(StateError::TracksPresent)
+        );
+        assert_eq!(
+            cggtts
+                .clone()
+                .with_utc_replica_reference_time("UTC(LAB)")
+                .err(),
+            Some(StateError::TracksPresent)
+        );
+        assert_eq!(
+            cggtts.clone().with_custom_reference_time("LAB").err(),
+            Some(StateError::TracksPresent)
+        );
+
+        let forced = cggtts.force_reference_time(TimeScale::TAI.into());
+        assert_eq!(forced.header.reference_time, TimeScale::TAI.into());
+        assert_eq!(forced.tracks.len(), 1);
+    }
+
+    #[test]
+    fn closure_filter() {
+        let mut cggtts = fixture_cggtts(0);
+
+        for (sv, elevation_deg) in [
+            ("G01", 10.0),
+            ("G02", 45.0),
+            ("E01", 60.0),
+            ("G03", 80.0),
+            ("E02", 5.0),
+        ] {
+            let track = Track::default()
+                .with_sv(SV::from_str(sv).unwrap())
+                .with_elevation_deg(elevation_deg);
+            cggtts.tracks.push(track);
+        }
+
+        let filtered = cggtts
+            .filter(|trk| trk.elevation_deg > 30.0 && trk.uses_constellation(Constellation::GPS));
+
+        assert_eq!(filtered.header.station, "LAB");
+
+        let svs = filtered
+            .tracks
+            .iter()
+            .map(|trk| trk.sv.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(svs, vec!["G02", "G03"]);
+
+        // original is preserved
+        assert_eq!(cggtts.tracks.len(), 5);
+
+        assert!(cggtts.filter(|_| false).tracks.is_empty());
+        assert_eq!(cggtts.filter(|_| true).tracks, cggtts.tracks);
+    }
+
+    #[test]
+    fn frc_and_class_filters() {
+        let mut cggtts = fixture_cggtts(0);
+
+        for (sv, frc, class) in [
+            ("G01", "L1C", CommonViewClass::SingleChannel),
+            ("G02", "L3P", CommonViewClass::MultiChannel),
+            ("G03", "L1C", CommonViewClass::MultiChannel),
+            ("G04", "L3P", CommonViewClass::MultiChannel),
+            ("G05", "L2P", CommonViewClass::SingleChannel),
+            ("G06", "L3P", CommonViewClass::SingleChannel),
+        ] {
+            let mut track = Track::default()
+                .with_sv(SV::from_str(sv).unwrap())
+                .with_carrier_code(frc);
+            track.class = class;
+            cggtts.tracks.push(track);
+        }
+
+        assert_eq!(cggtts.tracks_with_frc("L1C").count(), 2);
+        assert_eq!(cggtts.tracks_with_frc("L3P").count(), 3);
+        assert_eq!(cggtts.tracks_with_frc("E1").count(), 0);
+
+        let l3p = cggtts.filter_frc("L3P");
+        assert_eq!(l3p.header.station, "LAB");
+        assert_eq!(l3p.tracks.len(), 3);
+        assert!(l3p.tracks.iter().all(|trk| trk.frc == "L3P"));
+
+        assert_eq!(cggtts.filter_frc("L2P").tracks.len(), 1);
+
+        let multi_channel = cggtts.filter_class(CommonViewClass::MultiChannel);
+        assert_eq!(multi_channel.tracks.len(), 3);
+
+        // filters compose
+        let svs = cggtts
+            .filter_frc("L3P")
+            .filter_class(CommonViewClass::MultiChannel)
+            .tracks
+            .iter()
+            .map(|trk| trk.sv.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(svs, vec!["G02", "G04"]);
+
+        // original is preserved
+        assert_eq!(cggtts.tracks.len(), 6);
+    }
+
+    #[test]
+    fn horizon_mask() {
+        let mut cggtts = CGGTTS::default();
+
+        for (sv, elevation_deg, azimuth_deg) in [
+            ("G01", 15.0, 10.0),
+            ("G02", 15.0, 90.0),
+            ("G03", 45.0, 90.0),
+            ("G04", 30.0, 134.9),
+            ("G05", 5.0, 270.0),
+            ("G06", 15.0, 135.0),
+        ] {
+            let track = Track::default()
+                .with_sv(SV::from_str(sv).unwrap())
+                .with_elevation_deg(elevation_deg)
+                .with_azimuth_deg(azimuth_deg);
+            cggtts.tracks.push(track);
+        }
+
+        // obstructed eastern sector
+        cggtts.retain_above_horizon(|azimuth_deg| {
+            if (45.0..135.0).contains(&azimuth_deg) {
+                30.0
+            } else {
+                10.0
+            }
+        });
+
+        let svs = cggtts
+            .tracks
+            .iter()
+            .map(|trk| trk.sv.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(svs, vec!["G01", "G03", "G04", "G06"]);
+    }
+
+    #[test]
+    fn clock_model_detrending() {
+        let (offset_ns, drift_ns_per_day) = (12.5, -0.8);
+        let t0 = Epoch::from_str("2023-01-01T00:00:00 UTC").unwrap();
+        let drift_s_s = drift_ns_per_day * 1.0E-9 / 86400.0;
+
+        let mut cggtts = CGGTTS::default();
+
+        // two days of BIPM tracks, following the clock model
+        for nth in 0..180 {
+            let mut track = Track::default().with_sv(SV::from_str("G01").unwrap());
+            track.epoch = t0 + Duration::from_seconds(960.0 * nth as f64);
+            track.duration = Duration::from_seconds(780.0);
+
+            let midtrack = track.epoch + track.duration / 2;
+            let dt_days = (midtrack - t0).to_unit(hifitime::Unit::Day);
+            let model_s = (offset_ns + drift_ns_per_day * dt_days) * 1.0E-9;
+
+            track.data.refsys = model_s;
+            track.data.refsv = 1.0E-3 + model_s;
+            track.data.srsys = drift_s_s;
+            track.data.srsv = drift_s_s;
+            cggtts.tracks.push(track);
+        }
+
+        let original = cggtts.tracks.clone();
+
+        cggtts.detrend(offset_ns, drift_ns_per_day, t0);
+
+        let n = cggtts.tracks.len() as f64;
+        let mean = cggtts.tracks.iter().map(|trk| trk.data.refsys).sum::<f64>() / n;
+        assert!(mean.abs() < 1.0E-15, "mean={}", mean);
+
+        let first = cggtts.tracks.first().unwrap();
+        let last = cggtts.tracks.last().unwrap();
+        let slope =
+            (last.data.refsys - first.data.refsys) / (last.epoch - first.epoch).to_seconds();
+        assert!(slope.abs() < 1.0E-20, "slope={}", slope);
+
+        for trk in cggtts.tracks.iter() {
+            assert!(trk.data.refsys.abs() < 1.0E-15);
+            assert!((trk.data.refsv - 1.0E-3).abs() < 1.0E-15);
+            assert!(trk.data.srsys.abs() < 1.0E-25);
+            assert!(trk.data.srsv.abs() < 1.0E-25);
+        }
+
+        cggtts.retrend(offset_ns, drift_ns_per_day, t0);
+
+        for (trk, orig) in cggtts.tracks.iter().zip(original.iter()) {
+            assert!((trk.data.refsys - orig.data.refsys).abs() < 1.0E-15);
+            assert!((trk.data.refsv - orig.data.refsv).abs() < 1.0E-15);
+            assert!((trk.data.srsys - orig.data.srsys).abs() < 1.0E-25);
+        }
+    }
+
+    #[test]
+    fn full_day_span() {
+        let mut cggtts = CGGTTS::default();
+        assert_eq!(cggtts.span(), Duration::ZERO);
+
+        let t0 = Epoch::from_str("2023-01-01T00:00:00 UTC").unwrap();
+
+        // full day of contiguous (full period) tracks
+        for nth in 0..90 {
+            let mut track = Track::default().with_sv(SV::from_str("S23").unwrap());
+            track.epoch = t0 + Duration::from_seconds(960.0 * nth as f64);
+            track.duration = Duration::from_seconds(960.0);
+            cggtts.tracks.push(track);
+        }
+
+        let last = cggtts.tracks.last().unwrap();
+        assert_eq!(
+            last.end_epoch(),
+            Epoch::from_str("2023-01-02T00:00:00 UTC").unwrap()
+        );
+
+        assert_eq!(cggtts.span(), Duration::from_hours(24.0));
+
+        // compatibility: last tracking duration is not accounted for
+        assert_eq!(
+            cggtts.total_duration(),
+            Duration::from_hours(24.0) - Duration::from_seconds(960.0)
+        );
+    }
+
+    #[test]
+    fn tai_referenced_tracks() {
+        let mut cggtts = CGGTTS::default();
+        assert_eq!(cggtts.first_sv(), None);
+        assert_eq!(cggtts.last_sv(), None);
+
+        let g01 = SV::from_str("G01").unwrap();
+        let g02 = SV::from_str("G02").unwrap();
+
+        // tracks spanning a leap second
+        let t0 = Epoch::from_str("2016-12-31T23:50:00 TAI").unwrap();
+
+        for (nth, sv) in [g01, g02].iter().enumerate() {
+            let mut track = Track::default().with_sv(*sv);
+            track.epoch = t0 + Duration::from_seconds(1200.0 * nth as f64);
+            track.duration = Duration::from_seconds(780.0);
+            cggtts.tracks.push(track);
+        }
+
+        assert_eq!(cggtts.first_sv(), Some(g01));
+        assert_eq!(cggtts.last_sv(), Some(g02));
+
+        assert_eq!(cggtts.total_duration(), Duration::from_seconds(1200.0));
+        assert_eq!(cggtts.span(), Duration::from_seconds(1980.0));
+
+        // same instants, expressed in other timescales
+        for timescale in [TimeScale::UTC, TimeScale::GPST] {
+            let mut converted = cggtts.clone();
+            converted.tracks[1].epoch = converted.tracks[1].epoch.to_time_scale(timescale);
+            assert_eq!(converted.total_duration(), Duration::from_seconds(1200.0));
+            assert_eq!(converted.span(), Duration::from_seconds(1980.0));
+
+            let mut converted = cggtts.clone();
+            converted.tracks[0].epoch = converted.tracks[0].epoch.to_time_scale(timescale);
+            assert_eq!(converted.total_duration(), Duration::from_seconds(1200.0));
+            assert_eq!(converted.span(), Duration::from_seconds(1980.0));
+        }
+    }
+
+    #[test]
+    fn track_provenance() {
+        let header = Header::default().with_station("LAB");
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+
+        let source = |offset: usize| {
+            let mut cggtts = CGGTTS::default().with_header(header.clone());
+            for nth in 0..3 {
+                let mut track = Track::default()
+                    .with_sv(SV::from_str("G01").unwrap())
+                    .with_carrier_code("L1C");
+                track.epoch = t0 + Duration::from_seconds(960.0 * (2 * nth + offset) as f64);
+                track.duration = Duration::from_seconds(780.0);
+                cggtts.tracks.push(track);
+            }
+            cggtts
+        };
+
+        let dir = std::env::temp_dir();
+        let paths = [
+            dir.join("cggtts-provenance-a.txt"),
+            dir.join("cggtts-provenance-b.txt"),
+        ];
+
+        for (offset, path) in paths.iter().enumerate() {
+            source(offset).to_file(path).unwrap();
+        }
+
+        let untagged = CGGTTS::from_files(&paths, ParseOptions::default()).unwrap();
+        assert_eq!(untagged.tracks.len(), 6);
+        assert!(untagged.tracks.iter().all(|trk| trk.provenance.is_none()));
+
+        let options = ParseOptions::default().with_provenance(true);
+        let tagged = CGGTTS::from_files(&paths, options).unwrap();
+
+        for (nth, trk) in tagged.tracks.iter().enumerate() {
+            let expected = if nth % 2 == 0 {
+                "cggtts-provenance-a.txt"
+            } else {
+                "cggtts-provenance-b.txt"
+            };
+            assert_eq!(trk.provenance.as_deref(), Some(expected));
+        }
+
+        // text output is not affected
+        let format = |cggtts: &CGGTTS| {
+            let mut content = Vec::new();
+            {
+                let mut writer = BufWriter::new(&mut content);
+                cggtts.format(&mut writer).unwrap();
+            }
+            String::from_utf8(content).unwrap()
+        };
+
+        assert_eq!(format(&tagged), format(&untagged));
+
+        // JSON output carries the tag
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(&tagged).unwrap();
+            assert_eq!(json["tracks"][0]["provenance"], "cggtts-provenance-a.txt");
+            assert_eq!(json["tracks"][1]["provenance"], "cggtts-provenance-b.txt");
+
+            let json = serde_json::to_value(&untagged).unwrap();
+            assert!(json["tracks"][0]["provenance"].is_null());
+        }
+
+        for path in paths.iter() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn total_delay_round_trip() {
+        let receiver = Hardware::default()
+            .with_manufacturer("GTR51")
+            .with_model("2204005")
+            .with_serial_number("1.12.0")
+            .with_release_year(2022)
+            .with_release_version("2.0");
+
+        let mut header = Header::default()
+            .with_station("LAB")
+            .with_channels(20)
+            .with_receiver_hardware(receiver)
+            .with_reference_frame("ITRF");
+
+        header.delay = SystemDelay::default()
+            .with_antenna_cable_delay(237.0)
+            .with_ref_delay(149.6);
+
+        header.delay.freq_dependent_delays = smallvec::smallvec![
+            (Code::C1, Delay::Total(440.5)),
+            (Code::P2, Delay::Total(450.0)),
+        ];
+
+        let mut track = Track::default()
+            .with_sv(SV::from_str("G01").unwrap())
+            .with_elevation_deg(45.0)
+            .with_azimuth_deg(120.0)
+            .with_carrier_code("L1C");
+
+        track.epoch = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+        track.duration = Duration::from_seconds(780.0);
+
+        let cggtts = CGGTTS::default()
+            .with_header(header)
+            .with_tracks(vec![track]);
+
+        let mut writer = BufWriter::new(Vec::new());
+        cggtts.format(&mut writer).unwrap();
+
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        // total delays are not emitted as system delays
+        assert!(content.contains("TOT DLY =  440.5 ns (GPS C1), 450.0 ns (GPS P2)"));
+        assert!(!content.contains("SYS DLY"));
+
+        let mut reader = BufReader::new(content.as_bytes());
+        let parsed = CGGTTS::parse(&mut reader).unwrap();
+
+        assert_eq!(parsed.header.delay, cggtts.header.delay);
+        assert_eq!(parsed.tracks, cggtts.tracks);
+
+        // format back: unchanged
+        let mut writer = BufWriter::new(Vec::new());
+        parsed.format(&mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner().unwrap()).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn content_equality() {
+        let receiver = Hardware::default()
+            .with_manufacturer("GTR51")
+            .with_model("2204005")
+            .with_serial_number("1.12.0")
+            .with_release_year(2022)
+            .with_release_version("2.0");
+
+        let header = Header::default()
+            .with_station("LAB")
+            .with_channels(20)
+            .with_receiver_hardware(receiver.clone())
+            .with_ims_hardware(receiver)
+            .with_reference_frame("ITRF")
+            .with_comment("round trip")
+            .with_extra_field("PPS OFFSET", "12.5 ns")
+            .with_constellation(Constellation::Galileo);
+
+        let mut cggtts = CGGTTS::default().with_header(header);
+
+        for line in [
+            "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+            "E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 84",
+        ] {
+            cggtts.tracks.push(Track::from_str(line).unwrap());
+        }
+
+        let mut writer = BufWriter::new(Vec::new());
+        cggtts.format(&mut writer).unwrap();
+        let content = writer.into_inner().unwrap();
+
+        let mut parsed = CGGTTS::parse(&mut BufReader::new(content.as_slice())).unwrap();
+
+        // not formatted
+        assert_eq!(parsed.header.constellation, None);
+
+        assert!(parsed.content_eq(&cggtts));
+        assert!(cggtts.content_eq(&parsed));
+
+        // volatile fields
+        parsed.header.revision_date = Epoch::from_gregorian_utc_at_midnight(2024, 1, 1);
+        parsed.tag_provenance("parsed.txt");
+        assert!(parsed.content_eq(&cggtts));
+
+        let mut modified = parsed.clone();
+        modified.tracks[1].data.refsys += 1.0E-10;
+        assert!(!modified.content_eq(&cggtts));
+
+        let mut modified = parsed.clone();
+        modified.tracks.pop();
+        assert!(!modified.content_eq(&cggtts));
+
+        let mut modified = parsed.clone();
+        modified.header.station = "OTHER".to_string();
+        assert!(!modified.content_eq(&cggtts));
+
+        let mut modified = parsed;
+        modified.header.extra_fields.clear();
+        assert!(!modified.content_eq(&cggtts));
+
+        modified.header.format_extra_fields = false;
+        assert!(modified.content_eq(
+            &cggtts
+                .clone()
+                .with_header(cggtts.header.clone().with_extra_fields_formatting(false))
+        ));
+    }
+
+    #[test]
+    fn lossy_parsing() {
+        let mut cggtts = fixture_cggtts(3);
+
+        for (nth, track) in cggtts.tracks.iter_mut().enumerate() {
+            track.epoch = Epoch::from_mjd_utc(60258.0) + Duration::from_seconds(960.0 * nth as f64);
+        }
+
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            cggtts.format(&mut writer).unwrap();
+        }
+
+        let content = String::from_utf8(content).unwrap();
+        let mut lines = content.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+        let nb_lines = lines.len();
+
+        // corrupt first and last tracks
+        lines[nb_lines - 3].truncate(20);
+        lines[nb_lines - 1] = lines[nb_lines - 1].replacen("E03", "?03", 1);
+
+        let corrupted = lines.join("\n");
+
+        let (parsed, errors) =
+            CGGTTS::parse_lossy(&mut BufReader::new(corrupted.as_bytes())).unwrap();
+
+        assert_eq!(parsed.tracks.len(), 1);
+        assert_eq!(parsed.tracks[0], cggtts.tracks[1]);
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].0, nb_lines - 2);
+        assert_eq!(errors[1].0, nb_lines);
+
+        for (_, error) in errors.iter() {
+            assert!(matches!(error, ParsingError::TrackParsing(_)));
+        }
+
+        // standard parser silently drops them
+        let parsed = CGGTTS::parse(&mut BufReader::new(corrupted.as_bytes())).unwrap();
+        assert_eq!(parsed.tracks.len(), 1);
+    }
+
+    #[test]
+    fn footer_lines() {
+        let cggtts = fixture_cggtts(2);
+
+        let content = String::from_utf8(cggtts.to_vec().unwrap()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        let nb_lines = lines.len();
+
+        // repeated label rows, as appended by legacy concatenation tools
+        let labels = lines[nb_lines - 4..nb_lines - 2].join("\n");
+        assert!(labels.starts_with("SAT CL"));
+
+        let with_labels = format!("{}\n{}\n   \n", content.trim_end(), labels);
+
+        let strict = ParseOptions::default()
+            .with_strict_tracks(true)
+            .with_strict_crc(true)
+            .with_unknown_lines(true);
+
+        let parsed =
+            CGGTTS::parse_with_options(&mut BufReader::new(with_labels.as_bytes()), strict)
+                .unwrap();
+
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert!(parsed.unknown_lines.is_empty());
+
+        let (parsed, errors) =
+            CGGTTS::parse_lossy(&mut BufReader::new(with_labels.as_bytes())).unwrap();
+
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert!(errors.is_empty());
+
+        // any other footer line is reported, with its line number
+        let with_footer = format!("{}STATION FOOTER 1.0\n", with_labels);
+
+        let (parsed, errors) =
+            CGGTTS::parse_lossy(&mut BufReader::new(with_footer.as_bytes())).unwrap();
+
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, nb_lines + 4);
+        assert!(matches!(errors[0].1, ParsingError::TrackParsing(_)));
+
+        // end of file markers
+        for marker in ["EOF", "END OF FILE", "*** end of file ***", "=== EOF ==="] {
+            let with_marker = format!("{}{}\n\n", with_labels, marker);
+
+            let parsed =
+                CGGTTS::parse_with_options(&mut BufReader::new(with_marker.as_bytes()), strict)
+                    .unwrap();
+
+            assert_eq!(parsed.tracks, cggtts.tracks, "{}", marker);
+            assert!(parsed.unknown_lines.is_empty(), "{}", marker);
+        }
+
+        // footer lines are only tolerated after the last track
+        let last_track = lines[nb_lines - 1];
+        let within = format!(
+            "{}\nEOF\n{}\n",
+            lines[..nb_lines - 1].join("\n"),
+            last_track
+        );
+
+        let (parsed, errors) = CGGTTS::parse_lossy(&mut BufReader::new(within.as_bytes())).unwrap();
+
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, nb_lines);
+        assert!(matches!(errors[0].1, ParsingError::TrackParsing(_)));
+
+        let within = format!(
+            "{}\n{}\n{}\n",
+            lines[..nb_lines - 1].join("\n"),
+            labels,
+            last_track
+        );
+
+        let parsed = CGGTTS::parse_with_options(
+            &mut BufReader::new(within.as_bytes()),
+            ParseOptions::default().with_unknown_lines(true),
+        )
+        .unwrap();
+
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert_eq!(
+            parsed.unknown_lines,
+            vec![
+                (nb_lines, lines[nb_lines - 4].to_string()),
+                (nb_lines + 1, lines[nb_lines - 3].to_string()),
+            ]
+        );
+
+        assert!(matches!(
+            CGGTTS::parse_with_options(&mut BufReader::new(within.as_bytes()), strict),
+            Err(ParsingError::TrackParsing(_))
+        ));
+    }
+
+    #[test]
+    fn track_crcs_verification() {
+        let cggtts = fixture_cggtts(3);
+
+        // built tracks are not verified
+        assert!(CGGTTS::default()
+            .with_tracks(vec![Track::default()])
+            .verify_track_crcs()
+            .is_empty());
+
+        let content = String::from_utf8(cggtts.to_vec().unwrap()).unwrap();
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(content.as_bytes())).unwrap();
+        assert!(parsed.verify_track_crcs().is_empty());
+
+        // other column alignment (explicit + signs, IOE padding), correct checksum
+        let non_canonical = content.replacen(
+            "E03 FF 60258 001000  780 139  548      723788     14        -302    -14    2  76  325  -36   32   -3   20   20   3  0  0  E1 74",
+            "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+            1,
+        );
+        assert_ne!(non_canonical, content);
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(non_canonical.as_bytes())).unwrap();
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert!(parsed.verify_track_crcs().is_empty());
+
+        // corrupt the second track checksum
+        let corrupted = content.replace("E5a 30", "E5a 31");
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(corrupted.as_bytes())).unwrap();
+        assert_eq!(parsed.tracks, cggtts.tracks);
+        assert_eq!(parsed.verify_track_crcs(), vec![(1, 0x31, 0x30)]);
+    }
+
+    #[test]
+    fn parse_options() {
+        let mut cggtts = fixture_cggtts(0);
+
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+
+        for (nth, sv) in ["G01", "G02", "G03", "E01"].iter().enumerate() {
+            let mut track = Track::default()
+                .with_sv(SV::from_str(sv).unwrap())
+                .with_elevation_deg(45.0)
+                .with_azimuth_deg(120.0)
+                .with_carrier_code("L1C");
+            track.epoch = t0 + Duration::from_seconds(960.0 * nth as f64);
+            track.duration = Duration::from_seconds(780.0);
+            cggtts.tracks.push(track);
+        }
+
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            cggtts.format(&mut writer).unwrap();
+        }
+
+        let content = String::from_utf8(content).unwrap();
+        let mut lines = content.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+
+        // last track uses another constellation
+        let mixed = lines.pop().unwrap();
+        let nb_lines = lines.len();
+
+        // corrupt the second track checksum
+        let corrupted = lines[nb_lines - 2].clone();
+        let (track, ck) = corrupted.split_at(corrupted.len() - 2);
+        let ck = u8::from_str_radix(ck, 16).unwrap();
+        lines[nb_lines - 2] = format!("{}{:02X}", track, !ck);
+
+        let valid = lines.join("\n");
+
+        let parse = |content: &str, options: ParseOptions| {
+            CGGTTS::parse_with_options(&mut BufReader::new(content.as_bytes()), options)
+        };
+
+        // default options: checksum is not verified
+        let parsed = parse(&valid, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.tracks, cggtts.tracks[..3]);
+        assert!(parsed.unknown_lines.is_empty());
+
+        let parsed = parse(&valid, ParseOptions::default().with_strict_tracks(true)).unwrap();
+        assert_eq!(parsed.tracks.len(), 3);
+
+        // strict CRC
+        let strict_crc = ParseOptions::default().with_strict_crc(true);
+
+        let parsed = parse(&valid, strict_crc).unwrap();
+        assert_eq!(parsed.tracks.len(), 2);
+        assert_eq!(parsed.tracks[0], cggtts.tracks[0]);
+        assert_eq!(parsed.tracks[1], cggtts.tracks[2]);
+        assert!(parsed.unknown_lines.is_empty());
+
+        match parse(&valid, strict_crc.with_strict_tracks(true)) {
+            Err(ParsingError::TrackParsing(TrackError::ChecksumMismatch { found, computed })) => {
+                assert_eq!(found, !ck);
+                assert_eq!(computed, ck);
+            },
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // unknown lines
+        let with_footer = format!("{}\nSTATION FOOTER\n", valid);
+
+        let parsed = parse(&with_footer, ParseOptions::default()).unwrap();
+        assert_eq!(parsed.tracks.len(), 3);
+        assert!(parsed.unknown_lines.is_empty());
+
+        let parsed = parse(&with_footer, strict_crc.with_unknown_lines(true)).unwrap();
+        assert_eq!(parsed.tracks.len(), 2);
+        assert_eq!(
+            parsed.unknown_lines,
+            vec![
+                (nb_lines - 1, lines[nb_lines - 2].clone()),
+                (nb_lines + 1, "STATION FOOTER".to_string()),
+            ]
+        );
+
+        assert!(matches!(
+            parse(
+                &with_footer,
+                ParseOptions::default().with_strict_tracks(true)
+            ),
+            Err(ParsingError::TrackParsing(_))
+        ));
+
+        // mixed constellation
+        let mixed = format!("{}\n{}", valid, mixed);
+
+        assert!(matches!(
+            parse(&mixed, ParseOptions::default()),
+            Err(ParsingError::MixedConstellation)
+        ));
+
+        let parsed = parse(
+            &mixed,
+            ParseOptions::default().with_mixed_constellation(true),
+        )
+        .unwrap();
+        assert_eq!(parsed.tracks, cggtts.tracks);
+    }
+
+    #[test]
+    fn file_name_letter_override() {
+        let mut cggtts = fixture_cggtts(0);
+
+        let t0 = Epoch::from_str("2023-11-06T00:10:00 UTC").unwrap();
+
+        for sv in ["E01", "G01"] {
+            let mut track = Track::default().with_sv(SV::from_str(sv).unwrap());
+            track.epoch = t0;
+            cggtts.tracks.push(track);
+        }
+
+        assert_eq!(
+            cggtts.standardized_file_name(Some("LA"), Some("01")),
+            "ESLA0160.254"
+        );
+
+        assert_eq!(
+            cggtts.standardized_file_name_with_letter(Some("LA"), Some("01"), None),
+            cggtts.standardized_file_name(Some("LA"), Some("01")),
+        );
+
+        assert_eq!(
+            cggtts.standardized_file_name_with_letter(Some("LA"), Some("01"), Some('X')),
+            "XSLA0160.254"
+        );
+    }
+
+    #[test]
+    fn file_name_without_tracks() {
+        let header = Header::default().with_station("LAB");
+
+        let cggtts = CGGTTS::default().with_header(header.clone());
+        assert_eq!(
+            cggtts.standardized_file_name(Some("LA"), Some("01")),
+            "XZLA01dd.ddd"
+        );
+
+        let header = header
+            .with_constellation(Constellation::Galileo)
+            .with_nominal_mjd(60254);
+
+        let cggtts = CGGTTS::default().with_header(header);
+        assert_eq!(
+            cggtts.standardized_file_name(Some("LA"), Some("01")),
+            "EZLA0160.254"
+        );
+
+        // tracks prevail over the declarations
+        let mut track = Track::default().with_sv(SV::from_str("G01").unwrap());
+        track.epoch = Epoch::from_str("2023-11-07T00:10:00 UTC").unwrap();
+
+        let cggtts = cggtts.with_tracks(vec![track]);
+        assert_eq!(
+            cggtts.standardized_file_name(Some("LA"), Some("01")),
+            "GSLA0160.255"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "scheduler")]
+    fn custom_tracking() {
+        use crate::prelude::CommonViewPeriod;
+
+        let period =
+            CommonViewPeriod::default().with_tracking_duration(Duration::from_seconds(300.0));
+
+        let mut cggtts = CGGTTS::default();
+        assert!(cggtts.follows_tracking(&period));
+
+        for duration in [300.0, 300.5, 299.5] {
+            let mut track = Track::default().with_sv(SV::from_str("G01").unwrap());
+            track.duration = Duration::from_seconds(duration);
+            cggtts.tracks.push(track);
+        }
+
+        assert!(cggtts.follows_tracking(&period));
+        assert!(!cggtts.follows_bipm_tracking());
+
+        cggtts.tracks[1].duration = Duration::from_seconds(780.0);
+        assert!(!cggtts.follows_tracking(&period));
+    }
+
+    #[test]
+    #[cfg(feature = "scheduler")]
+    fn calendar_snapping() {
+        use crate::{errors::CalendarError, prelude::CommonViewCalendar};
+
+        let calendar = CommonViewCalendar::bipm();
+        let tolerance = Duration::from_seconds(10.0);
+
+        let t = Epoch::from_str("2023-11-10T14:00:00 UTC").unwrap();
+        let slot0 = calendar.next_period_start_after(t);
+        let slot1 = calendar.next_period_start_after(slot0);
+        let slot2 = calendar.next_period_start_after(slot1);
+
+        let g01 = SV::from_str("G01").unwrap();
+        let g02 = SV::from_str("G02").unwrap();
+
+        let track = |sv: SV, epoch: Epoch| {
+            let mut track = Track::default().with_sv(sv);
+            track.epoch = epoch;
+            track
+        };
+
+        let mut cggtts = CGGTTS::default().with_tracks(vec![
+            track(g01, slot0 + Duration::from_seconds(5.0)),
+            track(g02, slot0 - Duration::from_seconds(5.0)),
+            track(g01, slot1),
+            track(g01, slot2 + Duration::from_seconds(30.0)),
+        ]);
+
+        assert_eq!(cggtts.snap_to_calendar(&calendar, tolerance), Ok(2));
+
+        assert_eq!(cggtts.tracks[0].epoch, slot0);
+        assert_eq!(cggtts.tracks[1].epoch, slot0);
+        assert_eq!(cggtts.tracks[2].epoch, slot1);
+
+        // beyond tolerance
+        assert_eq!(cggtts.tracks[3].epoch, slot2 + Duration::from_seconds(30.0));
+
+        // already aligned
+        assert_eq!(cggtts.snap_to_calendar(&calendar, tolerance), Ok(0));
+
+        // same SV collapsing onto the same slot
+        let mut cggtts = CGGTTS::default().with_tracks(vec![
+            track(g01, slot1 - Duration::from_seconds(5.0)),
+            track(g01, slot1 + Duration::from_seconds(5.0)),
+        ]);
+
+        assert_eq!(
+            cggtts.snap_to_calendar(&calendar, tolerance),
+            Err(CalendarError::TrackCollision(g01, slot1))
+        );
+
+        // untouched
+        assert_eq!(cggtts.tracks[0].epoch, slot1 - Duration::from_seconds(5.0));
+    }
+
+    #[test]
+    fn missing_label_lines() {
+        let cggtts = fixture_cggtts(2);
+
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            cggtts.format(&mut writer).unwrap();
+        }
+
+        let content = String::from_utf8(content).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        let nb_lines = lines.len();
+
+        assert!(lines[nb_lines - 3].contains("hhmmss"));
+
+        // missing units line, then missing both label lines
+        for missing in [vec![nb_lines - 3], vec![nb_lines - 4, nb_lines - 3]] {
+            let truncated = lines
+                .iter()
+                .enumerate()
+                .filter_map(|(nth, line)| {
+                    if missing.contains(&nth) {
+                        None
+                    } else {
+                        Some(*line)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let parsed = CGGTTS::parse(&mut BufReader::new(truncated.as_bytes())).unwrap();
+            assert_eq!(parsed.tracks, cggtts.tracks);
+            assert_eq!(parsed.header.station, "LAB");
+
+            // line numbers remain correct
+            let corrupted = truncated.replacen("E08 FF", "?08 FF", 1);
+
+            let (parsed, errors) =
+                CGGTTS::parse_lossy(&mut BufReader::new(corrupted.as_bytes())).unwrap();
+
+            assert_eq!(parsed.tracks, cggtts.tracks[..1]);
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].0, nb_lines - missing.len());
+        }
+    }
+
+    #[test]
+    fn melting_pot() {
+        let mut cggtts = CGGTTS::default();
+
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+        let t1 = t0 + Duration::from_seconds(960.0);
+
+        for (sv, epoch, refsys) in [
+            ("G01", t0, 10.0E-9),
+            ("G02", t0, 20.0E-9),
+            ("G03", t0, 30.0E-9),
+            ("G01", t1, 40.0E-9),
+            ("G04", t1, 50.0E-9),
+        ] {
+            let mut track = Track::default().with_sv(SV::from_str(sv).unwrap());
+            track.epoch = epoch;
+            track.elevation_deg = 40.0;
+            track.data.refsys = refsys;
+            track.frc = "L1C".parse().unwrap();
+            cggtts.tracks.push(track);
+        }
+
+        let combined = cggtts.melting_pot();
+        assert_eq!(combined.tracks.len(), 2);
+
+        let g99 = SV::from_str("G99").unwrap();
+
+        for (track, (epoch, refsys, dsg)) in combined.tracks.iter().zip([
+            (t0, 20.0E-9, (200.0_f64 / 3.0).sqrt() * 1.0E-9),
+            (t1, 45.0E-9, 5.0E-9),
+        ]) {
+            assert_eq!(track.epoch, epoch);
+            assert_eq!(track.sv, g99);
+            assert_eq!(track.class, CommonViewClass::MultiChannel);
+            assert_eq!(track.frc, "L1C");
+            assert_eq!(track.elevation_deg, 40.0);
+            assert!((track.data.refsys - refsys).abs() < 1.0E-15);
+            assert!((track.data.dsg - dsg).abs() < 1.0E-15);
+        }
+    }
+
+    #[test]
+    fn concatenated_days() {
+        let header = Header::default().with_station("LAB");
+
+        let day = |t0: &str| {
+            let t0 = Epoch::from_str(t0).unwrap();
+            let mut cggtts = CGGTTS::default().with_header(header.clone());
+            for (nth, sv) in ["G01", "G02", "G03"].iter().enumerate() {
+                let mut track = Track::default()
+                    .with_sv(SV::from_str(sv).unwrap())
+                    .with_elevation_deg(45.0)
+                    .with_azimuth_deg(120.0)
+                    .with_carrier_code("L1C");
+                track.epoch = t0 + Duration::from_seconds(960.0 * nth as f64);
+                track.duration = Duration::from_seconds(780.0);
+                cggtts.tracks.push(track);
+            }
+            cggtts
+        };
+
+        let day1 = day("2020-01-01T00:02:00 UTC");
+        let day2 = day("2020-01-02T00:02:00 UTC");
+        let boundary = day2.tracks[0].epoch;
+
+        let mut concatenated = day1.clone();
+        concatenated.append_day(day2.clone()).unwrap();
+
+        assert_eq!(concatenated.tracks.len(), 6);
+        assert_eq!(concatenated.day_boundaries, vec![boundary]);
+
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            concatenated.format(&mut writer).unwrap();
+        }
+
+        let content = String::from_utf8(content).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+
+        // single separator, ahead of the second day
+        let separators = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        assert_eq!(separators.len(), 1);
+        assert_eq!(*separators[0].1, "# MJD 58850");
+        assert!(lines[separators[0].0 + 1].starts_with("G01"));
+
+        let parse = |content: &str, options: ParseOptions| {
+            CGGTTS::parse_with_options(&mut BufReader::new(content.as_bytes()), options)
+        };
+
+        // separators are tolerated, even in strict mode
+        let strict = ParseOptions::default()
+            .with_strict_tracks(true)
+            .with_strict_crc(true);
+
+        let parsed = parse(&content, strict).unwrap();
+        assert_eq!(parsed.tracks, concatenated.tracks);
+        assert!(parsed.day_boundaries.is_empty());
+
+        let parsed = parse(&content, strict.with_day_separators(true)).unwrap();
+        assert_eq!(parsed.tracks, concatenated.tracks);
+        assert_eq!(parsed.day_boundaries, vec![boundary]);
+
+        // other tools use free form separators
+        let content = content.replace("# MJD 58850", "#### 2020-01-02 ####");
+        let parsed = parse(&content, strict.with_day_separators(true)).unwrap();
+        assert_eq!(parsed.day_boundaries, vec![boundary]);
+
+        // headers must be identical
+        let mut day1 = day1;
+        let other = day2.with_header(header.with_station("OTHER"));
+        assert_eq!(day1.append_day(other), Err(MergeError::HeaderMismatch));
+    }
+
+    #[test]
+    fn shared_formatting_buffer() {
+        use crate::buffer::Utf8Buffer;
+        use std::fs::{read_to_string, remove_file, File};
+
+        let dir = std::env::temp_dir();
+        let mut buf = Utf8Buffer::new(1024);
+
+        for (nth, station) in ["LAB1", "LAB2", "LAB3"].iter().enumerate() {
+            let mut cggtts = CGGTTS::default().with_header(Header::default().with_station(station));
+
+            for line in [
+                "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+                "E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 84",
+            ] {
+                let mut track = Track::from_str(line).unwrap();
+                track.epoch += Duration::from_seconds(960.0 * nth as f64);
+                cggtts.tracks.push(track);
+            }
+
+            let path = dir.join(format!("cggtts-shared-buffer-{}.txt", nth));
+
+            let mut writer = BufWriter::new(File::create(&path).unwrap());
+            cggtts.format_with_buffer(&mut writer, &mut buf).unwrap();
+            drop(writer);
+
+            let content = read_to_string(&path).unwrap();
+            let _ = remove_file(&path);
+
+            // identical to a dedicated buffer
+            let mut model = BufWriter::new(Vec::new());
+            cggtts.format(&mut model).unwrap();
+            let model = String::from_utf8(model.into_inner().unwrap()).unwrap();
+
+            assert_eq!(content, model);
+
+            let parsed = CGGTTS::parse(&mut BufReader::new(content.as_bytes())).unwrap();
+            assert_eq!(parsed.header.station, *station);
+            assert_eq!(parsed.tracks, cggtts.tracks);
+        }
+    }
+
+    #[test]
+    fn channels_inference() {
+        use crate::prelude::WarningKind;
+
+        let t0 = Epoch::from_str("2023-11-10T14:46:00 UTC").unwrap();
+        let dt = Duration::from_seconds(960.0);
+
+        let mut cggtts = CGGTTS::default();
+
+        for (nth, nb_sv) in [3, 5, 1].iter().enumerate() {
+            for prn in 1..=*nb_sv {
+                let mut track = Track::default().with_sv(SV::new(Constellation::GPS, prn));
+                track.epoch = t0 + nth as f64 * dt;
+                track.class = CommonViewClass::MultiChannel;
+                cggtts.tracks.push(track);
+            }
+        }
+
+        assert_eq!(cggtts.header.nb_channels, 0);
+
+        let inconsistency = |cggtts: &CGGTTS| {
+            cggtts
+                .validate()
+                .into_iter()
+                .filter(|w| w.kind == WarningKind::ChannelNumber)
+                .map(|w| w.track_index)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(inconsistency(&cggtts), vec![Some(0)]);
+
+        cggtts.infer_channels();
+        assert_eq!(cggtts.header.nb_channels, 5);
+        assert!(inconsistency(&cggtts).is_empty());
+
+        // single channel tracks are not inconsistent
+        let mut single = cggtts.clone();
+        single.header.nb_channels = 0;
+        for trk in single.tracks.iter_mut() {
+            trk.class = CommonViewClass::SingleChannel;
+        }
+        assert!(inconsistency(&single).is_empty());
+
+        // at least one channel
+        let mut empty = CGGTTS::default();
+        empty.infer_channels();
+        assert_eq!(empty.header.nb_channels, 1);
+    }
+
+    #[test]
+    fn epoch_groups() {
+        let t0 = Epoch::from_str("2023-11-10T14:46:00 UTC").unwrap();
+        let dt = Duration::from_seconds(960.0);
+
+        let mut cggtts = CGGTTS::default();
+        assert_eq!(cggtts.epoch_groups().count(), 0);
+        assert!(cggtts.grouped_by_epoch().is_empty());
+
+        for (nth, nb_sv) in [3, 5, 1, 4].iter().enumerate() {
+            for prn in 1..=*nb_sv {
+                let mut track = Track::default().with_sv(SV::new(Constellation::GPS, prn));
+                track.epoch = t0 + nth as f64 * dt;
+                cggtts.tracks.push(track);
+            }
+        }
+
+        let groups = cggtts.epoch_groups().collect::<Vec<_>>();
+        assert_eq!(groups.len(), 4);
+
+        for (nth, ((epoch, tracks), nb_sv)) in groups.iter().zip([3, 5, 1, 4]).enumerate() {
+            assert_eq!(*epoch, t0 + nth as f64 * dt);
+            assert_eq!(tracks.len(), nb_sv);
+            assert!(tracks.iter().all(|trk| trk.epoch == *epoch));
+        }
+
+        // shuffled: only consecutive tracks are grouped
+        let mut shuffled = cggtts.clone();
+        shuffled.tracks.reverse();
+        shuffled.tracks.swap(0, 6);
+        assert!(shuffled.epoch_groups().count() > 4);
+
+        let grouped = shuffled.grouped_by_epoch();
+        assert_eq!(grouped.len(), 4);
+        for (nth, (epoch, tracks)) in grouped.iter().enumerate() {
+            assert_eq!(*epoch, t0 + nth as f64 * dt);
+            assert_eq!(tracks.len(), [3, 5, 1, 4][nth]);
+            assert!(tracks.iter().all(|trk| trk.epoch == *epoch));
+        }
+
+        shuffled.sort_tracks();
+        assert_eq!(
+            shuffled
+                .epoch_groups()
+                .map(|(_, tracks)| tracks.len())
+                .collect::<Vec<_>>(),
+            vec![3, 5, 1, 4]
+        );
+    }
+}
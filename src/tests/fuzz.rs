@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod test {
+    use crate::{
+        buffer::Utf8Buffer,
+        prelude::{Track, CGGTTS},
+        strategies,
+    };
+    use proptest::{option, prelude::*};
+    use std::{
+        io::{BufReader, BufWriter},
+        str::FromStr,
+    };
+
+    fn format(cggtts: &CGGTTS) -> String {
+        let mut content = Vec::new();
+        {
+            let mut writer = BufWriter::new(&mut content);
+            cggtts.format(&mut writer).unwrap();
+        }
+        String::from_utf8(content).unwrap()
+    }
+
+    fn parse(content: &[u8]) -> Result<CGGTTS, crate::ParsingError> {
+        CGGTTS::parse(&mut BufReader::new(content))
+    }
+
+    /// Columns of a track line (without CK): integers are compared by value,
+    /// so the sign and padding conventions do not matter.
+    fn columns(line: &str) -> Vec<Result<i64, String>> {
+        let mut columns = line
+            .split_ascii_whitespace()
+            .map(|column| column.parse::<i64>().map_err(|_| column.to_string()))
+            .collect::<Vec<_>>();
+        columns.pop();
+        columns
+    }
+
+    proptest! {
+        #[test]
+        fn format_parse_round_trip(cggtts in strategies::cggtts(8)) {
+            let x = format(&cggtts);
+
+            let first = format(&parse(x.as_bytes()).unwrap());
+            let second = format(&parse(first.as_bytes()).unwrap());
+
+            prop_assert_eq!(first, second);
+        }
+
+        #[test]
+        fn integer_columns_round_trip(
+            (elv, azth) in (0i64..=900, 0i64..3600),
+            (refsv, srsv, refsys, srsys) in (
+                -9_999_999_999i64..=99_999_999_999,
+                -99_999i64..=999_999,
+                -9_999_999_999i64..=99_999_999_999,
+                -99_999i64..=999_999,
+            ),
+            (dsg, ioe) in (0i64..=9_999, 0i64..=999),
+            (mdtr, smdt, mdio, smdi) in (
+                -999i64..=9_999,
+                -999i64..=9_999,
+                -999i64..=9_999,
+                -999i64..=9_999,
+            ),
+            iono in option::of((-999i64..=9_999, -99_999i64..=999_999, -99i64..=999)),
+        ) {
+            let iono = iono
+                .map(|(msio, smsi, isg)| format!("{} {} {} ", msio, smsi, isg))
+                .unwrap_or_default();
+
+            let line = format!(
+                "G01 FF 60000 000200  780 {} {} {} {} {} {} {} {} {} {} {} {} {}00 00 L1C 00",
+                elv, azth, refsv, srsv, refsys, srsys, dsg, ioe, mdtr, smdt, mdio, smdi, iono,
+            );
+
+            let track = Track::from_str(&line).unwrap();
+
+            let mut content = Vec::new();
+            {
+                let mut writer = BufWriter::new(&mut content);
+                track.format(&mut writer, &mut Utf8Buffer::new(256)).unwrap();
+            }
+
+            let formatted = String::from_utf8(content).unwrap();
+            prop_assert_eq!(columns(&formatted), columns(&line));
+
+            // bit identical, once formatted
+            let parsed = Track::from_str(&formatted).unwrap();
+            prop_assert_eq!(parsed, track);
+        }
+
+        #[test]
+        fn parse_arbitrary_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..1024)) {
+            let _ = parse(&bytes);
+        }
+
+        #[test]
+        fn parse_mutated_files(
+            cggtts in strategies::cggtts(4),
+            line in any::<prop::sample::Index>(),
+            cut in any::<prop::sample::Index>(),
+            insert in "\\PC{0,4}",
+        ) {
+            let content = format(&cggtts);
+            let mut lines = content.lines().map(|l| l.to_string()).collect::<Vec<_>>();
+
+            // truncate one line, and possibly insert random characters
+            let nth = line.index(lines.len());
+            let len = lines[nth].len();
+            lines[nth].truncate(cut.index(len + 1));
+            lines[nth].push_str(&insert);
+
+            let _ = parse(lines.join("\n").as_bytes());
+        }
+    }
+}
@@ -1,2 +1,4 @@
+mod alloc;
+mod cggtts;
+mod fuzz;
 mod parser;
-mod toolkit;
@@ -5,7 +5,7 @@ mod test {
     use crate::{
         header::CalibrationID,
         prelude::CGGTTS,
-        tests::toolkit::{random_name, track_dut_model_comparison},
+        testing::{fixture_cggtts, random_name},
         track::CommonViewClass,
     };
     use std::{
@@ -38,17 +38,102 @@ mod test {
             let parsed = CGGTTS::from_file(&file_name)
                 .unwrap_or_else(|e| panic!("failed to parse back \"{}\": {}", file_name, e));
 
-            assert_eq!(parsed.tracks.len(), cggtts.tracks.len());
-
-            for (dut, model) in parsed.tracks_iter().zip(cggtts.tracks_iter()) {
-                track_dut_model_comparison(dut, model);
-            }
+            assert!(
+                parsed.content_eq(&cggtts),
+                "{}: content changed after round trip",
+                path.display()
+            );
 
             // remove generated file
             let _ = std::fs::remove_file(&file_name);
         }
     }
 
+    #[test]
+    fn dataset_checksums() {
+        use crate::crc::Crc8;
+        use std::io::BufWriter;
+
+        let dir: PathBuf = PathBuf::new()
+            .join(env!("CARGO_MANIFEST_DIR"))
+            .join("data/CGGTTS");
+
+        // Header checksum, as described by the CKSUM line
+        fn header_checksum(content: &str) -> (u8, u8) {
+            let mut crc = Crc8::new();
+            for line in content.lines() {
+                if let Some(value) = line.strip_prefix("CKSUM = ") {
+                    crc.update(b"CKSUM = ");
+                    let value = u8::from_str_radix(value.trim(), 16).unwrap();
+                    return (crc.finalize(), value);
+                }
+                crc.update(line.as_bytes());
+            }
+            panic!("missing CKSUM");
+        }
+
+        for entry in read_dir(dir).unwrap() {
+            let entry = entry.unwrap();
+            let path = entry.path();
+            let is_hidden = path.file_name().unwrap().to_str().unwrap().starts_with('.');
+            if is_hidden || path.extension().map(|ext| ext == "gz").unwrap_or(false) {
+                continue;
+            }
+
+            // parser agrees with the file
+            let content = std::fs::read_to_string(&path).unwrap();
+            let (computed, stored) = header_checksum(&content);
+            assert_eq!(computed, stored, "{}: header checksum", path.display());
+
+            let cggtts = CGGTTS::from_file(&path).unwrap();
+
+            // formatter agrees with the same algorithm
+            let mut formatted = Vec::new();
+            {
+                let mut writer = BufWriter::new(&mut formatted);
+                cggtts.format(&mut writer).unwrap();
+            }
+
+            let formatted = String::from_utf8(formatted).unwrap();
+            let (computed, stored) = header_checksum(&formatted);
+            assert_eq!(computed, stored, "{}: formatted checksum", path.display());
+
+            for line in formatted.lines().rev().take(cggtts.tracks.len()) {
+                let (content, ck) = line.split_at(line.len() - 2);
+                let mut crc = Crc8::new();
+                crc.update(content.as_bytes());
+                assert_eq!(
+                    crc.finalize(),
+                    u8::from_str_radix(ck, 16).unwrap(),
+                    "{}: track checksum \"{}\"",
+                    path.display(),
+                    line
+                );
+            }
+
+            // public checksum reproduces the file CK values,
+            // which are retained by the parser
+            for (line, track) in content
+                .lines()
+                .rev()
+                .take(cggtts.tracks.len())
+                .zip(cggtts.tracks.iter().rev())
+            {
+                let line = line.trim_end();
+                let (content, ck) = line.split_at(line.len() - 2);
+                let ck = u8::from_str_radix(ck, 16).unwrap();
+                assert_eq!(
+                    crate::track::line_checksum(content),
+                    ck,
+                    "{}: track checksum \"{}\"",
+                    path.display(),
+                    line
+                );
+                assert_eq!(track.ck, Some(ck), "{}: parsed checksum", path.display());
+            }
+        }
+    }
+
     #[test]
     fn ezgtr60_258() {
         let path = Path::new(env!("CARGO_MANIFEST_DIR"))
@@ -61,7 +146,7 @@ mod test {
 
         assert_eq!(cggtts.header.receiver.manufacturer, "GTR51");
 
-        let ims = cggtts.header.ims_hardware.as_ref().unwrap();
+        let ims = cggtts.header.ims_hardware().unwrap();
         assert_eq!(ims.manufacturer, "GTR51");
 
         assert!(cggtts.is_galileo_cggtts());
@@ -71,6 +156,7 @@ mod test {
             Some(CalibrationID {
                 process_id: 1015,
                 year: 2021,
+                lab: None,
             })
         );
 
@@ -86,6 +172,9 @@ mod test {
                     assert!(track.follows_bipm_tracking());
                     assert!((track.elevation_deg - 13.9).abs() < 0.01);
                     assert!((track.azimuth_deg - 54.8).abs() < 0.01);
+                    assert!((track.data.refsv_seconds() - 72_378.8E-9).abs() < 1E-15);
+                    assert!((track.data.refsys_seconds() - -30.2E-9).abs() < 1E-15);
+                    assert!((track.data.srsys_s_per_s() - -1.4E-12).abs() < 1E-18);
                     assert!((track.data.refsv - 723788.0 * 0.1E-9) < 1E-10);
                     assert!((track.data.srsv - 14.0 * 0.1E-12) < 1E-12);
                     assert!((track.data.refsys - -302.0 * 0.1E-9) < 1E-10);
@@ -125,11 +214,7 @@ mod test {
         let parsed = CGGTTS::from_file(&file_name)
             .unwrap_or_else(|e| panic!("failed to parse back CGGTTS \"{}\": {}", file_name, e));
 
-        assert_eq!(parsed.tracks.len(), cggtts.tracks.len());
-
-        for (dut, model) in parsed.tracks_iter().zip(cggtts.tracks_iter()) {
-            track_dut_model_comparison(dut, model);
-        }
+        assert!(parsed.content_eq(&cggtts));
 
         let _ = remove_file(&file_name);
     }
@@ -145,8 +230,7 @@ mod test {
 
         let ims = cggtts
             .header
-            .ims_hardware
-            .as_ref()
+            .ims_hardware()
             .expect("failed to parse \"IMS=\"");
 
         assert_eq!(ims.manufacturer, "GTR51");
@@ -158,6 +242,7 @@ mod test {
             Some(CalibrationID {
                 process_id: 1015,
                 year: 2021,
+                lab: None,
             })
         );
 
@@ -211,12 +296,393 @@ mod test {
         let parsed = CGGTTS::from_file(&file_name)
             .unwrap_or_else(|e| panic!("failed to parse back CGGTTS \"{}\": {}", file_name, e));
 
-        assert_eq!(parsed.tracks.len(), cggtts.tracks.len());
+        assert!(parsed.content_eq(&cggtts));
+
+        let _ = remove_file(&file_name);
+    }
 
-        for (dut, model) in parsed.tracks_iter().zip(cggtts.tracks_iter()) {
-            track_dut_model_comparison(dut, model);
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn parallel_dataset_parsing() {
+        use crate::parse_many;
+
+        let dir: PathBuf = PathBuf::new()
+            .join(env!("CARGO_MANIFEST_DIR"))
+            .join("data/CGGTTS");
+
+        let paths = read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| !path.file_name().unwrap().to_str().unwrap().starts_with('.'))
+            .collect::<Vec<_>>();
+
+        let parallel = parse_many(&paths);
+        assert_eq!(parallel.len(), paths.len());
+
+        for (path, parsed) in paths.iter().zip(parallel.iter()) {
+            let sequential = CGGTTS::from_file(path).unwrap();
+            let parsed = parsed
+                .as_ref()
+                .unwrap_or_else(|e| panic!("failed to parse {}: {}", path.display(), e));
+
+            assert_eq!(parsed.tracks.len(), sequential.tracks.len());
+            assert_eq!(parsed.tracks, sequential.tracks);
         }
+    }
+
+    #[tokio::test]
+    #[cfg(feature = "tokio")]
+    async fn async_parsing() {
+        use std::io::{BufReader, BufWriter};
+
+        let cggtts = fixture_cggtts(2);
+
+        let mut writer = BufWriter::new(Vec::new());
+        cggtts.format(&mut writer).unwrap();
+        let bytes = writer.into_inner().unwrap();
+
+        let model = CGGTTS::parse(&mut BufReader::new(&bytes[..])).unwrap();
+
+        let reader = tokio::io::BufReader::new(&bytes[..]);
+        let dut = CGGTTS::from_async_reader(reader).await.unwrap();
+
+        assert_eq!(dut.header.station, "LAB");
+        assert_eq!(dut.tracks.len(), 2);
+        assert_eq!(dut.tracks, model.tracks);
+        assert!(dut.content_eq(&model));
+    }
+
+    #[test]
+    #[cfg(feature = "memmap2")]
+    fn mmap_parsing() {
+        let cggtts = fixture_cggtts(2);
+
+        let file_name = random_name(8);
+        cggtts.to_file(&file_name).unwrap();
+
+        let model = CGGTTS::from_file(&file_name).unwrap();
+        let dut = CGGTTS::from_mmap(&file_name).unwrap();
 
         let _ = remove_file(&file_name);
+
+        assert_eq!(dut.header.station, model.header.station);
+        assert_eq!(dut.tracks.len(), 2);
+        assert_eq!(dut.tracks, model.tracks);
+
+        assert!(CGGTTS::from_mmap("not-existing.txt").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "flate2")]
+    fn gzip_compression_levels() {
+        let cggtts = fixture_cggtts(2);
+
+        let file_name = random_name(8);
+        cggtts.to_file(&file_name).unwrap();
+        let model = CGGTTS::from_file(&file_name);
+        let _ = remove_file(&file_name);
+        let model = model.unwrap().to_vec().unwrap();
+
+        for level in [0, 9] {
+            let file_name = format!("{}.gz", random_name(8));
+            cggtts.to_gzip_file_with_level(&file_name, level).unwrap();
+
+            let parsed = CGGTTS::from_gzip_file(&file_name);
+            let _ = remove_file(&file_name);
+
+            let parsed = parsed.unwrap();
+            assert_eq!(parsed.header.station, "LAB");
+            assert_eq!(parsed.tracks, cggtts.tracks);
+            assert_eq!(parsed.to_vec().unwrap(), model);
+        }
+
+        let file_name = format!("{}.gz", random_name(8));
+        assert!(matches!(
+            cggtts.to_gzip_file_with_level(&file_name, 10),
+            Err(crate::errors::FormattingError::InvalidCompressionLevel(10))
+        ));
+        assert!(!Path::new(&file_name).exists());
+    }
+
+    #[test]
+    fn header_only_file() {
+        use crate::{
+            errors::ParsingError,
+            prelude::{Duration, ParseOptions},
+        };
+        use std::io::BufReader;
+
+        let content = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+REV DATE = 2023-06-27
+RCVR = GTR51 2204005 1.12.0 2022 2.0
+CH = 20
+IMS = GTR51 2204005 1.12.0 2022 2.0
+LAB = LAB
+X = +3970727.80 m
+Y = +1018888.02 m
+Z = +4870276.84 m
+FRAME = ITRF
+COMMENTS = NO COMMENTS
+INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021
+CAB DLY =  155.2 ns
+REF DLY =    0.0 ns
+REF = UTC(LAB)
+CKSUM = 37
+
+SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK
+             hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s.1ns.1ps/s.1ns
+";
+
+        // tolerated by default
+        let mut reader = BufReader::new(content.as_bytes());
+        let cggtts = CGGTTS::parse(&mut reader).unwrap();
+
+        assert!(cggtts.is_empty());
+        assert_eq!(cggtts.header.station, "LAB");
+        assert!(cggtts.first_epoch().is_none());
+        assert!(cggtts.last_epoch().is_none());
+        assert!(cggtts.first_sv().is_none());
+        assert_eq!(cggtts.total_duration(), Duration::ZERO);
+        assert_eq!(cggtts.span(), Duration::ZERO);
+        assert_eq!(cggtts.common_view_class(), CommonViewClass::SingleChannel);
+        assert!(cggtts.has_ionospheric_data());
+
+        let name = cggtts.standardized_file_name(None, Some("01"));
+        assert_eq!(name.len(), 12);
+        assert_eq!(&name[2..6], "LA01");
+
+        // rejected in strict mode
+        let options = ParseOptions::default().with_empty_rejection(true);
+
+        let mut reader = BufReader::new(content.as_bytes());
+        match CGGTTS::parse_with_options(&mut reader, options) {
+            Err(ParsingError::EmptyFile) => {},
+            other => panic!(
+                "expecting empty file error, got {:?}",
+                other.map(|c| c.tracks)
+            ),
+        }
+
+        // one valid track is enough
+        let content = format!(
+            "{}{}\n",
+            content,
+            "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5"
+        );
+
+        let mut reader = BufReader::new(content.as_bytes());
+        let cggtts = CGGTTS::parse_with_options(&mut reader, options).unwrap();
+        assert!(!cggtts.is_empty());
+    }
+
+    #[test]
+    fn progress_report() {
+        use crate::prelude::Duration;
+        use std::fs::File;
+
+        let mut cggtts = fixture_cggtts(1);
+        let track = cggtts.tracks.remove(0);
+
+        for nth in 0..95 {
+            let mut track = track.clone();
+            track.epoch += Duration::from_seconds(960.0 * nth as f64);
+            cggtts.tracks.push(track);
+        }
+
+        let file_name = random_name(8);
+        cggtts.to_file(&file_name).unwrap();
+
+        let size = std::fs::metadata(&file_name).unwrap().len();
+
+        let mut reports = Vec::<u64>::new();
+
+        let fd = File::open(&file_name).unwrap();
+        let parsed =
+            CGGTTS::parse_with_progress_interval(fd, size, 10, |consumed| reports.push(consumed))
+                .unwrap();
+
+        let model = CGGTTS::from_file(&file_name).unwrap();
+        let content = std::fs::read(&file_name).unwrap();
+        let _ = remove_file(&file_name);
+
+        assert_eq!(parsed.tracks, model.tracks);
+        assert_eq!(parsed.tracks.len(), 95);
+
+        // every 10 lines, then the final report
+        assert_eq!(reports.len(), 10);
+        assert!(reports.windows(2).all(|w| w[0] < w[1]), "{:?}", reports);
+        assert_eq!(reports.last(), Some(&size));
+
+        // default interval: final report only
+        let mut reports = Vec::<u64>::new();
+        CGGTTS::parse_with_progress(&content[..], size, |consumed| reports.push(consumed)).unwrap();
+        assert_eq!(reports, vec![size]);
+    }
+
+    #[test]
+    fn large_file_allocations() {
+        use crate::{
+            prelude::{Duration, Hardware, Header, Track},
+            tests::alloc::count_allocations,
+        };
+        use std::{io::BufReader, str::FromStr};
+
+        const NB_TRACKS: usize = 5_000;
+
+        let mut cggtts = CGGTTS::default().with_header(
+            Header::default()
+                .with_station("LAB")
+                .with_receiver_hardware(
+                    Hardware::default()
+                        .with_manufacturer("GTR51")
+                        .with_model("2204005")
+                        .with_serial_number("1.12.0")
+                        .with_release_year(2022)
+                        .with_release_version("2.0"),
+                )
+                .with_comment("large file"),
+        );
+
+        let track = Track::from_str(
+            "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+        )
+        .unwrap();
+
+        for nth in 0..NB_TRACKS {
+            let mut track = track.clone();
+            track.epoch += Duration::from_seconds(16.0 * nth as f64);
+            cggtts.tracks.push(track);
+        }
+
+        let file_name = random_name(8);
+        cggtts.to_file(&file_name).unwrap();
+        let content = std::fs::read(&file_name).unwrap();
+        let _ = remove_file(&file_name);
+
+        let nb_lines = content.iter().filter(|b| **b == b'\n').count();
+
+        let (header, header_allocations) =
+            count_allocations(|| Header::parse(&mut BufReader::new(&content[..])).unwrap());
+
+        let (parsed, allocations) = count_allocations(|| {
+            let mut reader = BufReader::new(&content[..]);
+            CGGTTS::parse_with_capacity(&mut reader, NB_TRACKS).unwrap()
+        });
+
+        // CRC verified, everything preserved
+        assert_eq!(header, cggtts.header);
+        assert_eq!(parsed.tracks, cggtts.tracks);
+
+        // only the retained data allocates: never once per line
+        assert!(
+            header_allocations < 20,
+            "{} allocations to parse the header",
+            header_allocations
+        );
+        assert!(
+            allocations < 30,
+            "{} allocations to parse {} lines",
+            allocations,
+            nb_lines
+        );
+    }
+
+    #[test]
+    fn newer_version_rejection() {
+        use crate::errors::ParsingError;
+        use std::io::BufReader;
+
+        let content = "CGGTTS     GENERIC DATA FORMAT VERSION = 3A
+REV DATE = 2023-06-27
+RCVR = GTR51 2204005 1.12.0 2022 2.0
+CH = 20
+";
+
+        let mut reader = BufReader::new(content.as_bytes());
+        match CGGTTS::parse(&mut reader) {
+            Err(ParsingError::NewerVersionUnsupported(version)) => assert_eq!(version, "3A"),
+            other => panic!("unexpected result: {:?}", other.map(|cggtts| cggtts.header)),
+        }
+
+        // malformed revisions are still reported as such
+        let content = content.replace("3A", "2?");
+        let mut reader = BufReader::new(content.as_bytes());
+        assert!(matches!(
+            CGGTTS::parse(&mut reader),
+            Err(ParsingError::NonSupportedRevision)
+        ));
+    }
+
+    #[test]
+    fn case_insensitive_keywords() {
+        use crate::crc::repair;
+        use std::io::BufReader;
+
+        // header of EZGTR60.258
+        let content = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+REV DATE = 2023-06-27
+RCVR = GTR51 2204005 1.12.0 2022 2.0
+CH = 20
+IMS = GTR51 2204005 1.12.0 2022 2.0
+LAB = LAB
+X = +3970727.80 m
+Y = +1018888.02 m
+Z = +4870276.84 m
+FRAME = ITRF
+COMMENTS = NO COMMENTS
+INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021
+CAB DLY =  155.2 ns
+REF DLY =    0.0 ns
+REF = UTC(LAB)
+CKSUM = 37
+
+SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK
+             hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s.1ns.1ps/s.1ns
+E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5
+E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 30
+";
+
+        let original = CGGTTS::parse(&mut BufReader::new(content.as_bytes())).unwrap();
+        assert_eq!(original.tracks.len(), 2);
+
+        // lowercase keywords (and CAL_ID, NO COMMENTS), values untouched
+        let (header, tracks) = content.split_at(content.find("\n\n").unwrap());
+
+        let mut lowercase = header
+            .lines()
+            .map(|line| {
+                let (key, value) = line.split_once('=').unwrap();
+                let value = value
+                    .replace("CAL_ID", "cal_id")
+                    .replace("NO COMMENTS", "no comments");
+                format!("{}={}", key.to_lowercase(), value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        lowercase.push_str(tracks);
+
+        for (mixed, lower) in [
+            ("Rev Date", "rev date"),
+            ("Cksum", "cksum"),
+            ("Int Dly", "int dly"),
+        ] {
+            lowercase = lowercase.replace(lower, mixed);
+        }
+
+        assert!(lowercase.starts_with("cggtts     generic data format version = 2E\n"));
+        assert!(lowercase.contains("Rev Date = 2023-06-27"));
+
+        // the checksum is computed over the original bytes
+        let mut repaired = Vec::new();
+        repair(BufReader::new(lowercase.as_bytes()), &mut repaired).unwrap();
+        let repaired = String::from_utf8(repaired).unwrap();
+
+        let parsed = CGGTTS::parse(&mut BufReader::new(repaired.as_bytes())).unwrap();
+        assert_eq!(parsed.header, original.header);
+        assert_eq!(parsed.tracks, original.tracks);
+
+        // which is still verified
+        assert!(CGGTTS::parse(&mut BufReader::new(lowercase.as_bytes())).is_err());
     }
 }
@@ -0,0 +1,451 @@
+//! Common view time transfer between two CGGTTS files.
+use std::collections::BTreeMap;
+
+use crate::prelude::{Duration, Epoch, Track, CGGTTS, SV};
+
+#[cfg(feature = "scheduler")]
+use crate::prelude::CommonViewCalendar;
+
+#[cfg(feature = "scheduler")]
+use hifitime::Unit;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// DSG resolution (s), used as the minimal uncertainty
+/// when weighting by inverse DSG.
+const DSG_RESOLUTION: f64 = 0.1E-9;
+
+/// [Weighting] applied to each common view pair.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Weighting {
+    /// All pairs have the same weight
+    #[default]
+    Uniform,
+    /// Weight is the product of the sine of both elevations,
+    /// to favor high elevation satellites. Satellites below
+    /// the horizon (negative elevation) get a null weight.
+    Elevation,
+    /// Weight is the inverse of the combined DSG variance.
+    InverseDsg,
+}
+
+impl Weighting {
+    /// Weight of this common view pair
+    fn weight(&self, lhs: &Track, rhs: &Track) -> f64 {
+        match self {
+            Self::Uniform => 1.0,
+            Self::Elevation => {
+                let lhs = lhs.elevation_deg.to_radians().sin().max(0.0);
+                let rhs = rhs.elevation_deg.to_radians().sin().max(0.0);
+                lhs * rhs
+            },
+            Self::InverseDsg => {
+                let variance = lhs.data.dsg.powi(2) + rhs.data.dsg.powi(2);
+                1.0 / variance.max(DSG_RESOLUTION.powi(2))
+            },
+        }
+    }
+}
+
+/// [TimeTransferOptions] to customize [CGGTTS::time_transfer_with_options].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeTransferOptions {
+    /// Maximal epoch difference between two [Track]s, for them
+    /// to be considered in common view. Default is zero:
+    /// both [Track]s must start at the same time.
+    pub tolerance: Duration,
+    /// [Weighting] used when averaging
+    pub weighting: Weighting,
+    /// When true, all satellites in common view are averaged,
+    /// producing one [ClockDifference] per common view epoch.
+    pub all_in_view: bool,
+    /// When true, the REFSYS of both files are corrected by the reference
+    /// time offsets declared in their header (refer to
+    /// [crate::prelude::Header::reference_offsets]).
+    pub reference_offsets: bool,
+}
+
+impl Default for TimeTransferOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: Duration::ZERO,
+            weighting: Weighting::default(),
+            all_in_view: false,
+            reference_offsets: false,
+        }
+    }
+}
+
+impl TimeTransferOptions {
+    /// Define epoch matching tolerance
+    pub fn with_tolerance(self, tolerance: Duration) -> Self {
+        let mut s = self;
+        s.tolerance = tolerance;
+        s
+    }
+
+    /// Define [Weighting]
+    pub fn with_weighting(self, weighting: Weighting) -> Self {
+        let mut s = self;
+        s.weighting = weighting;
+        s
+    }
+
+    /// Average all satellites in view
+    pub fn with_all_in_view(self) -> Self {
+        let mut s = self;
+        s.all_in_view = true;
+        s
+    }
+
+    /// Correct both files by their declared reference time offsets
+    pub fn with_reference_offsets(self) -> Self {
+        let mut s = self;
+        s.reference_offsets = true;
+        s
+    }
+}
+
+/// Reference time offset (s) at desired [Epoch], linearly interpolated
+/// between the declared offsets and held constant outside of them.
+/// Zero when no offsets were declared.
+fn reference_offset_at(offsets: &[(Epoch, f64)], t: Epoch) -> f64 {
+    let after = offsets.iter().position(|(epoch, _)| *epoch > t);
+
+    match after {
+        None => offsets
+            .last()
+            .map(|(_, offset)| *offset)
+            .unwrap_or_default(),
+        Some(0) => offsets[0].1,
+        Some(index) => {
+            let (t0, y0) = offsets[index - 1];
+            let (t1, y1) = offsets[index];
+            let dt = (t - t0).to_seconds() / (t1 - t0).to_seconds();
+            y0 + (y1 - y0) * dt
+        },
+    }
+}
+
+/// [ClockDifference] is the result of the common view time transfer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClockDifference {
+    /// Common view [Epoch], as defined by the local [Track]
+    pub epoch: Epoch,
+    /// [SV] in common view, or None when all satellites
+    /// in view were averaged.
+    pub sv: Option<SV>,
+    /// Local clock - remote clock (s)
+    pub offset: f64,
+    /// Weight of this solution: the sum of all pair weights
+    pub weight: f64,
+    /// Number of satellites that contributed
+    pub nb_sv: usize,
+}
+
+impl CGGTTS {
+    /// Common view time transfer between this (local) [CGGTTS]
+    /// and a remote [CGGTTS], with default [TimeTransferOptions]:
+    /// one [ClockDifference] per [Track] starting at the same [Epoch],
+    /// for the same [SV].
+    pub fn time_transfer(&self, rhs: &Self) -> Vec<ClockDifference> {
+        self.time_transfer_with_options(rhs, &TimeTransferOptions::default())
+    }
+
+    /// Common view time transfer between this (local) [CGGTTS]
+    /// and a remote [CGGTTS], with desired [TimeTransferOptions].
+    /// Each local [Track] is paired to the closest remote [Track]
+    /// of the same [SV], within the tolerance.
+    pub fn time_transfer_with_options(
+        &self,
+        rhs: &Self,
+        options: &TimeTransferOptions,
+    ) -> Vec<ClockDifference> {
+        let mut pairs = Vec::<ClockDifference>::new();
+
+        let (local_offsets, remote_offsets) = if options.reference_offsets {
+            let mut local = self.header.reference_offsets();
+            let mut remote = rhs.header.reference_offsets();
+            local.sort_by_key(|(epoch, _)| *epoch);
+            remote.sort_by_key(|(epoch, _)| *epoch);
+            (local, remote)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        for local in self.tracks.iter() {
+            let remote = rhs
+                .tracks
+                .iter()
+                .filter(|trk| trk.sv == local.sv)
+                .filter_map(|trk| {
+                    let dt = (trk.epoch - local.epoch).abs();
+                    if dt <= options.tolerance {
+                        Some((dt, trk))
+                    } else {
+                        None
+                    }
+                })
+                .min_by(|(a, _), (b, _)| a.cmp(b));
+
+            if let Some((_, remote)) = remote {
+                pairs.push(ClockDifference {
+                    epoch: local.epoch,
+                    sv: Some(local.sv),
+                    offset: (local.data.refsys - reference_offset_at(&local_offsets, local.epoch))
+                        - (remote.data.refsys - reference_offset_at(&remote_offsets, remote.epoch)),
+                    weight: options.weighting.weight(local, remote),
+                    nb_sv: 1,
+                });
+            }
+        }
+
+        if !options.all_in_view {
+            return pairs;
+        }
+
+        let mut epochs = BTreeMap::<Epoch, (f64, f64, usize)>::new();
+
+        for pair in pairs.iter() {
+            let (sum, weights, nb_sv) = epochs.entry(pair.epoch).or_insert((0.0, 0.0, 0));
+            *sum += pair.offset * pair.weight;
+            *weights += pair.weight;
+            *nb_sv += 1;
+        }
+
+        epochs
+            .into_iter()
+            .filter(|(_, (_, weights, _))| *weights > 0.0)
+            .map(|(epoch, (sum, weights, nb_sv))| ClockDifference {
+                epoch,
+                sv: None,
+                offset: sum / weights,
+                weight: weights,
+                nb_sv,
+            })
+            .collect()
+    }
+
+    /// Exports the REFSYS time series of this [CGGTTS], aligned on the
+    /// period starts of this [CommonViewCalendar], covering the span of this file.
+    /// [Track]s starting at the same slot are averaged, weighted by their inverse
+    /// DSG variance. Slots without any [Track] are explicitly reported as None,
+    /// so gaps remain visible when plotting or interpolating.
+    /// [Track]s should lie on the calendar, refer to [CGGTTS::snap_to_calendar].
+    #[cfg(feature = "scheduler")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
+    pub fn gridded_refsys(&self, calendar: &CommonViewCalendar) -> Vec<(Epoch, Option<f64>)> {
+        let mut series = Vec::<(Epoch, Option<f64>)>::new();
+
+        let epochs = self.tracks.iter().map(|trk| trk.epoch);
+
+        let (first, last) = match (epochs.clone().min(), epochs.max()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return series,
+        };
+
+        let mut slots = BTreeMap::<Epoch, (f64, f64)>::new();
+
+        for trk in self.tracks.iter() {
+            let weight = 1.0 / trk.data.dsg.powi(2).max(DSG_RESOLUTION.powi(2));
+            let (sum, weights) = slots.entry(trk.epoch).or_insert((0.0, 0.0));
+            *sum += trk.data.refsys * weight;
+            *weights += weight;
+        }
+
+        let mut slot = calendar.next_period_start_after(first - 1.0 * Unit::Nanosecond);
+
+        while slot <= last {
+            let value = slots.get(&slot).map(|(sum, weights)| sum / weights);
+
+            series.push((slot, value));
+            slot = calendar.next_period_start_after(slot);
+        }
+
+        series
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TimeTransferOptions, Weighting};
+    use crate::prelude::{Duration, Epoch, Track, CGGTTS, SV};
+    use std::str::FromStr;
+
+    fn track(sv: &str, epoch: Epoch, refsys: f64, elevation_deg: f64, dsg: f64) -> Track {
+        let mut track = Track::default().with_sv(SV::from_str(sv).unwrap());
+        track.epoch = epoch;
+        track.elevation_deg = elevation_deg;
+        track.data.refsys = refsys;
+        track.data.dsg = dsg;
+        track
+    }
+
+    #[test]
+    fn single_sv_matching() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+        let t1 = t0 + Duration::from_seconds(960.0);
+
+        let mut local = CGGTTS::default();
+        local.tracks.push(track("G01", t0, 10.0E-9, 45.0, 1.0E-9));
+        local.tracks.push(track("G02", t0, 20.0E-9, 45.0, 1.0E-9));
+        local.tracks.push(track("G01", t1, 30.0E-9, 45.0, 1.0E-9));
+
+        let mut remote = CGGTTS::default();
+        remote.tracks.push(track("G01", t0, 4.0E-9, 30.0, 1.0E-9));
+        remote.tracks.push(track("G03", t0, 5.0E-9, 30.0, 1.0E-9));
+
+        // remote receiver runs 30s late
+        remote.tracks.push(track(
+            "G01",
+            t1 + Duration::from_seconds(30.0),
+            6.0E-9,
+            30.0,
+            1.0E-9,
+        ));
+
+        let solutions = local.time_transfer(&remote);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].epoch, t0);
+        assert_eq!(solutions[0].sv, Some(SV::from_str("G01").unwrap()));
+        assert!((solutions[0].offset - 6.0E-9).abs() < 1.0E-15);
+
+        let options = TimeTransferOptions::default().with_tolerance(Duration::from_seconds(60.0));
+
+        let solutions = local.time_transfer_with_options(&remote, &options);
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(solutions[1].epoch, t1);
+        assert!((solutions[1].offset - 24.0E-9).abs() < 1.0E-15);
+    }
+
+    #[test]
+    fn all_in_view_averaging() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+        let t1 = t0 + Duration::from_seconds(960.0);
+
+        let mut local = CGGTTS::default();
+        local.tracks.push(track("G01", t0, 10.0E-9, 90.0, 1.0E-9));
+        local.tracks.push(track("G02", t0, 20.0E-9, 30.0, 2.0E-9));
+        local.tracks.push(track("G01", t1, 30.0E-9, 90.0, 1.0E-9));
+
+        let mut remote = CGGTTS::default();
+        remote.tracks.push(track("G01", t0, 0.0, 90.0, 1.0E-9));
+        remote.tracks.push(track("G02", t0, 0.0, 30.0, 2.0E-9));
+        remote.tracks.push(track("G01", t1, 0.0, 90.0, 1.0E-9));
+
+        // uniform
+        let options = TimeTransferOptions::default().with_all_in_view();
+        let solutions = local.time_transfer_with_options(&remote, &options);
+
+        assert_eq!(solutions.len(), 2);
+        assert_eq!(solutions[0].sv, None);
+        assert_eq!(solutions[0].nb_sv, 2);
+        assert!((solutions[0].offset - 15.0E-9).abs() < 1.0E-15);
+        assert_eq!(solutions[1].nb_sv, 1);
+        assert!((solutions[1].offset - 30.0E-9).abs() < 1.0E-15);
+
+        // elevation: weights are 1.0 and 0.25
+        let options = options.with_weighting(Weighting::Elevation);
+        let solutions = local.time_transfer_with_options(&remote, &options);
+        assert!((solutions[0].offset - 12.0E-9).abs() < 1.0E-15);
+        assert!((solutions[0].weight - 1.25).abs() < 1.0E-9);
+
+        // elevation: pairs below the horizon never contribute
+        let mut below = local.clone();
+        below.tracks[1].elevation_deg = -30.0;
+        let solutions = below.time_transfer_with_options(&remote, &options);
+        assert_eq!(solutions[0].nb_sv, 2);
+        assert!((solutions[0].offset - 10.0E-9).abs() < 1.0E-15);
+        assert!((solutions[0].weight - 1.0).abs() < 1.0E-9);
+
+        // both below the horizon: no weight at all, epoch is dropped
+        let mut remote_below = remote.clone();
+        remote_below.tracks[1].elevation_deg = -30.0;
+        below.tracks[0].elevation_deg = -10.0;
+        remote_below.tracks[0].elevation_deg = -10.0;
+        let solutions = below.time_transfer_with_options(&remote_below, &options);
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0].epoch, t1);
+
+        // inverse DSG: variances are 2 and 8 ns²
+        let options = options.with_weighting(Weighting::InverseDsg);
+        let solutions = local.time_transfer_with_options(&remote, &options);
+        assert!((solutions[0].offset - 12.0E-9).abs() < 1.0E-15);
+    }
+
+    #[test]
+    fn reference_offsets_correction() {
+        use crate::prelude::Header;
+
+        let t0 = Epoch::from_str("2023-11-10T00:00:00 UTC").unwrap();
+        let t1 = t0 + Duration::from_seconds(43_200.0);
+
+        let mut local = CGGTTS::default().with_header(Header::default().with_reference_offsets(&[
+            (t0, 2.0E-9),
+            (t0 + Duration::from_seconds(86_400.0), 4.0E-9),
+        ]));
+        local.tracks.push(track("G01", t0, 10.0E-9, 45.0, 1.0E-9));
+        local.tracks.push(track("G01", t1, 10.0E-9, 45.0, 1.0E-9));
+
+        let mut remote = CGGTTS::default()
+            .with_header(Header::default().with_reference_offsets(&[(t0, -1.0E-9)]));
+        remote.tracks.push(track("G01", t0, 4.0E-9, 45.0, 1.0E-9));
+        remote.tracks.push(track("G01", t1, 4.0E-9, 45.0, 1.0E-9));
+
+        // not applied by default
+        let solutions = local.time_transfer(&remote);
+        assert!((solutions[0].offset - 6.0E-9).abs() < 1.0E-15);
+        assert!((solutions[1].offset - 6.0E-9).abs() < 1.0E-15);
+
+        let options = TimeTransferOptions::default().with_reference_offsets();
+        let solutions = local.time_transfer_with_options(&remote, &options);
+
+        // (10 - 2) - (4 + 1)
+        assert!((solutions[0].offset - 3.0E-9).abs() < 1.0E-15);
+        // (10 - 3) - (4 + 1): interpolated at midday
+        assert!((solutions[1].offset - 2.0E-9).abs() < 1.0E-15);
+    }
+
+    #[test]
+    #[cfg(feature = "scheduler")]
+    fn gridded_refsys() {
+        use crate::prelude::CommonViewCalendar;
+
+        let calendar = CommonViewCalendar::bipm();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+
+        let t0 = calendar.next_period_start_after(t0);
+        let t1 = calendar.next_period_start_after(t0);
+        let t2 = calendar.next_period_start_after(t1);
+        let t3 = calendar.next_period_start_after(t2);
+
+        let mut cggtts = CGGTTS::default();
+
+        // two SV at t0: variances are 1 and 4 ns²
+        cggtts.tracks.push(track("G01", t0, 10.0E-9, 45.0, 1.0E-9));
+        cggtts.tracks.push(track("G02", t0, 20.0E-9, 45.0, 2.0E-9));
+        cggtts.tracks.push(track("G01", t1, 30.0E-9, 45.0, 1.0E-9));
+        // t2 is missing
+        cggtts.tracks.push(track("G01", t3, 40.0E-9, 45.0, 1.0E-9));
+
+        let series = cggtts.gridded_refsys(&calendar);
+        assert_eq!(series.len(), 4);
+
+        assert_eq!(series[0].0, t0);
+        assert!((series[0].1.unwrap() - 12.0E-9).abs() < 1.0E-15);
+
+        assert_eq!(series[1].0, t1);
+        assert!((series[1].1.unwrap() - 30.0E-9).abs() < 1.0E-15);
+
+        assert_eq!(series[2], (t2, None));
+
+        assert_eq!(series[3].0, t3);
+        assert!((series[3].1.unwrap() - 40.0E-9).abs() < 1.0E-15);
+
+        assert!(CGGTTS::default().gridded_refsys(&calendar).is_empty());
+    }
+}
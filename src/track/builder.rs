@@ -0,0 +1,267 @@
+//! [Track] builder
+use crate::track::{CommonViewClass, EphemerisData, IonosphericData, Track, TrackData};
+
+use gnss::prelude::SV;
+use hifitime::{Duration, Epoch};
+use thiserror::Error;
+
+/// Errors that may occur when building a [Track], naming the offending field.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TrackBuildError {
+    #[error("ELV: elevation {0}° should be within [0, 90]°")]
+    InvalidElevation(f64),
+    #[error("AZTH: azimuth {0}° should be within [0, 360[°")]
+    InvalidAzimuth(f64),
+    #[error("TRKL: tracking duration should be positive")]
+    InvalidDuration,
+    #[error("DSG: data sigma {0} should not be negative")]
+    NegativeDsg(f64),
+    #[error("FRC: missing carrier code")]
+    MissingCarrierCode,
+    #[error("FRC: \"{0}\" is not a valid carrier code (1 to 3 ASCII characters)")]
+    InvalidCarrierCode(String),
+}
+
+/// [TrackBuilder] is the fluent and verified way to build a [Track].
+/// ```
+/// use cggtts::prelude::*;
+/// use std::str::FromStr;
+///
+/// let track = TrackBuilder::default()
+///     .sv(SV::from_str("G01").unwrap())
+///     .epoch(Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap())
+///     .duration(Duration::from_seconds(780.0))
+///     .elevation(45.0)
+///     .azimuth(120.0)
+///     .frc("L1C")
+///     .build()
+///     .unwrap();
+///
+/// assert!(track.follows_bipm_tracking());
+///
+/// // the carrier code is mandatory
+/// let error = TrackBuilder::default()
+///     .sv(SV::from_str("G01").unwrap())
+///     .duration(Duration::from_seconds(780.0))
+///     .build()
+///     .unwrap_err();
+///
+/// assert_eq!(error, TrackBuildError::MissingCarrierCode);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TrackBuilder {
+    track: Track,
+    invalid_frc: Option<String>,
+}
+
+impl TrackBuilder {
+    /// Defines the tracked [SV]
+    pub fn sv(mut self, sv: SV) -> Self {
+        self.track.sv = sv;
+        self
+    }
+
+    /// Defines the [Track] [Epoch], which should be expressed in UTC.
+    pub fn epoch(mut self, epoch: Epoch) -> Self {
+        self.track.epoch = epoch;
+        self
+    }
+
+    /// Defines the tracking [Duration]
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.track.duration = duration;
+        self
+    }
+
+    /// Defines the [CommonViewClass]
+    pub fn class(mut self, class: CommonViewClass) -> Self {
+        self.track.class = class;
+        self
+    }
+
+    /// Defines the elevation (at track midpoint) in degrees
+    pub fn elevation(mut self, elevation_deg: f64) -> Self {
+        self.track.elevation_deg = elevation_deg;
+        self
+    }
+
+    /// Defines the azimuth (at track midpoint) in degrees
+    pub fn azimuth(mut self, azimuth_deg: f64) -> Self {
+        self.track.azimuth_deg = azimuth_deg;
+        self
+    }
+
+    /// Defines the [TrackData]
+    pub fn data(mut self, data: TrackData) -> Self {
+        self.track.data = data;
+        self
+    }
+
+    /// Defines the [EphemerisData], rendered in the `IOE` column.
+    pub fn ephemeris(mut self, ephemeris: EphemerisData) -> Self {
+        self.track.data.ioe = ephemeris.ioe();
+        self
+    }
+
+    /// Defines the [IonosphericData]
+    pub fn iono(mut self, iono: IonosphericData) -> Self {
+        self.track.iono = Some(iono);
+        self
+    }
+
+    /// Defines the receiver (hardware) channel, 0 if unknown
+    pub fn channel(mut self, channel: u8) -> Self {
+        self.track.hc = channel;
+        self
+    }
+
+    /// Defines the Glonass FDMA channel
+    pub fn fdma_channel(mut self, channel: u8) -> Self {
+        self.track.fdma_channel = Some(channel);
+        self
+    }
+
+    /// Defines the carrier frequency code (FRC)
+    pub fn frc(mut self, frc: &str) -> Self {
+        match frc.parse() {
+            Ok(frc) => {
+                self.track.frc = frc;
+                self.invalid_frc = None;
+            },
+            Err(_) => {
+                self.invalid_frc = Some(frc.to_string());
+            },
+        }
+        self
+    }
+
+    /// Builds the [Track], verifying that elevation is within [0, 90]°,
+    /// azimuth within [0, 360[°, that duration is positive,
+    /// that DSG is not negative and that the carrier code is defined and valid.
+    pub fn build(self) -> Result<Track, TrackBuildError> {
+        if let Some(frc) = self.invalid_frc {
+            return Err(TrackBuildError::InvalidCarrierCode(frc));
+        }
+
+        let track = self.track;
+
+        if !(0.0..=90.0).contains(&track.elevation_deg) {
+            return Err(TrackBuildError::InvalidElevation(track.elevation_deg));
+        }
+
+        if !(0.0..360.0).contains(&track.azimuth_deg) {
+            return Err(TrackBuildError::InvalidAzimuth(track.azimuth_deg));
+        }
+
+        if track.duration <= Duration::ZERO {
+            return Err(TrackBuildError::InvalidDuration);
+        }
+
+        if track.data.dsg.is_sign_negative() {
+            return Err(TrackBuildError::NegativeDsg(track.data.dsg));
+        }
+
+        if track.frc.is_empty() {
+            return Err(TrackBuildError::MissingCarrierCode);
+        }
+
+        Ok(track)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TrackBuildError, TrackBuilder};
+    use crate::prelude::{
+        CommonViewClass, Duration, EphemerisData, Epoch, IonosphericData, TrackData, SV,
+    };
+    use std::str::FromStr;
+
+    fn builder() -> TrackBuilder {
+        TrackBuilder::default()
+            .sv(SV::from_str("R24").unwrap())
+            .epoch(Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap())
+            .duration(Duration::from_seconds(780.0))
+            .elevation(39.4)
+            .azimuth(359.9)
+            .frc("L3P")
+    }
+
+    #[test]
+    fn valid_build() {
+        let iono = IonosphericData {
+            msio: 1.0E-9,
+            smsi: 0.0,
+            isg: 0.0,
+        };
+
+        let track = builder()
+            .class(CommonViewClass::MultiChannel)
+            .iono(iono)
+            .channel(3)
+            .fdma_channel(2)
+            .ephemeris(EphemerisData::GlonassQuarter(12))
+            .build()
+            .unwrap();
+
+        assert_eq!(track.sv, SV::from_str("R24").unwrap());
+        assert_eq!(track.class, CommonViewClass::MultiChannel);
+        assert_eq!(track.elevation_deg, 39.4);
+        assert_eq!(track.azimuth_deg, 359.9);
+        assert_eq!(track.iono, Some(iono));
+        assert_eq!(track.hc, 3);
+        assert_eq!(track.fdma_channel, Some(2));
+        assert_eq!(track.frc, "L3P");
+        assert_eq!(track.data.ioe, 12);
+        assert!(track.follows_bipm_tracking());
+    }
+
+    #[test]
+    fn invalid_builds() {
+        for elevation in [-0.1, 90.1, f64::NAN] {
+            match builder().elevation(elevation).build() {
+                Err(TrackBuildError::InvalidElevation(_)) => {},
+                other => panic!("unexpected result for {}: {:?}", elevation, other),
+            }
+        }
+
+        for azimuth in [-0.1, 360.0, f64::NAN] {
+            match builder().azimuth(azimuth).build() {
+                Err(TrackBuildError::InvalidAzimuth(_)) => {},
+                other => panic!("unexpected result for {}: {:?}", azimuth, other),
+            }
+        }
+
+        for duration in [Duration::ZERO, Duration::from_seconds(-780.0)] {
+            assert_eq!(
+                builder().duration(duration).build(),
+                Err(TrackBuildError::InvalidDuration)
+            );
+        }
+
+        let data = TrackData {
+            dsg: -1.0E-9,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            builder().data(data).build(),
+            Err(TrackBuildError::NegativeDsg(-1.0E-9))
+        );
+
+        assert_eq!(
+            builder().frc("L1CA").build(),
+            Err(TrackBuildError::InvalidCarrierCode("L1CA".to_string()))
+        );
+
+        // last valid definition prevails
+        assert!(builder().frc("L1CA").frc("L1C").build().is_ok());
+
+        assert_eq!(
+            TrackBuilder::default()
+                .duration(Duration::from_seconds(780.0))
+                .build(),
+            Err(TrackBuildError::MissingCarrierCode)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Carrier frequency code (FRC)
+use crate::track::Error;
+
+use std::convert::TryFrom;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Maximal length of a [CarrierCode], which is the FRC column width.
+const MAX_LEN: usize = 3;
+
+/// [CarrierCode] is the carrier frequency code (FRC) of a [crate::prelude::Track],
+/// for example "L1C" or "E5a". It is stored inline (1 to 3 ASCII characters),
+/// so tracks do not require a heap allocation. [CarrierCode] dereferences
+/// to [str] and may be compared to strings directly.
+/// ```
+/// use cggtts::prelude::CarrierCode;
+/// use std::str::FromStr;
+///
+/// let code = CarrierCode::from_str("E5a").unwrap();
+/// assert_eq!(code, "E5a");
+/// assert_eq!(code.to_string(), "E5a");
+/// assert!(code.starts_with("E5"));
+///
+/// // does not fit in the FRC column
+/// assert!(CarrierCode::from_str("L1CA").is_err());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(into = "String", try_from = "String")
+)]
+pub struct CarrierCode {
+    bytes: [u8; MAX_LEN],
+    len: u8,
+}
+
+impl CarrierCode {
+    /// Returns this [CarrierCode] as [str]
+    pub fn as_str(&self) -> &str {
+        // content is ASCII, verified on construction
+        std::str::from_utf8(&self.bytes[..self.len as usize]).unwrap_or_default()
+    }
+}
+
+impl CarrierCode {
+    /// Builds a [CarrierCode] from any input, without failure: the first
+    /// (up to 3) ASCII non whitespace characters are preserved, others are dropped.
+    /// An input without any such character results in the (empty) default code.
+    pub(crate) fn truncated(s: &str) -> Self {
+        let mut bytes = [0u8; MAX_LEN];
+        let mut len = 0;
+
+        for b in s.bytes().filter(|b| b.is_ascii_graphic()).take(MAX_LEN) {
+            bytes[len] = b;
+            len += 1;
+        }
+
+        Self {
+            bytes,
+            len: len as u8,
+        }
+    }
+}
+
+impl std::str::FromStr for CarrierCode {
+    type Err = Error;
+
+    /// Builds a [CarrierCode] from 1 to 3 ASCII (non whitespace) characters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.is_empty() || s.len() > MAX_LEN || !s.bytes().all(|b| b.is_ascii_graphic()) {
+            return Err(Error::InvalidCarrierCode);
+        }
+
+        let mut bytes = [0u8; MAX_LEN];
+        bytes[..s.len()].copy_from_slice(s.as_bytes());
+
+        Ok(Self {
+            bytes,
+            len: s.len() as u8,
+        })
+    }
+}
+
+/// The empty (default) code is accepted, so any [CarrierCode]
+/// (de)serializes.
+impl TryFrom<String> for CarrierCode {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s.is_empty() {
+            Ok(Self::default())
+        } else {
+            s.parse()
+        }
+    }
+}
+
+impl From<CarrierCode> for String {
+    fn from(code: CarrierCode) -> Self {
+        code.as_str().to_string()
+    }
+}
+
+impl std::ops::Deref for CarrierCode {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for CarrierCode {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for CarrierCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // supports padding, like any str
+        self.as_str().fmt(f)
+    }
+}
+
+impl PartialEq<str> for CarrierCode {
+    fn eq(&self, rhs: &str) -> bool {
+        self.as_str() == rhs
+    }
+}
+
+impl PartialEq<&str> for CarrierCode {
+    fn eq(&self, rhs: &&str) -> bool {
+        self.as_str() == *rhs
+    }
+}
+
+impl PartialEq<String> for CarrierCode {
+    fn eq(&self, rhs: &String) -> bool {
+        self.as_str() == rhs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CarrierCode;
+    use crate::prelude::{CommonViewClass, Duration, Epoch, TrackData, SV};
+    use crate::track::{Error, Track};
+    use std::str::FromStr;
+
+    #[test]
+    fn carrier_code() {
+        for code in ["C1", "L1C", "E5a", "B1i", "S"] {
+            let frc = CarrierCode::from_str(code).unwrap();
+            assert_eq!(frc, code);
+            assert_eq!(frc.len(), code.len());
+            assert_eq!(format!("{:>3}", frc), format!("{:>3}", code));
+        }
+
+        assert_eq!(CarrierCode::default(), "");
+
+        for code in ["", " ", "L1CA", "E5 a", "Ł1"] {
+            assert_eq!(CarrierCode::from_str(code), Err(Error::InvalidCarrierCode));
+        }
+
+        for (code, truncated) in [
+            ("L1C", "L1C"),
+            (" E5a ", "E5a"),
+            ("L1CA", "L1C"),
+            ("E5 a", "E5a"),
+            ("Ł1", "1"),
+            ("", ""),
+            (" ", ""),
+        ] {
+            assert_eq!(CarrierCode::truncated(code), truncated, "{}", code);
+
+            // constructors never panic
+            let track = Track::new(
+                SV::default(),
+                Epoch::default(),
+                Duration::default(),
+                CommonViewClass::default(),
+                45.0,
+                120.0,
+                TrackData::default(),
+                None,
+                0,
+                code,
+            );
+            assert_eq!(track.frc, truncated);
+
+            let track = Track::new_glonass(
+                SV::default(),
+                Epoch::default(),
+                Duration::default(),
+                CommonViewClass::default(),
+                45.0,
+                120.0,
+                TrackData::default(),
+                None,
+                0,
+                1,
+                code,
+            );
+            assert_eq!(track.frc, truncated);
+
+            assert_eq!(Track::default().with_carrier_code(code).frc, truncated);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn carrier_code_serde() {
+        for code in ["L1C", "E5a", ""] {
+            let frc = CarrierCode::truncated(code);
+            let json = serde_json::to_string(&frc).unwrap();
+            assert_eq!(json, format!("\"{}\"", code));
+            assert_eq!(serde_json::from_str::<CarrierCode>(&json).unwrap(), frc);
+        }
+
+        assert!(serde_json::from_str::<CarrierCode>("\"L1CA\"").is_err());
+
+        // default tracks round trip
+        let track = Track::default();
+        let json = serde_json::to_string(&track).unwrap();
+        assert_eq!(serde_json::from_str::<Track>(&json).unwrap(), track);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn track_size() {
+        // a Track no longer requires a heap allocation (unless
+        // tagged with its provenance), make sure its (inline) size does not regress.
+        assert_eq!(std::mem::size_of::<CarrierCode>(), 4);
+        assert_eq!(std::mem::size_of::<Track>(), 208);
+    }
+}
@@ -35,13 +35,18 @@ impl std::fmt::UpperHex for CommonViewClass {
 
 impl std::str::FromStr for CommonViewClass {
     type Err = Error;
+
+    /// Parses the CL column: "FF" (multi channel) or "99" (single channel).
+    /// Lowercase hexadecimal, found in older files, and the readable
+    /// [std::fmt::Display] representation are accepted as well.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.eq("FF") {
+        let trimmed = s.trim();
+        if trimmed.eq_ignore_ascii_case("FF") || trimmed.eq_ignore_ascii_case("Multi Channel") {
             Ok(Self::MultiChannel)
-        } else if s.eq("99") {
+        } else if trimmed.eq("99") || trimmed.eq_ignore_ascii_case("Single Channel") {
             Ok(Self::SingleChannel)
         } else {
-            Err(Error::UnknownClass)
+            Err(Error::UnknownClass(s.to_string()))
         }
     }
 }
@@ -49,18 +54,39 @@ impl std::str::FromStr for CommonViewClass {
 #[cfg(test)]
 mod test {
     use super::CommonViewClass;
+    use crate::track::Error;
     use std::str::FromStr;
+
     #[test]
     fn cv_class() {
         assert_eq!(format!("{:X}", CommonViewClass::MultiChannel), "FF");
         assert_eq!(format!("{:X}", CommonViewClass::SingleChannel), "99");
+
+        for (content, class) in [
+            ("FF", CommonViewClass::MultiChannel),
+            ("ff", CommonViewClass::MultiChannel),
+            (" FF ", CommonViewClass::MultiChannel),
+            ("Multi Channel", CommonViewClass::MultiChannel),
+            ("99", CommonViewClass::SingleChannel),
+            ("Single Channel", CommonViewClass::SingleChannel),
+        ] {
+            assert_eq!(CommonViewClass::from_str(content), Ok(class));
+        }
+
+        for class in [
+            CommonViewClass::SingleChannel,
+            CommonViewClass::MultiChannel,
+        ] {
+            assert_eq!(CommonViewClass::from_str(&class.to_string()), Ok(class));
+            assert_eq!(
+                CommonViewClass::from_str(&format!("{:X}", class)),
+                Ok(class)
+            );
+        }
+
         assert_eq!(
-            CommonViewClass::from_str("FF"),
-            Ok(CommonViewClass::MultiChannel)
-        );
-        assert_eq!(
-            CommonViewClass::from_str("FF"),
-            Ok(CommonViewClass::MultiChannel)
+            CommonViewClass::from_str("9F"),
+            Err(Error::UnknownClass("9F".to_string()))
         );
     }
 }
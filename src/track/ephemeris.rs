@@ -0,0 +1,182 @@
+//! Typed ephemeris data, rendered in the IOE column
+use gnss::prelude::Constellation;
+use hifitime::{Epoch, TimeScale, Unit};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [EphemerisData] describes the ephemeris used in the computation
+/// of a [Track](crate::prelude::Track). It is rendered in the `IOE` column,
+/// whose meaning depends on the [Constellation].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EphemerisData {
+    /// Issue of Ephemeris (GPS, Galileo, QZSS..)
+    Ioe(u16),
+    /// BeiDou hour of clock (0-23)
+    BeidouHour(u8),
+    /// Glonass date of ephemeris, as daily quarter of hour (1-96),
+    /// starting at 1 for 00:00:00 midnight (Moscow time).
+    GlonassQuarter(u8),
+}
+
+/// [IoeKind] describes how the `IOE` column should be interpreted,
+/// which depends on the [Constellation].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IoeKind {
+    /// Issue of Ephemeris (GPS, Galileo, QZSS..)
+    IssueOfEphemeris,
+    /// BeiDou hour of clock (0-23)
+    BeidouHour,
+    /// Glonass date of ephemeris, as daily quarter of hour (1-96)
+    GlonassQuarter,
+}
+
+impl From<Constellation> for IoeKind {
+    fn from(constellation: Constellation) -> Self {
+        match constellation {
+            Constellation::BeiDou => Self::BeidouHour,
+            Constellation::Glonass => Self::GlonassQuarter,
+            _ => Self::IssueOfEphemeris,
+        }
+    }
+}
+
+impl Default for EphemerisData {
+    fn default() -> Self {
+        Self::Ioe(0)
+    }
+}
+
+impl EphemerisData {
+    /// Interprets the `IOE` column value, for this [Constellation].
+    /// Values that do not fit the BeiDou hour (0-23) or Glonass quarter (1-96)
+    /// ranges are preserved as a raw [EphemerisData::Ioe].
+    pub fn from_ioe(constellation: Constellation, ioe: u16) -> Self {
+        match (constellation, ioe) {
+            (Constellation::BeiDou, 0..=23) => Self::BeidouHour(ioe as u8),
+            (Constellation::Glonass, 1..=96) => Self::GlonassQuarter(ioe as u8),
+            _ => Self::Ioe(ioe),
+        }
+    }
+
+    /// Builds [EphemerisData::BeidouHour] from the ephemeris clock [Epoch]
+    /// (toc), whatever its [TimeScale]: the hour is counted in BDT.
+    pub fn from_beidou_epoch(toc: Epoch) -> Self {
+        let (_, _, _, hour, _, _, _) = toc.to_gregorian(TimeScale::BDT);
+        Self::BeidouHour(hour)
+    }
+
+    /// Builds [EphemerisData::GlonassQuarter] from the ephemeris reference
+    /// [Epoch] (tb), whatever its [TimeScale]. Like tb, the quarter of hour
+    /// is counted in Moscow time (UTC + 3h).
+    pub fn from_glonass_epoch(tb: Epoch) -> Self {
+        let moscow = tb + 3 * Unit::Hour;
+        let (_, _, _, hour, minute, _, _) = moscow.to_gregorian(TimeScale::UTC);
+        Self::GlonassQuarter(hour * 4 + minute / 15 + 1)
+    }
+
+    /// Returns the [IoeKind] of this [EphemerisData].
+    pub fn kind(&self) -> IoeKind {
+        match self {
+            Self::Ioe(_) => IoeKind::IssueOfEphemeris,
+            Self::BeidouHour(_) => IoeKind::BeidouHour,
+            Self::GlonassQuarter(_) => IoeKind::GlonassQuarter,
+        }
+    }
+
+    /// Returns the value rendered in the `IOE` column.
+    pub fn ioe(&self) -> u16 {
+        match self {
+            Self::Ioe(ioe) => *ioe,
+            Self::BeidouHour(hour) => *hour as u16,
+            Self::GlonassQuarter(quarter) => *quarter as u16,
+        }
+    }
+}
+
+impl From<EphemerisData> for u16 {
+    fn from(data: EphemerisData) -> u16 {
+        data.ioe()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EphemerisData, IoeKind};
+    use gnss::prelude::Constellation;
+    use hifitime::Epoch;
+    use std::str::FromStr;
+
+    #[test]
+    fn ioe_kind() {
+        for (constellation, kind) in [
+            (Constellation::GPS, IoeKind::IssueOfEphemeris),
+            (Constellation::Galileo, IoeKind::IssueOfEphemeris),
+            (Constellation::QZSS, IoeKind::IssueOfEphemeris),
+            (Constellation::BeiDou, IoeKind::BeidouHour),
+            (Constellation::Glonass, IoeKind::GlonassQuarter),
+        ] {
+            assert_eq!(IoeKind::from(constellation), kind);
+            assert_eq!(EphemerisData::from_ioe(constellation, 1).kind(), kind);
+        }
+    }
+
+    #[test]
+    fn ioe_out_of_range() {
+        for (constellation, ioe, expected) in [
+            (Constellation::BeiDou, 0, EphemerisData::BeidouHour(0)),
+            (Constellation::BeiDou, 23, EphemerisData::BeidouHour(23)),
+            (Constellation::BeiDou, 24, EphemerisData::Ioe(24)),
+            (Constellation::BeiDou, 300, EphemerisData::Ioe(300)),
+            (Constellation::Glonass, 0, EphemerisData::Ioe(0)),
+            (Constellation::Glonass, 1, EphemerisData::GlonassQuarter(1)),
+            (
+                Constellation::Glonass,
+                96,
+                EphemerisData::GlonassQuarter(96),
+            ),
+            (Constellation::Glonass, 353, EphemerisData::Ioe(353)),
+        ] {
+            let data = EphemerisData::from_ioe(constellation, ioe);
+            assert_eq!(data, expected, "failed for {} {}", constellation, ioe);
+            assert_eq!(data.ioe(), ioe);
+        }
+    }
+
+    #[test]
+    fn ephemeris_from_epoch() {
+        for (toc, hour) in [
+            ("2023-01-01T00:00:00 BDT", 0),
+            ("2023-01-01T13:59:59 BDT", 13),
+            ("2023-01-01T23:30:00 BDT", 23),
+            // BDT = UTC + 4s (2023)
+            ("2023-01-01T12:59:58 UTC", 13),
+        ] {
+            let toc = Epoch::from_str(toc).unwrap();
+            assert_eq!(
+                EphemerisData::from_beidou_epoch(toc),
+                EphemerisData::BeidouHour(hour),
+                "failed for {}",
+                toc
+            );
+        }
+
+        for (tb, quarter) in [
+            ("2023-01-01T21:00:00 UTC", 1),
+            ("2023-01-01T21:14:59 UTC", 1),
+            ("2023-01-01T21:15:00 UTC", 2),
+            ("2023-01-01T00:00:00 UTC", 13),
+            ("2023-01-01T20:45:00 UTC", 96),
+        ] {
+            let tb = Epoch::from_str(tb).unwrap();
+            assert_eq!(
+                EphemerisData::from_glonass_epoch(tb),
+                EphemerisData::GlonassQuarter(quarter),
+                "failed for {}",
+                tb
+            );
+        }
+    }
+}
@@ -1,26 +1,52 @@
-use crate::{buffer::Utf8Buffer, errors::FormattingError, prelude::Track};
+use crate::{buffer::Utf8Buffer, crc::Crc8, errors::FormattingError, prelude::Track};
 
 use std::io::{BufWriter, Write};
 
 use std::cmp::{max as cmp_max, min as cmp_min};
 
-fn fmt_saturated<T: std::cmp::Ord + std::fmt::Display>(nb: T, sat: T, padding: usize) -> String {
-    format!("{:>padding$}", std::cmp::min(nb, sat))
-}
-
-fn fmt_saturated_f64(nb: f64, scaling: f64, sat: i64, padding: usize) -> String {
+/// Scales and rounds `nb`, saturated to `sat` (one digit less when negative, for the sign).
+/// Parsed values are recovered exactly: once scaled back, they lie within a few ULPs
+/// of the original integer, far from any rounding tie.
+fn saturated_f64(nb: f64, scaling: f64, sat: i64) -> i64 {
     let scaled = (nb * scaling).round() as i64;
     if scaled.is_negative() {
-        format!(
-            "{:>padding$}",
-            cmp_max(scaled, -sat / 10),
-            padding = padding
-        ) // remove 1 digit for sign
+        cmp_max(scaled, -sat / 10)
     } else {
-        format!("{:>padding$}", cmp_min(scaled, sat), padding = padding)
+        cmp_min(scaled, sat)
     }
 }
 
+/// Elevation, in the 0.1° ELV column: clamped to [0, 90]°
+fn elevation_column(elevation_deg: f64) -> i64 {
+    ((elevation_deg * 10.0).round() as i64).clamp(0, 900)
+}
+
+/// Azimuth, in the 0.1° AZTH column: wrapped to [0, 360[°,
+/// once rounded, so 359.97° is formatted as 0.0°.
+fn azimuth_column(azimuth_deg: f64) -> i64 {
+    ((azimuth_deg * 10.0).round() as i64).rem_euclid(3600)
+}
+
+/// Computes the checksum (CK column) of a track line, with the exact
+/// algorithm used by [Track::format]: the modulo 256 sum of every byte
+/// of `line_without_ck`, which is the complete line up to and including
+/// the space that precedes the CK column (that space is covered, like
+/// any other column separator). Carriage return and line feed bytes are ignored.
+/// ```
+/// use cggtts::track::line_checksum;
+///
+/// let line = "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5";
+/// let (content, ck) = line.split_at(line.len() - 2);
+///
+/// assert!(content.ends_with(' '));
+/// assert_eq!(line_checksum(content), u8::from_str_radix(ck, 16).unwrap());
+/// ```
+pub fn line_checksum(line_without_ck: &str) -> u8 {
+    let mut crc = Crc8::new();
+    crc.update(line_without_ck.as_bytes());
+    crc.finalize()
+}
+
 impl Track {
     /// Format [Track] into mutable [BufWriter].
     /// Requires a pre-allocated [Utf8Buffer].
@@ -29,114 +55,101 @@ impl Track {
         writer: &mut BufWriter<W>,
         buffer: &mut Utf8Buffer,
     ) -> Result<(), FormattingError> {
+        self.push_fields(buffer)?;
+
+        // ready to proceed to calculation
+        let crc = line_checksum(buffer.to_utf8_ascii()?);
+
+        // append CRC
+        write!(buffer, "{:02X}", crc)?;
+
+        // interprate
+        let utf8 = buffer.to_utf8_ascii()?; // we will never format bad Utf8
+
+        // forward to user buffer
+        writer.write_all(utf8.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Returns the checksum (CK column) of this [Track],
+    /// as it would be formatted, see [Self::format].
+    pub fn crc(&self) -> u8 {
+        let mut buffer = Utf8Buffer::new(128);
+        self.push_fields(&mut buffer)
+            .expect("in memory formatting is infallible");
+        let content = buffer.to_utf8_ascii().expect("we never format bad utf-8");
+        line_checksum(content)
+    }
+
+    /// Formats all fields contributing to the checksum,
+    /// into cleared [Utf8Buffer].
+    fn push_fields(&self, buffer: &mut Utf8Buffer) -> std::io::Result<()> {
         // start by clearing buffer from past residues
         buffer.clear();
 
-        buffer.push_str(&format!("{} {:X} ", self.sv, self.class));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.epoch.to_mjd_utc_days().floor(), 1.0, 99999, 4)
-        ));
-
-        let (_, _, _, h, m, s, _) = self.epoch.to_gregorian_utc();
-        buffer.push_str(&format!("{:02}{:02}{:02} ", h, m, s));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated(self.duration.to_seconds() as u64, 9999, 4)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.elevation_deg, 10.0, 999, 3)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.azimuth_deg, 10.0, 9999, 4)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.refsv, 1E10, 99_999_999_999, 11)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.srsv, 1E13, 999_999, 6)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.refsys, 1E10, 99_999_999_999, 11)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.srsys, 1E13, 999_999, 6)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.dsg, 1E10, 9_999, 4)
-        ));
-
-        buffer.push_str(&format!("{} ", fmt_saturated(self.data.ioe, 999, 3)));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.mdtr, 1E10, 9_999, 4)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.smdt, 1E13, 9_999, 4)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.mdio, 1E10, 9_999, 4)
-        ));
-
-        buffer.push_str(&format!(
-            "{} ",
-            fmt_saturated_f64(self.data.smdi, 1E13, 9_999, 4)
-        ));
+        write!(buffer, "{} {:X} ", self.sv, self.class)?;
 
-        if let Some(iono) = self.iono {
-            buffer.push_str(&format!(
-                "{} {} {} ",
-                fmt_saturated_f64(iono.msio, 1E10, 9_999, 4),
-                fmt_saturated_f64(iono.smsi, 1E13, 999_999, 4),
-                fmt_saturated_f64(iono.isg, 1E10, 9_999, 3),
-            ));
-        }
+        buffer.push_integer(cmp_min(self.mjd(), 99999), 4);
+        buffer.push_str(" ");
+
+        let (h, m, s) = self.sttime();
+        buffer.push_zero_padded(h as u64, 2);
+        buffer.push_zero_padded(m as u64, 2);
+        buffer.push_zero_padded(s as u64, 2);
+        buffer.push_str(" ");
 
-        if let Some(fdma) = &self.fdma_channel {
-            buffer.push_str(&format!("{:2} ", fdma));
-        } else {
-            buffer.push_str(" 0 ");
+        buffer.push_integer(cmp_min(self.duration.to_seconds() as u64, 9999), 4);
+        buffer.push_str(" ");
+
+        buffer.push_integer(elevation_column(self.elevation_deg), 3);
+        buffer.push_str(" ");
+
+        buffer.push_integer(azimuth_column(self.azimuth_deg), 4);
+        buffer.push_str(" ");
+
+        for (value, scaling, sat, padding) in [
+            (self.data.refsv, 1E10, 99_999_999_999, 11),
+            (self.data.srsv, 1E13, 999_999, 6),
+            (self.data.refsys, 1E10, 99_999_999_999, 11),
+            (self.data.srsys, 1E13, 999_999, 6),
+            (self.data.dsg, 1E10, 9_999, 4),
+        ] {
+            buffer.push_integer(saturated_f64(value, scaling, sat), padding);
+            buffer.push_str(" ");
         }
 
-        buffer.push_str(&format!(
-            "{:2} {:>frc_padding$} ",
-            self.hc,
-            self.frc,
-            frc_padding = 3
-        ));
+        buffer.push_integer(cmp_min(self.data.ioe, 999), 3);
+        buffer.push_str(" ");
+
+        for (value, scaling, sat, padding) in [
+            (self.data.mdtr, 1E10, 9_999, 4),
+            (self.data.smdt, 1E13, 9_999, 4),
+            (self.data.mdio, 1E10, 9_999, 4),
+            (self.data.smdi, 1E13, 9_999, 4),
+        ] {
+            buffer.push_integer(saturated_f64(value, scaling, sat), padding);
+            buffer.push_str(" ");
+        }
 
-        // ready to proceed to calculation
-        let crc = buffer.calculate_crc();
+        if let Some(iono) = self.iono {
+            for (value, scaling, sat, padding) in [
+                (iono.msio, 1E10, 9_999, 4),
+                (iono.smsi, 1E13, 999_999, 4),
+                (iono.isg, 1E10, 9_999, 3),
+            ] {
+                buffer.push_integer(saturated_f64(value, scaling, sat), padding);
+                buffer.push_str(" ");
+            }
+        }
 
-        // append CRC
-        buffer.push_str(&format!("{:02X}", crc));
+        buffer.push_integer(self.fdma_channel.unwrap_or(0), 2);
+        buffer.push_str(" ");
 
-        // interprate
-        let utf8 = buffer.to_utf8_ascii()?; // we will never format bad Utf8
+        buffer.push_integer(self.hc, 2);
+        buffer.push_str(" ");
 
-        // forward to user buffer
-        write!(writer, "{}", &utf8)?;
+        write!(buffer, "{:>3} ", self.frc)?;
 
         Ok(())
     }
@@ -145,6 +158,9 @@ impl Track {
 #[cfg(test)]
 mod test {
     use crate::buffer::Utf8Buffer;
+    use crate::prelude::{
+        CommonViewClass, Duration, EphemerisData, Epoch, IonosphericData, TrackData, SV,
+    };
     use crate::track::Track;
     use std::io::BufWriter;
     use std::str::FromStr;
@@ -205,4 +221,172 @@ mod test {
             "E03 FF 60258 001000  780 139  548      724092     28           2      1    2  76  325  -36   54   -6   34   35   5  0  0 E5b 77"
         );
     }
+
+    #[test]
+    fn ephemeris_data_formatting() {
+        let track = Track::from_str(
+            "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5"
+        )
+            .unwrap();
+
+        for (sv, data, expected) in [
+            ("E03", EphemerisData::Ioe(76), " 76"),
+            ("G08", EphemerisData::Ioe(999), "999"),
+            ("C05", EphemerisData::BeidouHour(23), " 23"),
+            ("R10", EphemerisData::GlonassQuarter(1), "  1"),
+            ("R10", EphemerisData::GlonassQuarter(96), " 96"),
+        ] {
+            let sv = SV::from_str(sv).unwrap();
+            let track = track.clone().with_sv(sv).with_ephemeris_data(data);
+
+            assert_eq!(track.ephemeris_data(), data);
+
+            let mut buf = Utf8Buffer::new(1024);
+            let mut user_buf = BufWriter::new(Utf8Buffer::new(1024));
+
+            track.format(&mut user_buf, &mut buf).unwrap();
+
+            let inner = user_buf.into_inner().unwrap_or_else(|_| panic!("oops"));
+            let ascii_utf8 = inner.to_utf8_ascii().expect("generated invalid utf-8!");
+
+            // IOE column
+            assert_eq!(&ascii_utf8[77..80], expected, "{}", ascii_utf8);
+        }
+    }
+
+    #[test]
+    fn saturated_formatting() {
+        let track = Track {
+            sv: SV::from_str("R24").unwrap(),
+            class: CommonViewClass::MultiChannel,
+            epoch: Epoch::from_str("2023-01-01T23:59:30 UTC").unwrap(),
+            duration: Duration::from_seconds(123_456.0),
+            elevation_deg: 123.45,
+            azimuth_deg: -12.34,
+            data: TrackData {
+                refsv: -1.0,
+                srsv: 1.0,
+                refsys: 1.0,
+                srsys: -1.0,
+                dsg: 1.0,
+                ioe: 1234,
+                mdtr: -1.23E-9,
+                smdt: 1.0,
+                mdio: 5.55E-9,
+                smdi: -1.0,
+            },
+            iono: Some(IonosphericData {
+                msio: 1.0,
+                smsi: -1.0,
+                isg: 0.05E-9,
+            }),
+            fdma_channel: Some(12),
+            hc: 123,
+            frc: "L1C".parse().unwrap(),
+            provenance: None,
+            ck: None,
+            computed_ck: None,
+        };
+
+        let mut buf = Utf8Buffer::new(1024);
+        let mut user_buf = BufWriter::new(Utf8Buffer::new(1024));
+
+        track.format(&mut user_buf, &mut buf).unwrap();
+
+        let inner = user_buf.into_inner().unwrap_or_else(|_| panic!("oops"));
+        let ascii_utf8 = inner.to_utf8_ascii().expect("generated invalid utf-8!");
+
+        assert_eq!(
+            ascii_utf8,
+            "R24 FF 59945 235930 9999 900 3477 -9999999999 999999 10000000000 -99999 9999 999  -12 9999   56 -999 9999 -99999   1 12 123 L1C D2"
+        );
+    }
+
+    #[test]
+    fn angle_boundaries() {
+        let mut buf = Utf8Buffer::new(1024);
+
+        for (elevation_deg, azimuth_deg, columns, parsed) in [
+            (0.0, 0.0, "  0    0", (0.0, 0.0)),
+            (89.95, 359.94, "900 3599", (90.0, 359.9)),
+            (90.0, 359.97, "900    0", (90.0, 0.0)),
+            (90.04, 360.0, "900    0", (90.0, 0.0)),
+            (-0.5, -0.04, "  0    0", (0.0, 0.0)),
+            (123.45, -12.34, "900 3477", (90.0, 347.7)),
+        ] {
+            let track = Track {
+                sv: SV::from_str("G01").unwrap(),
+                epoch: Epoch::from_str("2023-01-01T00:02:00 UTC").unwrap(),
+                duration: Duration::from_seconds(780.0),
+                elevation_deg,
+                azimuth_deg,
+                frc: "L1C".parse().unwrap(),
+                ..Default::default()
+            };
+
+            let mut user_buf = BufWriter::new(Utf8Buffer::new(1024));
+            track.format(&mut user_buf, &mut buf).unwrap();
+
+            let inner = user_buf.into_inner().unwrap_or_else(|_| panic!("oops"));
+            let ascii_utf8 = inner.to_utf8_ascii().unwrap();
+
+            // ELV and AZTH columns
+            assert_eq!(&ascii_utf8[25..33], columns, "{}", ascii_utf8);
+
+            let track = Track::from_str(ascii_utf8).unwrap();
+            assert!((track.elevation_deg - parsed.0).abs() < 1.0E-9);
+            assert!((track.azimuth_deg - parsed.1).abs() < 1.0E-9);
+        }
+    }
+
+    #[test]
+    fn track_crc() {
+        for (line, ck) in [
+            (
+                "E03 FF 60258 001000  780 139  548      723788     14        -302    -14    2  76  325  -36   32   -3   20   20   3  0  0  E1 74",
+                0x74,
+            ),
+            (
+                "E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 30",
+                0x30,
+            ),
+        ] {
+            let track = Track::from_str(line).unwrap();
+            assert_eq!(track.crc(), ck);
+
+            // identical to the formatted CK column
+            let mut buf = Utf8Buffer::new(1024);
+            let mut user_buf = BufWriter::new(Utf8Buffer::new(1024));
+            track.format(&mut user_buf, &mut buf).unwrap();
+
+            let inner = user_buf.into_inner().unwrap_or_else(|_| panic!("oops"));
+            let ascii_utf8 = inner.to_utf8_ascii().unwrap();
+            assert_eq!(ascii_utf8, line);
+            assert_eq!(
+                u8::from_str_radix(&ascii_utf8[ascii_utf8.len() - 2..], 16).unwrap(),
+                track.crc()
+            );
+        }
+    }
+
+    #[test]
+    fn line_checksums() {
+        for line in [
+            "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+            "E03 FF 60258 001000  780 139  548      723788     14        -302    -14    2  76  325  -36   32   -3   20   20   3  0  0  E1 74",
+            "E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 30",
+        ] {
+            let (content, ck) = line.split_at(line.len() - 2);
+            let ck = u8::from_str_radix(ck, 16).unwrap();
+
+            assert_eq!(super::line_checksum(content), ck);
+            assert_eq!(super::line_checksum(&format!("{}\r\n", content)), ck);
+
+            // the separating space is covered
+            assert_eq!(
+                super::line_checksum(content.trim_end()),
+                ck.wrapping_sub(b' ')
+            );
+        }
+    }
 }
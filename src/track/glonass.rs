@@ -30,7 +30,7 @@ impl std::str::FromStr for GlonassChannel {
         let ch = s
             .trim()
             .parse::<u8>()
-            .map_err(|_| Error::FieldParsing(String::from("FR")))?;
+            .map_err(|_| Error::InvalidField { name: "FR" })?;
         if ch == 0 {
             Ok(Self::Unknown)
         } else {
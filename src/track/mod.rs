@@ -1,9 +1,16 @@
 use thiserror::Error;
 
+mod builder;
+mod carrier;
 mod class;
+mod ephemeris;
 mod formatting;
 
+pub use builder::{TrackBuildError, TrackBuilder};
+pub use carrier::CarrierCode;
 pub use class::CommonViewClass;
+pub use ephemeris::{EphemerisData, IoeKind};
+pub use formatting::line_checksum;
 
 use gnss::prelude::{Constellation, SV};
 use hifitime::{Duration, Epoch, Unit};
@@ -14,20 +21,37 @@ use serde::{Deserialize, Serialize};
 #[cfg(docsrs)]
 use crate::prelude::TimeScale;
 
+#[cfg(feature = "scheduler")]
+use crate::prelude::CommonViewPeriod;
+
+#[cfg(feature = "log")]
+use log::warn;
+
+/// BIPM track (data collection) duration
+const BIPM_TRACKING_DURATION_SECONDS: f64 = 780.0;
+
+/// BIPM common view period (warmup and data collection) duration
+const BIPM_PERIOD_SECONDS: f64 = 960.0;
+
+/// Tolerance on the tracking duration, when verifying a schedule
+const TRACKING_TOLERANCE_SECONDS: f64 = 1.0;
+
 const TRACK_WITH_IONOSPHERIC: usize = 24;
 const TRACK_WITHOUT_IONOSPHERIC: usize = 21;
 
 /// A Track is a CGGTTS measurement
-#[derive(Debug, Default, PartialEq, Clone)]
+#[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Track {
     /// Common View Class
     pub class: CommonViewClass,
     /// [Epoch] of this track
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::epoch"))]
     pub epoch: Epoch,
     /// Tracking [Duration]
     pub duration: Duration,
     /// SV tracked during this realization
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::sv"))]
     pub sv: SV,
     /// [SV] elevation in degrees (at track midpoint, in case of complex
     /// track collection and fitting algorithm), in degrees.
@@ -46,28 +70,70 @@ pub struct Track {
     pub hc: u8,
     /// Carrier frequency standard 3 letter code,
     /// refer to RINEX specifications for meaning
-    pub frc: String,
+    pub frc: CarrierCode,
+    /// Possible origin of this [Track] (for example, its source file name),
+    /// when [Track]s of several sources are gathered. This is never formatted
+    /// in CGGTTS files, but is part of the serde representation.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub provenance: Option<String>,
+    /// Checksum (CK) declared in the file this [Track] was parsed from,
+    /// None for [Track]s that were not parsed. It is never formatted
+    /// (the checksum is always recomputed) and ignored by equality.
+    /// Refer to [crate::prelude::CGGTTS::verify_track_crcs].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ck: Option<u8>,
+    /// Checksum of the raw line this [Track] was parsed from, computed
+    /// over the exact file content that precedes the CK column (whatever
+    /// its column alignment). None for [Track]s that were not parsed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) computed_ck: Option<u8>,
+}
+
+/// Equality compares the complete content, except the parsed checksums
+/// ([Track::ck]), so a parsed [Track] equals the [Track] it was formatted from.
+impl PartialEq for Track {
+    fn eq(&self, rhs: &Self) -> bool {
+        self.class == rhs.class
+            && self.epoch == rhs.epoch
+            && self.duration == rhs.duration
+            && self.sv == rhs.sv
+            && self.elevation_deg == rhs.elevation_deg
+            && self.azimuth_deg == rhs.azimuth_deg
+            && self.data == rhs.data
+            && self.iono == rhs.iono
+            && self.fdma_channel == rhs.fdma_channel
+            && self.hc == rhs.hc
+            && self.frc == rhs.frc
+            && self.provenance == rhs.provenance
+    }
 }
 
+/// Errors that may occur when parsing a [Track], naming the offending field.
 #[derive(Error, Debug, PartialEq)]
 pub enum Error {
     #[error("invalid track format")]
     InvalidFormat,
-    #[error("invalid sttime field format")]
-    InvalidTrkTimeFormat,
-    #[error("unknown common view class")]
-    UnknownClass,
-    #[error("failed to parse sv")]
-    SVParsing(#[from] gnss::sv::ParsingError),
-    #[error("failed to parse \"{0}\" field")]
-    FieldParsing(String),
+    #[error("invalid SAT field \"{0}\"")]
+    InvalidSv(String),
+    #[error("invalid MJD field \"{0}\"")]
+    InvalidMjd(String),
+    #[error("invalid STTIME field \"{0}\", should be hhmmss")]
+    InvalidSttime(String),
+    #[error("unknown common view class \"{0}\"")]
+    UnknownClass(String),
+    #[error("failed to parse \"{name}\" field")]
+    InvalidField { name: &'static str },
     #[error("missing \"{0}\" field")]
-    MissingField(String),
-    #[error("checksum error")]
-    CrcError(#[from] crate::errors::CrcError),
+    MissingField(&'static str),
+    #[error("checksum mismatch: found {found:02X} but {computed:02X} locally computed")]
+    ChecksumMismatch { found: u8, computed: u8 },
+    #[error("carrier code should be 1 to 3 ASCII characters")]
+    InvalidCarrierCode,
 }
 
-/// Track data
+/// Track data. Values are stored in SI units (seconds, seconds per second),
+/// while the file uses scaled integers (0.1 ns, 0.1 ps/s): scaling is applied
+/// when parsing and formatting. Prefer the SI accessors when in doubt.
 #[derive(Debug, Default, Clone, Copy, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TrackData {
@@ -98,6 +164,85 @@ pub struct TrackData {
     pub smdi: f64,
 }
 
+impl TrackData {
+    /// Returns [TrackData] with desired [EphemerisData],
+    /// which is the typed way of defining the `IOE` field.
+    pub fn with_ephemeris_data(self, data: EphemerisData) -> Self {
+        let mut s = self;
+        s.ioe = data.ioe();
+        s
+    }
+
+    /// Returns REFSV: satellite clock offset to local clock, in seconds.
+    pub fn refsv_seconds(&self) -> f64 {
+        self.refsv
+    }
+
+    /// Returns SRSV: REFSV slope, in seconds per second.
+    pub fn srsv_s_per_s(&self) -> f64 {
+        self.srsv
+    }
+
+    /// Returns REFSYS: system time offset to local clock, in seconds.
+    pub fn refsys_seconds(&self) -> f64 {
+        self.refsys
+    }
+
+    /// Returns SRSYS: REFSYS slope, in seconds per second.
+    pub fn srsys_s_per_s(&self) -> f64 {
+        self.srsys
+    }
+
+    /// Returns DSG: RMS residuals to the REFSYS linear fit, in seconds.
+    pub fn dsg_seconds(&self) -> f64 {
+        self.dsg
+    }
+
+    /// Returns MDTR: modeled tropospheric delay, in seconds.
+    pub fn mdtr_seconds(&self) -> f64 {
+        self.mdtr
+    }
+
+    /// Returns SMDT: MDTR slope, in seconds per second.
+    pub fn smdt_s_per_s(&self) -> f64 {
+        self.smdt
+    }
+
+    /// Returns MDIO: modeled ionospheric delay, in seconds.
+    pub fn mdio_seconds(&self) -> f64 {
+        self.mdio
+    }
+
+    /// Returns SMDI: MDIO slope, in seconds per second.
+    pub fn smdi_s_per_s(&self) -> f64 {
+        self.smdi
+    }
+
+    /// Defines the `IOE` field as an Issue of Ephemeris,
+    /// for GPS, Galileo, QZSS.. (not BeiDou, nor Glonass).
+    pub fn set_ioe_gps(&mut self, iode: u16) {
+        self.ioe = EphemerisData::Ioe(iode).ioe();
+    }
+
+    /// Defines the `IOE` field as the BeiDou hour of clock (0-23),
+    /// from the ephemeris clock [Epoch] (toc).
+    pub fn set_ioe_beidou_from_epoch(&mut self, toc: Epoch) {
+        self.ioe = EphemerisData::from_beidou_epoch(toc).ioe();
+    }
+
+    /// Defines the `IOE` field as the Glonass daily quarter of hour (1-96),
+    /// from the ephemeris reference [Epoch] (tb).
+    pub fn set_ioe_glonass_from_epoch(&mut self, tb: Epoch) {
+        self.ioe = EphemerisData::from_glonass_epoch(tb).ioe();
+    }
+
+    /// Returns how the `IOE` field should be interpreted,
+    /// for this [Constellation].
+    pub fn ioe_interpretation(&self, constellation: Constellation) -> IoeKind {
+        IoeKind::from(constellation)
+    }
+}
+
 /// Ionospheric Data are attached to a CGGTTS track
 /// when generated in dual frequency contexts.
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -114,10 +259,28 @@ pub struct IonosphericData {
     pub isg: f64,
 }
 
+impl IonosphericData {
+    /// Returns MSIO: measured ionospheric delay, in seconds.
+    pub fn msio_seconds(&self) -> f64 {
+        self.msio
+    }
+
+    /// Returns SMSI: MSIO slope, in seconds per second.
+    pub fn smsi_s_per_s(&self) -> f64 {
+        self.smsi
+    }
+
+    /// Returns ISG: RMS residuals to the MSIO linear fit, in seconds.
+    pub fn isg_seconds(&self) -> f64 {
+        self.isg
+    }
+}
+
 impl Track {
     /// Builds a new CGGTTS [Track]. To follow CGGTTS guidelines,
     /// it is important to use an [Epoch] expressed in [Timescale::UTC].
     /// Prefer [Track::new_glonass] when working with [SV] from this constellation.
+    /// Prefer the [TrackBuilder], which verifies the [Track] content.
     ///
     /// ## Inputs
     /// - sv: [SV] that was tracked
@@ -136,6 +299,12 @@ impl Track {
     /// - frc: (ideally) RINEx like carrier/modulation frequency
     /// code. For example "C1" would be (old) pseudo range on L1 frequency.
     /// And "C1C" is the modern equivalent, that fully describe the modulation.
+    ///
+    /// This constructor never fails, so invalid values are not rejected:
+    /// like [Track::from_str], elevation is clamped to [-90, 90]° and azimuth
+    /// normalized to [0, 360[°, while only the first (up to 3) ASCII non whitespace
+    /// characters of `frc` are preserved. Prefer [TrackBuilder], which rejects
+    /// out of range angles and invalid carrier codes.
     pub fn new(
         sv: SV,
         utc_epoch: Epoch,
@@ -153,13 +322,16 @@ impl Track {
             epoch: utc_epoch,
             class,
             duration,
-            elevation_deg,
-            azimuth_deg,
+            elevation_deg: clamped_elevation(elevation_deg),
+            azimuth_deg: normalized_azimuth(azimuth_deg),
             data,
             iono,
             fdma_channel: None,
             hc: rcvr_channel,
-            frc: frc.to_string(),
+            frc: CarrierCode::truncated(frc),
+            provenance: None,
+            ck: None,
+            computed_ck: None,
         }
     }
 
@@ -182,6 +354,12 @@ impl Track {
     /// - frc: (ideally) RINEx like carrier/modulation frequency
     /// code. For example "C1" would be (old) pseudo range on L1 frequency.
     /// And "C1C" is the modern equivalent, that fully describe the modulation.
+    ///
+    /// This constructor never fails, so invalid values are not rejected:
+    /// like [Track::from_str], elevation is clamped to [-90, 90]° and azimuth
+    /// normalized to [0, 360[°, while only the first (up to 3) ASCII non whitespace
+    /// characters of `frc` are preserved. Prefer [TrackBuilder], which rejects
+    /// out of range angles and invalid carrier codes.
     pub fn new_glonass(
         sv: SV,
         utc_epoch: Epoch,
@@ -200,133 +378,320 @@ impl Track {
             epoch: utc_epoch,
             duration,
             class,
-            elevation_deg,
-            azimuth_deg,
+            elevation_deg: clamped_elevation(elevation_deg),
+            azimuth_deg: normalized_azimuth(azimuth_deg),
             data,
             iono,
             fdma_channel: Some(fdma_channel),
             hc: rcvr_channel,
-            frc: frc.to_string(),
+            frc: CarrierCode::truncated(frc),
+            provenance: None,
+            ck: None,
+            computed_ck: None,
+        }
+    }
+
+    /// Verifies the parsed checksum ([Track::ck]) against the checksum
+    /// of the raw line it was parsed from. [Track]s that were not parsed
+    /// have nothing to verify.
+    pub(crate) fn verify_ck(&self) -> Result<(), Error> {
+        match (self.ck, self.computed_ck) {
+            (Some(found), Some(computed)) if found != computed => {
+                Err(Error::ChecksumMismatch { found, computed })
+            },
+            _ => Ok(()),
         }
     }
 
+    /// Parses a [Track] like [Track::from_str], then verifies the
+    /// checksum (CK) declared in the line against the checksum of the raw line.
+    /// ```
+    /// use cggtts::prelude::Track;
+    /// use cggtts::track::Error;
+    ///
+    /// let line = "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5";
+    /// assert!(Track::from_str_checked(line).is_ok());
+    ///
+    /// let corrupted = line.replace(" E1 A5", " E1 A6");
+    /// assert_eq!(
+    ///     Track::from_str_checked(&corrupted),
+    ///     Err(Error::ChecksumMismatch { found: 0xA6, computed: 0xA5 }),
+    /// );
+    /// ```
+    pub fn from_str_checked(line: &str) -> Result<Self, Error> {
+        let track = line.parse::<Self>()?;
+        track.verify_ck()?;
+        Ok(track)
+    }
+
     /// Returns true if this [Track]ed  the following [Constellation].
     pub fn uses_constellation(&self, c: Constellation) -> bool {
         self.sv.constellation == c
     }
 
+    /// Returns true if the carrier frequency code (FRC) is a legal
+    /// carrier for the [Constellation] that was tracked. For example,
+    /// "E1" is not legal for a [Constellation::GPS] [SV].
+    /// Common view requires matching carriers, so this helps
+    /// detecting receivers that mislabel them.
+    pub fn is_frc_valid(&self) -> bool {
+        let frc = self.frc.as_str();
+        match self.sv.constellation {
+            Constellation::GPS | Constellation::QZSS => matches!(
+                frc,
+                "L1C" | "L1P" | "L2C" | "L2P" | "L5" | "L5I" | "L5Q" | "L3P"
+            ),
+            Constellation::Glonass => matches!(frc, "L1C" | "L1P" | "L2C" | "L2P" | "L3P"),
+            Constellation::Galileo => {
+                matches!(frc, "E1" | "E5" | "E5a" | "E5b" | "E6" | "L3E")
+            },
+            Constellation::BeiDou => matches!(
+                frc,
+                "B1" | "B1i"
+                    | "B1I"
+                    | "B1C"
+                    | "B2"
+                    | "B2i"
+                    | "B2I"
+                    | "B2a"
+                    | "B2b"
+                    | "B3"
+                    | "B3i"
+                    | "B3I"
+                    | "L3B"
+            ),
+            Constellation::IRNSS => matches!(frc, "L5" | "S"),
+            c if c.is_sbas() => matches!(frc, "L1C" | "L5"),
+            _ => false,
+        }
+    }
+
     /// Returns True if this [Track] seems compatible with the [CommonViewPeriod]
     /// recommended by BIPM. This cannot be a complete confirmation,
     /// because only the receiver that generated this data knows
     /// if the [Track] collection and fitting was implemented correctly.
+    ///
+    /// Geostationary (SBAS) satellites are continuously tracked: a complete
+    /// period (without warmup phase) is accepted as well, in that case.
     pub fn follows_bipm_tracking(&self) -> bool {
-        self.duration == Duration::from_seconds(780.0)
+        self.follows_tracking_durations(
+            Duration::from_seconds(BIPM_TRACKING_DURATION_SECONDS),
+            Duration::from_seconds(BIPM_PERIOD_SECONDS),
+        )
+    }
+
+    /// Returns True if this [Track] seems compatible with the custom
+    /// [CommonViewPeriod]: the tracking duration should match
+    /// (within 1 second). Like [Self::follows_bipm_tracking],
+    /// a complete period is accepted for geostationary (SBAS) satellites.
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// // experimental 5' tracking
+    /// let period = CommonViewPeriod::default()
+    ///     .with_tracking_duration(Duration::from_seconds(300.0));
+    ///
+    /// let mut track = Track::default();
+    /// track.duration = Duration::from_seconds(300.0);
+    ///
+    /// assert!(track.follows_tracking(&period));
+    /// assert!(!track.follows_bipm_tracking());
+    /// ```
+    #[cfg(feature = "scheduler")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "scheduler")))]
+    pub fn follows_tracking(&self, period: &CommonViewPeriod) -> bool {
+        self.follows_tracking_durations(period.tracking_duration, period.total_duration())
+    }
+
+    /// Verifies the tracking duration, with 1 second tolerance.
+    fn follows_tracking_durations(&self, tracking: Duration, total: Duration) -> bool {
+        let matches = |expected: Duration| {
+            (self.duration - expected).abs() <= Duration::from_seconds(TRACKING_TOLERANCE_SECONDS)
+        };
+
+        matches(tracking) || (self.sv.constellation.is_sbas() && matches(total))
+    }
+
+    /// Returns the [Epoch] at which this [Track] ended: `epoch + duration`.
+    pub fn end_epoch(&self) -> Epoch {
+        self.epoch + self.duration
+    }
+
+    /// Returns the MJD (UTC) of this [Track], as written in the `MJD` column.
+    pub fn mjd(&self) -> u32 {
+        self.epoch.to_mjd_utc_days().floor() as u32
+    }
+
+    /// Returns the (hours, minutes, seconds) UTC time of day of this [Track],
+    /// as written in the `STTIME` column.
+    pub fn sttime(&self) -> (u8, u8, u8) {
+        let (_, _, _, h, m, s, _) = self.epoch.to_gregorian_utc();
+        (h, m, s)
+    }
+
+    /// Returns the `STTIME` column of this [Track], formatted as `hhmmss`.
+    /// ```
+    /// use cggtts::prelude::Track;
+    /// use std::str::FromStr;
+    ///
+    /// let track = Track::from_str(
+    ///     "G01 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3 00 00 L1C 00",
+    /// ).unwrap();
+    ///
+    /// assert_eq!(track.mjd(), 60258);
+    /// assert_eq!(track.sttime(), (0, 10, 0));
+    /// assert_eq!(track.sttime_string(), "001000");
+    /// ```
+    pub fn sttime_string(&self) -> String {
+        let (h, m, s) = self.sttime();
+        format!("{:02}{:02}{:02}", h, m, s)
+    }
+
+    /// Returns the UTC [Epoch] described by the `MJD` and `STTIME` columns.
+    /// This is the inverse of [Self::mjd] and [Self::sttime].
+    pub fn epoch_from_mjd_sttime(mjd: u32, h: u8, m: u8, s: u8) -> Epoch {
+        let mut epoch = Epoch::from_mjd_utc(mjd as f64);
+        epoch += (h as f64) * Unit::Hour;
+        epoch += (m as f64) * Unit::Minute;
+        epoch += (s as f64) * Unit::Second;
+        epoch
     }
 
     /// Returns a [Track] with desired [SV].
-    pub fn with_sv(&self, sv: SV) -> Self {
-        let mut t = self.clone();
+    pub fn with_sv(self, sv: SV) -> Self {
+        let mut t = self;
         t.sv = sv;
         t
     }
 
     /// Returns a [Track] with desired elevation (at mid point in the fitting collection
-    /// algorithm), in degrees.
-    pub fn with_elevation_deg(&self, elevation_deg: f64) -> Self {
-        let mut t = self.clone();
-        t.elevation_deg = elevation_deg;
+    /// algorithm), in degrees. Elevation is clamped to [-90, 90]°.
+    pub fn with_elevation_deg(self, elevation_deg: f64) -> Self {
+        let mut t = self;
+        t.elevation_deg = clamped_elevation(elevation_deg);
         t
     }
 
     /// Returns a [Track] with desired azimuth (at mid point in the fitting collection
-    /// algorithm), in degrees.
-    pub fn with_azimuth_deg(&self, azimuth_deg: f64) -> Self {
-        let mut t = self.clone();
-        t.azimuth_deg = azimuth_deg;
+    /// algorithm), in degrees. Azimuth is normalized to [0, 360[°.
+    pub fn with_azimuth_deg(self, azimuth_deg: f64) -> Self {
+        let mut t = self;
+        t.azimuth_deg = normalized_azimuth(azimuth_deg);
+        t
+    }
+
+    /// Returns a [Track] with desired [EphemerisData], rendered in the `IOE` column.
+    pub fn with_ephemeris_data(self, data: EphemerisData) -> Self {
+        let mut t = self;
+        t.data = t.data.with_ephemeris_data(data);
         t
     }
 
-    /// Returns a `Track` with desired Frequency carrier code
-    pub fn with_carrier_code(&self, code: &str) -> Self {
-        let mut t = self.clone();
-        t.frc = code.to_string();
+    /// Returns the [EphemerisData] used by this [Track],
+    /// interpreted from the `IOE` field and the [Constellation].
+    pub fn ephemeris_data(&self) -> EphemerisData {
+        EphemerisData::from_ioe(self.sv.constellation, self.data.ioe)
+    }
+
+    /// Returns a `Track` with desired Frequency carrier code.
+    /// Like [Self::new], an invalid code is not rejected: only its first
+    /// (up to 3) ASCII non whitespace characters are preserved.
+    /// Prefer [TrackBuilder], which verifies the carrier code.
+    pub fn with_carrier_code(self, code: &str) -> Self {
+        let mut t = self;
+        t.frc = CarrierCode::truncated(code);
         t
     }
+
     /// Returns true if Self comes with Ionospheric parameter estimates
     pub fn has_ionospheric_data(&self) -> bool {
         self.iono.is_some()
     }
 }
 
+/// Normalizes azimuth angle to [0, 360[ degrees
+fn normalized_azimuth(azimuth_deg: f64) -> f64 {
+    let azimuth_deg = azimuth_deg.rem_euclid(360.0);
+    // rem_euclid may round tiny negative angles up to 360
+    if azimuth_deg < 360.0 {
+        azimuth_deg
+    } else {
+        0.0
+    }
+}
+
+/// Clamps elevation angle to [-90, 90] degrees
+fn clamped_elevation(elevation_deg: f64) -> f64 {
+    if elevation_deg.abs() > 90.0 {
+        #[cfg(feature = "log")]
+        warn!("elevation {}° out of range, clamped", elevation_deg);
+        elevation_deg.clamp(-90.0, 90.0)
+    } else {
+        elevation_deg
+    }
+}
+
+/// Returns next field, borrowed from the line
+fn next_field<'a>(
+    items: &mut std::str::SplitAsciiWhitespace<'a>,
+    name: &'static str,
+) -> Result<&'a str, Error> {
+    items.next().ok_or(Error::MissingField(name))
+}
+
+/// Parses [SV] without allocation, for the most common [Constellation]s.
+/// Other [Constellation]s (like SBAS, which depends on the PRN) are
+/// deferred to the [SV] parser.
+fn parse_sv(s: &str) -> Result<SV, Error> {
+    let constellation = match s.as_bytes().first() {
+        Some(b'G') => Some(Constellation::GPS),
+        Some(b'E') => Some(Constellation::Galileo),
+        Some(b'R') => Some(Constellation::Glonass),
+        Some(b'C') => Some(Constellation::BeiDou),
+        Some(b'J') => Some(Constellation::QZSS),
+        Some(b'I') => Some(Constellation::IRNSS),
+        _ => None,
+    };
+
+    match (
+        constellation,
+        s.get(1..).map(|prn| prn.trim().parse::<u8>()),
+    ) {
+        (Some(constellation), Some(Ok(prn))) => Ok(SV::new(constellation, prn)),
+        _ => s.parse::<SV>().map_err(|_| Error::InvalidSv(s.to_string())),
+    }
+}
+
+/// Parses next field
+fn parse_field<T: std::str::FromStr>(
+    items: &mut std::str::SplitAsciiWhitespace<'_>,
+    name: &'static str,
+) -> Result<T, Error> {
+    next_field(items, name)?
+        .parse::<T>()
+        .map_err(|_| Error::InvalidField { name })
+}
+
 fn parse_data(items: &mut std::str::SplitAsciiWhitespace<'_>) -> Result<TrackData, Error> {
-    let refsv = items
-        .next()
-        .ok_or(Error::MissingField(String::from("REFSV")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("REFSV")))?
-        * 1E-10;
-
-    let srsv = items
-        .next()
-        .ok_or(Error::MissingField(String::from("SRSV")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("SRSV")))?
-        * 1E-13;
-
-    let refsys = items
-        .next()
-        .ok_or(Error::MissingField(String::from("REFSYS")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("REFSYS")))?
-        * 1E-10;
-
-    let srsys = items
-        .next()
-        .ok_or(Error::MissingField(String::from("SRSYS")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("SRSYS")))?
-        * 1E-13;
-
-    let dsg = items
-        .next()
-        .ok_or(Error::MissingField(String::from("DSG")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("DSG")))?
-        * 1E-10;
-
-    let ioe = items
-        .next()
-        .ok_or(Error::MissingField(String::from("IOE")))?
-        .parse::<u16>()
-        .map_err(|_| Error::FieldParsing(String::from("IOE")))?;
-
-    let mdtr = items
-        .next()
-        .ok_or(Error::MissingField(String::from("MDTR")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("MDTR")))?
-        * 1E-10;
-
-    let smdt = items
-        .next()
-        .ok_or(Error::MissingField(String::from("SMDT")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("SMDT")))?
-        * 1E-13;
-
-    let mdio = items
-        .next()
-        .ok_or(Error::MissingField(String::from("MDIO")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("MDIO")))?
-        * 1E-10;
-
-    let smdi = items
-        .next()
-        .ok_or(Error::MissingField(String::from("SMDI")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("SMDI")))?
-        * 1E-13;
+    let refsv = parse_field::<f64>(items, "REFSV")? * 1E-10;
+
+    let srsv = parse_field::<f64>(items, "SRSV")? * 1E-13;
+
+    let refsys = parse_field::<f64>(items, "REFSYS")? * 1E-10;
+
+    let srsys = parse_field::<f64>(items, "SRSYS")? * 1E-13;
+
+    let dsg = parse_field::<f64>(items, "DSG")? * 1E-10;
+
+    let ioe = parse_field::<u16>(items, "IOE")?;
+
+    let mdtr = parse_field::<f64>(items, "MDTR")? * 1E-10;
+
+    let smdt = parse_field::<f64>(items, "SMDT")? * 1E-13;
+
+    let mdio = parse_field::<f64>(items, "MDIO")? * 1E-10;
+
+    let smdi = parse_field::<f64>(items, "SMDI")? * 1E-13;
 
     Ok(TrackData {
         refsv,
@@ -354,26 +719,11 @@ fn parse_with_iono(
 ) -> Result<(TrackData, Option<IonosphericData>), Error> {
     let data = parse_data(items)?;
 
-    let msio = items
-        .next()
-        .ok_or(Error::MissingField(String::from("MSIO")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("MSIO")))?
-        * 0.1E-9;
-
-    let smsi = items
-        .next()
-        .ok_or(Error::MissingField(String::from("SMSI")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("SMSI")))?
-        * 0.1E-12;
-
-    let isg = items
-        .next()
-        .ok_or(Error::MissingField(String::from("ISG")))?
-        .parse::<f64>()
-        .map_err(|_| Error::FieldParsing(String::from("ISG")))?
-        * 0.1E-9;
+    let msio = parse_field::<f64>(items, "MSIO")? * 0.1E-9;
+
+    let smsi = parse_field::<f64>(items, "SMSI")? * 0.1E-12;
+
+    let isg = parse_field::<f64>(items, "ISG")? * 0.1E-9;
 
     Ok((data, Some(IonosphericData { msio, smsi, isg })))
 }
@@ -384,77 +734,49 @@ impl std::str::FromStr for Track {
      * Builds a Track from given str description
      */
     fn from_str(line: &str) -> Result<Self, Self::Err> {
-        let cleanedup = String::from(line.trim());
-        let _epoch = Epoch::default();
-        let mut items = cleanedup.split_ascii_whitespace();
+        // CGGTTS is ASCII only
+        if !line.is_ascii() {
+            return Err(Error::InvalidFormat);
+        }
+
+        // fields are borrowed from the line: no allocation
+        let mut items = line.split_ascii_whitespace();
 
         let nb_items = items.clone().count();
 
-        let sv = SV::from_str(
-            items
-                .next()
-                .ok_or(Error::MissingField(String::from("SV")))?,
-        )?;
-
-        let class = CommonViewClass::from_str(
-            items
-                .next()
-                .ok_or(Error::MissingField(String::from("CL")))?
-                .trim(),
-        )?;
-
-        let mjd = items
-            .next()
-            .ok_or(Error::MissingField(String::from("MJD")))?
-            .parse::<i32>()
-            .map_err(|_| Error::FieldParsing(String::from("MJD")))?;
-
-        let trk_sttime = items
-            .next()
-            .ok_or(Error::MissingField(String::from("STTIME")))?;
-
-        if trk_sttime.len() < 6 {
-            return Err(Error::InvalidTrkTimeFormat);
-        }
+        let sv = parse_sv(next_field(&mut items, "SV")?)?;
 
-        let h = trk_sttime[0..2]
-            .parse::<u8>()
-            .map_err(|_| Error::FieldParsing(String::from("STTIME:%H")))?;
+        let class = CommonViewClass::from_str(next_field(&mut items, "CL")?)?;
 
-        let m = trk_sttime[2..4]
-            .parse::<u8>()
-            .map_err(|_| Error::FieldParsing(String::from("STTIME:%M")))?;
+        let mjd = next_field(&mut items, "MJD")?;
+        let mjd = mjd
+            .parse::<u32>()
+            .map_err(|_| Error::InvalidMjd(mjd.to_string()))?;
 
-        let s = trk_sttime[4..6]
-            .parse::<u8>()
-            .map_err(|_| Error::FieldParsing(String::from("STTIME:%S")))?;
+        let trk_sttime = next_field(&mut items, "STTIME")?;
 
-        let mut epoch = Epoch::from_mjd_utc(mjd as f64);
-        epoch += (h as f64) * Unit::Hour;
-        epoch += (m as f64) * Unit::Minute;
-        epoch += (s as f64) * Unit::Second;
+        let invalid_sttime = || Error::InvalidSttime(trk_sttime.to_string());
 
-        let duration = Duration::from_seconds(
-            items
-                .next()
-                .ok_or(Error::MissingField(String::from("STTIME")))?
-                .parse::<f64>()
-                .map_err(|_| Error::FieldParsing(String::from("STTIME")))?,
-        );
+        if trk_sttime.len() != 6 {
+            return Err(invalid_sttime());
+        }
 
-        let elevation_deg = items
-            .next()
-            .ok_or(Error::MissingField(String::from("ELV")))?
-            .parse::<f64>()
-            .map_err(|_| Error::FieldParsing(String::from("ELV")))?
-            * 0.1;
+        let (h, m, s) = match (
+            trk_sttime[0..2].parse::<u8>(),
+            trk_sttime[2..4].parse::<u8>(),
+            trk_sttime[4..6].parse::<u8>(),
+        ) {
+            (Ok(h), Ok(m), Ok(s)) => (h, m, s),
+            _ => return Err(invalid_sttime()),
+        };
+
+        let epoch = Self::epoch_from_mjd_sttime(mjd, h, m, s);
+
+        let duration = Duration::from_seconds(parse_field::<f64>(&mut items, "TRKL")?);
+
+        let elevation_deg = clamped_elevation(parse_field::<f64>(&mut items, "ELV")? * 0.1);
 
-        let azimuth_deg = items
-            .next()
-            .ok_or(Error::MissingField(String::from("AZTH")))?
-            .parse::<f64>()
-            .map_err(|_| Error::FieldParsing(String::from("AZTH")))?
-            * 0.1;
+        let azimuth_deg = normalized_azimuth(parse_field::<f64>(&mut items, "AZTH")? * 0.1);
 
         let (data, iono) = match nb_items {
             TRACK_WITH_IONOSPHERIC => parse_with_iono(&mut items)?,
@@ -464,39 +786,22 @@ impl std::str::FromStr for Track {
             },
         };
 
-        let fr = items
-            .next()
-            .ok_or(Error::MissingField(String::from("fr")))?
-            .parse::<u8>()
-            .map_err(|_| Error::FieldParsing(String::from("fr")))?;
+        let fr = parse_field::<u8>(&mut items, "fr")?;
 
-        let hc = items
-            .next()
-            .ok_or(Error::MissingField(String::from("hc")))?
-            .parse::<u8>()
-            .map_err(|_| Error::FieldParsing(String::from("hc")))?;
+        let hc = parse_field::<u8>(&mut items, "hc")?;
 
-        let frc: String = items
-            .next()
-            .ok_or(Error::MissingField(String::from("frc")))?
-            .parse()
-            .map_err(|_| Error::FieldParsing(String::from("frc")))?;
+        let frc = next_field(&mut items, "frc")?
+            .parse::<CarrierCode>()
+            .map_err(|_| Error::InvalidField { name: "frc" })?;
 
-        // checksum
-        let ck = items
-            .next()
-            .ok_or(Error::MissingField(String::from("ck")))?;
+        // checksum: CK is the last field, it covers the raw line content that precedes it
+        let ck_field = next_field(&mut items, "ck")?;
 
-        let _ck =
-            u8::from_str_radix(ck, 16).map_err(|_| Error::FieldParsing(String::from("ck")))?;
+        let ck =
+            u8::from_str_radix(ck_field, 16).map_err(|_| Error::InvalidField { name: "ck" })?;
 
-        // let cksum = calc_crc(&line.split_at(end_pos - 1).0)?;
-
-        // verification
-        /*if cksum != ck {
-            println!("GOT {} EXPECT {}", ck, cksum);
-            return Err(Error::ChecksumError(cksum, ck))
-        }*/
+        let line = line.trim_end();
+        let computed_ck = line_checksum(&line[..line.len() - ck_field.len()]);
 
         Ok(Track {
             sv,
@@ -510,16 +815,276 @@ impl std::str::FromStr for Track {
             hc,
             frc,
             fdma_channel: if fr == 0 { None } else { Some(fr) },
+            provenance: None,
+            ck: Some(ck),
+            computed_ck: Some(computed_ck),
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::buffer::Utf8Buffer;
     use crate::prelude::*;
     use gnss::prelude::{Constellation, SV};
     use hifitime::Duration;
+    use std::io::BufWriter;
     use std::str::FromStr;
+
+    #[test]
+    fn sv_parsing() {
+        for sv in [
+            "G08", "E03", "R24", "C01", "J02", "I05", "S23", "S36", "G 8",
+        ] {
+            assert_eq!(super::parse_sv(sv).ok(), SV::from_str(sv).ok(), "{}", sv);
+        }
+
+        assert!(super::parse_sv("X01").is_err());
+        assert!(super::parse_sv("G").is_err());
+    }
+
+    #[test]
+    fn mjd_sttime() {
+        for line in [
+            "G99 99 59568 001000 0780 099 0099 +9999999999 +99999       +1536   +181   26 999 9999 +999 9999 +999 00 00 L1C D3",
+            "R24 FF 57000 000600  780 347 394 +1186342 +0 163 +0 40 2 141 +22 23 -1 23 -1 29 +2 0 L3P 0F",
+            "E03 FF 60258 235945  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+        ] {
+            let columns = line.split_ascii_whitespace().collect::<Vec<_>>();
+            let track = Track::from_str(line).unwrap();
+
+            assert_eq!(track.mjd().to_string(), columns[2]);
+            assert_eq!(track.sttime_string(), columns[3]);
+
+            let (h, m, s) = track.sttime();
+            assert_eq!(format!("{:02}{:02}{:02}", h, m, s), columns[3]);
+
+            assert_eq!(
+                Track::epoch_from_mjd_sttime(track.mjd(), h, m, s),
+                track.epoch
+            );
+
+            // matches the formatted columns
+            let mut content = Vec::new();
+            {
+                let mut writer = BufWriter::new(&mut content);
+                track
+                    .format(&mut writer, &mut Utf8Buffer::new(256))
+                    .unwrap();
+            }
+
+            let formatted = String::from_utf8(content).unwrap();
+            let formatted = formatted.split_ascii_whitespace().collect::<Vec<_>>();
+            assert_eq!(formatted[2], columns[2]);
+            assert_eq!(formatted[3], columns[3]);
+        }
+    }
+
+    #[test]
+    fn si_accessors() {
+        // EZGTR60.258 first track
+        let track = Track::from_str(
+"E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5").unwrap();
+
+        let data = track.data;
+
+        for (value, expected) in [
+            (data.refsv_seconds(), 72_378.8E-9),
+            (data.srsv_s_per_s(), 1.4E-12),
+            (data.refsys_seconds(), -30.2E-9),
+            (data.srsys_s_per_s(), -1.4E-12),
+            (data.dsg_seconds(), 0.2E-9),
+            (data.mdtr_seconds(), 32.5E-9),
+            (data.smdt_s_per_s(), -3.6E-12),
+            (data.mdio_seconds(), 3.2E-9),
+            (data.smdi_s_per_s(), -0.3E-12),
+        ] {
+            assert!(
+                (value - expected).abs() < expected.abs() * 1E-9,
+                "{} != {}",
+                value,
+                expected
+            );
+        }
+
+        let iono = track.iono.unwrap();
+
+        for (value, expected) in [
+            (iono.msio_seconds(), 2.0E-9),
+            (iono.smsi_s_per_s(), 2.0E-12),
+            (iono.isg_seconds(), 0.3E-9),
+        ] {
+            assert!(
+                (value - expected).abs() < expected.abs() * 1E-9,
+                "{} != {}",
+                value,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn ioe_setters() {
+        let mut data = TrackData::default();
+
+        data.set_ioe_gps(135);
+        assert_eq!(data.ioe, 135);
+        assert_eq!(
+            data.ioe_interpretation(Constellation::GPS),
+            IoeKind::IssueOfEphemeris
+        );
+
+        data.set_ioe_beidou_from_epoch(Epoch::from_str("2023-01-01T07:00:00 BDT").unwrap());
+        assert_eq!(data.ioe, 7);
+        assert_eq!(
+            data.ioe_interpretation(Constellation::BeiDou),
+            IoeKind::BeidouHour
+        );
+
+        data.set_ioe_glonass_from_epoch(Epoch::from_str("2023-01-01T07:00:00 UTC").unwrap());
+        assert_eq!(data.ioe, 41);
+        assert_eq!(
+            data.ioe_interpretation(Constellation::Glonass),
+            IoeKind::GlonassQuarter
+        );
+    }
+    #[test]
+    fn frc_validity() {
+        for (sv, frc, valid) in [
+            ("G01", "L1C", true),
+            ("G01", "L3P", true),
+            ("G01", "E1", false),
+            ("G01", "B1i", false),
+            ("R24", "L3P", true),
+            ("R24", "E5a", false),
+            ("E03", "E1", true),
+            ("E08", "E5a", true),
+            ("E03", "L1C", false),
+            ("C01", "B1i", true),
+            ("C01", "L1C", false),
+            ("J01", "L1C", true),
+            ("G01", "", false),
+        ] {
+            let track = Track::default()
+                .with_sv(SV::from_str(sv).unwrap())
+                .with_carrier_code(frc);
+
+            assert_eq!(track.is_frc_valid(), valid, "failed for {}/{}", sv, frc);
+        }
+    }
+
+    #[test]
+    fn sbas_bipm_tracking() {
+        for (sv, duration, expected) in [
+            ("G01", 780.0, true),
+            ("G01", 960.0, false),
+            ("G01", 600.0, false),
+            ("S36", 780.0, true),
+            ("S36", 960.0, true),
+            ("S36", 600.0, false),
+            ("S23", 960.0, true),
+        ] {
+            let sv = SV::from_str(sv).unwrap();
+
+            let mut track = Track::default().with_sv(sv);
+            track.duration = Duration::from_seconds(duration);
+
+            assert_eq!(
+                track.follows_bipm_tracking(),
+                expected,
+                "failed for {} ({}s)",
+                sv,
+                duration
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "scheduler")]
+    fn custom_tracking() {
+        let period = CommonViewPeriod::default()
+            .with_setup_duration(Duration::from_seconds(60.0))
+            .with_tracking_duration(Duration::from_seconds(300.0));
+
+        let bipm = CommonViewPeriod::bipm_common_view_period();
+
+        for (sv, duration, expected) in [
+            ("G01", 300.0, true),
+            ("G01", 299.0, true),
+            ("G01", 301.0, true),
+            ("G01", 302.0, false),
+            ("G01", 360.0, false),
+            ("G01", 780.0, false),
+            ("S36", 360.0, true),
+            ("S36", 600.0, false),
+        ] {
+            let mut track = Track::default().with_sv(SV::from_str(sv).unwrap());
+            track.duration = Duration::from_seconds(duration);
+
+            assert_eq!(
+                track.follows_tracking(&period),
+                expected,
+                "failed for {} ({}s)",
+                sv,
+                duration
+            );
+
+            assert_eq!(
+                track.follows_tracking(&bipm),
+                track.follows_bipm_tracking(),
+                "failed for {} ({}s)",
+                sv,
+                duration
+            );
+        }
+    }
+
+    #[test]
+    fn angle_normalization() {
+        let track = Track::from_str(
+"G99 99 59568 001000 0780 910 -050 +9999999999 +99999       +1536   +181   26 999 9999 +999 9999 +999 00 00 L1C D3").unwrap();
+
+        assert!((track.azimuth_deg - 355.0).abs() < 1E-9);
+        assert_eq!(track.elevation_deg, 90.0);
+
+        for (azimuth, expected) in [
+            (-5.0, 355.0),
+            (0.0, 0.0),
+            (359.9, 359.9),
+            (360.0, 0.0),
+            (725.0, 5.0),
+            (-1.0E-20, 0.0),
+        ] {
+            let track = Track::default().with_azimuth_deg(azimuth);
+            assert!(
+                (track.azimuth_deg - expected).abs() < 1E-9,
+                "failed for {}",
+                azimuth
+            );
+            assert!(track.azimuth_deg < 360.0);
+        }
+
+        for (elevation, expected) in [(91.0, 90.0), (-91.0, -90.0), (45.0, 45.0), (-5.0, -5.0)] {
+            let track = Track::default().with_elevation_deg(elevation);
+            assert_eq!(track.elevation_deg, expected);
+        }
+
+        let track = Track::new(
+            SV::from_str("G01").unwrap(),
+            Epoch::default(),
+            Duration::from_seconds(780.0),
+            CommonViewClass::SingleChannel,
+            91.0,
+            -5.0,
+            TrackData::default(),
+            None,
+            0,
+            "L1C",
+        );
+        assert_eq!(track.elevation_deg, 90.0);
+        assert_eq!(track.azimuth_deg, 355.0);
+    }
+
     #[test]
     fn track_parsing() {
         let content =
@@ -628,4 +1193,176 @@ mod tests {
         assert_eq!(track.hc, 0);
         assert_eq!(track.frc, "L3P");
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_representation() {
+        let track = Track::from_str(
+            "G08 FF 60258 144600  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0 L1C 00",
+        )
+        .unwrap();
+
+        let json = serde_json::to_value(&track).unwrap();
+        assert_eq!(json["sv"], "G08");
+        assert_eq!(json["epoch"], "2023-11-10T14:46:00 UTC");
+
+        let content = serde_json::to_string(&track).unwrap();
+        assert_eq!(serde_json::from_str::<Track>(&content).unwrap(), track);
+    }
+
+    #[test]
+    fn shifted_columns() {
+        for (content, dsg, mdio, iono) in [
+            // 3 digit DSG, right shifted
+            (
+                "G08 FF 60258 001000  780 139  548     +723788    +14        -302    -14  123  076  325  -36   32   -3 12 33 L1C ",
+                123.0E-10,
+                32.0E-10,
+                None,
+            ),
+            // 3 digit MDIO, right shifted
+            (
+                "G08 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   321  -3   20  +20   3 12 33 L1C ",
+                2.0E-10,
+                321.0E-10,
+                Some((20.0E-10, 20.0E-13, 3.0E-10)),
+            ),
+            // both, with tabulations
+            (
+                "G08 FF 60258 001000  780 139  548     +723788    +14        -302    -14 1234\t076  325  -36 1321\t-3   20  +20   3 12 33 L1C ",
+                1234.0E-10,
+                1321.0E-10,
+                Some((20.0E-10, 20.0E-13, 3.0E-10)),
+            ),
+        ] {
+            let mut crc = crate::crc::Crc8::new();
+            crc.update(content.as_bytes());
+            let line = format!("{}{:02X}", content, crc.finalize());
+
+            let track = Track::from_str(&line).unwrap();
+            assert_eq!(track.verify_ck(), Ok(()), "{}", line);
+
+            assert_eq!(track.sv, SV::from_str("G08").unwrap());
+            assert!((track.data.dsg - dsg).abs() < 1.0E-15);
+            assert_eq!(track.data.ioe, 76);
+            assert!((track.data.mdtr - 325.0E-10).abs() < 1.0E-15);
+            assert!((track.data.smdt + 36.0E-13).abs() < 1.0E-18);
+            assert!((track.data.mdio - mdio).abs() < 1.0E-15);
+            assert!((track.data.smdi + 3.0E-13).abs() < 1.0E-18);
+
+            match iono {
+                Some((msio, smsi, isg)) => {
+                    let data = track.iono.unwrap();
+                    assert!((data.msio - msio).abs() < 1.0E-15);
+                    assert!((data.smsi - smsi).abs() < 1.0E-18);
+                    assert!((data.isg - isg).abs() < 1.0E-15);
+                },
+                None => assert!(track.iono.is_none()),
+            }
+
+            assert_eq!(track.fdma_channel, Some(12));
+            assert_eq!(track.hc, 33);
+            assert_eq!(track.frc, "L1C");
+        }
+
+        // STTIME is a single hhmmss field
+        assert_eq!(
+            Track::from_str("G08 FF 60258 0010 00  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3 12 33 L1C 00"),
+            Err(super::Error::InvalidSttime("0010".to_string()))
+        );
+    }
+
+    #[test]
+    fn malformed_lines() {
+        use super::Error;
+
+        let valid = "G08 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3 12 33 L1C 00";
+
+        assert!(Track::from_str(valid).is_ok());
+
+        for (token, replacement, error) in [
+            ("G08", "X99", Error::InvalidSv("X99".to_string())),
+            ("FF", "9F", Error::UnknownClass("9F".to_string())),
+            ("60258", "6025A", Error::InvalidMjd("6025A".to_string())),
+            (
+                "001000",
+                "0010AA",
+                Error::InvalidSttime("0010AA".to_string()),
+            ),
+            ("-302", "-3O2", Error::InvalidField { name: "REFSYS" }),
+            (" 2 076", " 2 O76", Error::InvalidField { name: "IOE" }),
+            ("L1C", "L1CA", Error::InvalidField { name: "frc" }),
+            ("L1C 00", "L1C ZZ", Error::InvalidField { name: "ck" }),
+            ("  -3 12", "", Error::InvalidFormat),
+        ] {
+            let line = valid.replacen(token, replacement, 1);
+            assert_eq!(Track::from_str(&line), Err(error), "{}", line);
+        }
+
+        // canonical line, which CK is 0x74
+        let line = "E03 FF 60258 001000  780 139  548      723788     14        -302    -14    2  76  325  -36   32   -3   20   20   3  0  0  E1 74";
+
+        assert!(Track::from_str_checked(line).is_ok());
+
+        let corrupted = line.replace(" E1 74", " E1 47");
+
+        // only verified on demand
+        assert_eq!(Track::from_str(&corrupted).unwrap().ck, Some(0x47));
+
+        assert_eq!(
+            Track::from_str_checked(&corrupted),
+            Err(Error::ChecksumMismatch {
+                found: 0x47,
+                computed: 0x74,
+            })
+        );
+    }
+
+    #[test]
+    fn parsed_checksum_retention() {
+        for (line, ck, computed_ck, formatted_ck) in [
+            // non canonical alignment, correct checksum
+            (
+                "E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 A5",
+                0xA5,
+                0xA5,
+                "74",
+            ),
+            // incorrect checksum
+            (
+                "E08 FF 60258 002600  780 142  988     1745615     40        -233    -19    4  79  321  -96   73  -14  116  -53  13  0  0 E5a 84",
+                0x84,
+                0x30,
+                "30",
+            ),
+        ] {
+            let track = Track::from_str(line).unwrap();
+            assert_eq!(track.ck, Some(ck));
+
+            // computed over the raw line, whatever its alignment or termination
+            assert_eq!(track.computed_ck, Some(computed_ck));
+
+            let terminated = format!("{}\r\n", line);
+            let track = Track::from_str(&terminated).unwrap();
+            assert_eq!(track.computed_ck, Some(computed_ck));
+
+            // not part of the semantic content
+            let mut built = track.clone();
+            built.ck = None;
+            assert_eq!(built, track);
+
+            // formatting recomputes the checksum
+            let mut buf = Utf8Buffer::new(1024);
+            let mut writer = BufWriter::new(Utf8Buffer::new(1024));
+            track.format(&mut writer, &mut buf).unwrap();
+
+            let inner = writer.into_inner().unwrap_or_else(|_| panic!("oops"));
+            let formatted = inner.to_utf8_ascii().unwrap();
+            assert!(formatted.ends_with(formatted_ck), "{}", formatted);
+            assert_eq!(format!("{:02X}", track.crc()), formatted_ck);
+        }
+
+        assert_eq!(Track::default().ck, None);
+        assert_eq!(Track::default().computed_ck, None);
+    }
 }
@@ -4,7 +4,10 @@ use log::debug;
 use polyfit_rs::polyfit_rs::polyfit;
 use thiserror::Error;
 
-use crate::prelude::{Duration, Epoch, FittedData, SV};
+use crate::{
+    prelude::{Duration, Epoch, FittedData, Track, SV},
+    tracker::{Smoother, SvTrackingStatus},
+};
 
 /// CGGTTS track formation errors
 #[derive(Debug, Clone, Error)]
@@ -12,22 +15,52 @@ pub enum FitError {
     /// Unknown satellite (not tracked at all)
     #[error("unknown satellite (not tracked at all)")]
     UnknownSatellite,
-    /// At least two symbols are required to fit
-    #[error("track fitting requires at least 3 observations")]
+    /// Not enough symbols to fit, see [SVTracker::with_min_symbols]
+    #[error("not enough observations to fit")]
     NotEnoughSymbols,
     /// Linear regression failure. Either extreme values
     /// encountered or data gaps are present.
     #[error("linear regression failure")]
     LinearRegressionFailure,
+    /// Issue of Ephemeris does not fit in the IOE field (0-999)
+    #[error("invalid issue of ephemeris {0} (0-999)")]
+    InvalidIssueOfEphemeris(u16),
+    /// BeiDou hour of clock must be within 0-23
+    #[error("invalid beidou hour of clock {0} (0-23)")]
+    InvalidBeidouHour(u8),
+    /// Glonass daily quarter of hour must be within 1-96
+    #[error("invalid glonass quarter of hour {0} (1-96)")]
+    InvalidGlonassQuarter(u8),
+    /// Elevation at midpoint is below the elevation mask
+    #[error("midpoint elevation {0}° is below the elevation mask")]
+    BelowElevationMask(f64),
+    /// Forced midpoint must lie within the buffered period
+    #[error("midpoint is not within the buffered period")]
+    MidpointOutOfRange,
+    /// Hardware channel must be within 0-99
+    #[error("invalid hardware channel {0} (0-99)")]
+    InvalidHardwareChannel(u8),
+    /// Carrier code must be 1 to 3 ASCII characters
+    #[error("invalid carrier code \"{0}\"")]
+    InvalidCarrierCode(String),
+    /// Combination weights must be positive and finite
+    #[error("invalid combination weights")]
+    InvalidWeights,
 }
 
+/// Default minimal number of symbols to fit
+const DEFAULT_MIN_SYMBOLS: usize = 3;
+
 /// [SVTracker] is used to track an individual [SV].
-#[derive(Default, Debug, Clone)]
+/// It can only be built with [SVTracker::new].
+#[derive(Debug, Clone)]
 pub struct SVTracker {
     /// SV being tracked
     sv: SV,
     /// Symbols counter
     size: usize,
+    /// Minimal number of symbols to fit
+    min_symbols: usize,
     /// Sampling gap tolerance
     gap_tolerance: Option<Duration>,
     /// First Epoch of this fit
@@ -36,6 +69,10 @@ pub struct SVTracker {
     prev_t: Option<Epoch>,
     /// Internal buffer
     buffer: Vec<Observation>,
+    /// Possible REFSV and REFSYS smoothers
+    smoothers: Option<(Box<dyn Smoother>, Box<dyn Smoother>)>,
+    /// Issue of Ephemeris of the buffered content, if known
+    ioe: Option<u16>,
 }
 
 /// [Observation] you need to provide to attempt a CGGTTS fit.
@@ -57,6 +94,121 @@ pub struct Observation {
     pub elevation: f64,
     /// Azimuth in degrees
     pub azimuth: f64,
+    /// Possible carrier phase variation (in seconds) since previous [Observation],
+    /// used by the [Smoother] pre-filter, if any.
+    pub carrier_delta: Option<f64>,
+    /// Cycle slip flag: resets the [Smoother] pre-filter, if any.
+    pub cycle_slip: bool,
+    /// Possible receiver hardware channel (0-99) tracking this satellite.
+    pub channel: Option<u8>,
+}
+
+impl Observation {
+    /// Builds a new [Observation] at this [Epoch]
+    pub fn new(epoch: Epoch) -> Self {
+        Self {
+            epoch,
+            ..Default::default()
+        }
+    }
+
+    /// Returns [Observation] with desired REFSV (s)
+    pub fn with_refsv(self, refsv: f64) -> Self {
+        let mut s = self;
+        s.refsv = refsv;
+        s
+    }
+
+    /// Returns [Observation] with desired REFSYS (s)
+    pub fn with_refsys(self, refsys: f64) -> Self {
+        let mut s = self;
+        s.refsys = refsys;
+        s
+    }
+
+    /// Returns [Observation] with desired modeled tropospheric delay (s)
+    pub fn with_mdtr(self, mdtr: f64) -> Self {
+        let mut s = self;
+        s.mdtr = mdtr;
+        s
+    }
+
+    /// Returns [Observation] with desired modeled ionospheric delay (s)
+    pub fn with_mdio(self, mdio: f64) -> Self {
+        let mut s = self;
+        s.mdio = mdio;
+        s
+    }
+
+    /// Returns [Observation] with desired measured ionospheric delay (s)
+    pub fn with_msio(self, msio: f64) -> Self {
+        let mut s = self;
+        s.msio = Some(msio);
+        s
+    }
+
+    /// Returns [Observation] with desired elevation (degrees)
+    pub fn with_elevation(self, elevation: f64) -> Self {
+        let mut s = self;
+        s.elevation = elevation;
+        s
+    }
+
+    /// Returns [Observation] with desired azimuth (degrees)
+    pub fn with_azimuth(self, azimuth: f64) -> Self {
+        let mut s = self;
+        s.azimuth = azimuth;
+        s
+    }
+
+    /// Returns [Observation] with desired carrier phase variation (s)
+    pub fn with_carrier_delta(self, delta: f64) -> Self {
+        let mut s = self;
+        s.carrier_delta = Some(delta);
+        s
+    }
+
+    /// Returns [Observation] with desired cycle slip flag
+    pub fn with_cycle_slip(self, cycle_slip: bool) -> Self {
+        let mut s = self;
+        s.cycle_slip = cycle_slip;
+        s
+    }
+
+    /// Returns [Observation] with desired receiver hardware channel
+    pub fn with_channel(self, channel: u8) -> Self {
+        let mut s = self;
+        s.channel = Some(channel);
+        s
+    }
+
+    /// Reconstructs a representative [Observation] from a [Track].
+    /// This is an approximation: a [Track] only describes its
+    /// linear fit, evaluated at the track midpoint. The [Observation] is
+    /// therefore located at the track midpoint, with the fitted values,
+    /// while the slopes (SRSV, SRSYS..), DSG and the carrier phase are lost.
+    /// A hardware channel of 0 (unknown) is not reported.
+    pub fn from_track(track: &Track) -> Self {
+        Self {
+            epoch: track.epoch + track.duration / 2,
+            refsv: track.data.refsv,
+            refsys: track.data.refsys,
+            mdtr: track.data.mdtr,
+            mdio: track.data.mdio,
+            msio: track.iono.map(|iono| iono.msio),
+            elevation: track.elevation_deg,
+            azimuth: track.azimuth_deg,
+            carrier_delta: None,
+            cycle_slip: false,
+            channel: if track.hc == 0 { None } else { Some(track.hc) },
+        }
+    }
+}
+
+impl From<&Track> for Observation {
+    fn from(track: &Track) -> Self {
+        Self::from_track(track)
+    }
 }
 
 impl SVTracker {
@@ -67,30 +219,65 @@ impl SVTracker {
     pub fn new(satellite: SV) -> Self {
         Self {
             size: 0,
+            min_symbols: DEFAULT_MIN_SYMBOLS,
             t0: None,
             prev_t: None,
             sv: satellite,
             gap_tolerance: None,
             buffer: Vec::with_capacity(16),
+            smoothers: None,
+            ioe: None,
         }
     }
 
+    /// Returns the [SV] being tracked.
+    pub fn sv(&self) -> SV {
+        self.sv
+    }
+
     /// Define a new [SVTracker] with desired observation gap tolerance.
-    pub fn with_gap_tolerance(&self, tolerance: Duration) -> Self {
-        let mut s = self.clone();
+    pub fn with_gap_tolerance(self, tolerance: Duration) -> Self {
+        let mut s = self;
         s.gap_tolerance = Some(tolerance);
         s
     }
 
+    /// Define a new [SVTracker] with desired minimal number of symbols
+    /// (3 by default) required to fit. This cannot be lower than 2,
+    /// which is the minimum for a linear regression.
+    pub fn with_min_symbols(self, min_symbols: usize) -> Self {
+        let mut s = self;
+        s.min_symbols = min_symbols.max(2);
+        s
+    }
+
+    /// Define a new [SVTracker] with desired [Smoother] pre-filter,
+    /// applied to both REFSV and REFSYS prior fitting.
+    /// The pre-filter is reset on data gaps and cycle slips.
+    pub fn with_smoother<S: Smoother + Clone + 'static>(self, smoother: S) -> Self {
+        let mut s = self;
+        s.smoothers = Some((Box::new(smoother.clone()), Box::new(smoother)));
+        s
+    }
+
+    pub(crate) fn with_boxed_smoother(self, smoother: Box<dyn Smoother>) -> Self {
+        let mut s = self;
+        s.smoothers = Some((smoother.clone(), smoother));
+        s
+    }
+
     /// Feed new [Observation] at t [Epoch] of observation (sampling).
     /// Although CGGTTS works in UTC internally, we accept any timescale here.
     /// Samples must be provided in chronological order.
     /// If you provide MSIO, you are expected to provide it at very single epoch,
     /// like any other fields, in order to obtain valid results.
     ///
+    /// When a [Smoother] is defined, REFSV and REFSYS are smoothed
+    /// before being buffered.
+    ///
     /// ## Input
     /// - data: [Observation]
-    pub fn new_observation(&mut self, data: Observation) {
+    pub fn new_observation(&mut self, mut data: Observation) {
         if let Some(past_t) = self.prev_t {
             if let Some(tolerance) = self.gap_tolerance {
                 let dt = data.epoch - past_t;
@@ -98,10 +285,21 @@ impl SVTracker {
                     debug!("{}({}) - {} data gap", data.epoch, self.sv, dt);
                     self.size = 0;
                     self.buffer.clear();
+                    self.reset_smoothers();
                 }
             }
         }
 
+        if data.cycle_slip {
+            debug!("{}({}) - cycle slip", data.epoch, self.sv);
+            self.reset_smoothers();
+        }
+
+        if let Some((refsv, refsys)) = &mut self.smoothers {
+            data.refsv = refsv.smooth(data.refsv, data.carrier_delta);
+            data.refsys = refsys.smooth(data.refsys, data.carrier_delta);
+        }
+
         if self.t0.is_none() {
             self.t0 = Some(data.epoch);
         }
@@ -111,11 +309,45 @@ impl SVTracker {
         self.size += 1;
     }
 
+    /// Feed new [Observation], like [Self::new_observation], obtained
+    /// with this Issue of Ephemeris. When the Issue of Ephemeris changes,
+    /// the internal buffer is reset first, so a fit always relies on
+    /// a single ephemeris.
+    pub fn new_observation_with_ioe(&mut self, data: Observation, ioe: u16) {
+        if let Some(prev_ioe) = self.ioe {
+            if prev_ioe != ioe {
+                debug!(
+                    "{}({}) - ephemeris update ({} -> {})",
+                    data.epoch, self.sv, prev_ioe, ioe
+                );
+                self.reset();
+            }
+        }
+
+        self.ioe = Some(ioe);
+        self.new_observation(data);
+    }
+
+    /// Returns the Issue of Ephemeris of the buffered [Observation]s,
+    /// if provided with [Self::new_observation_with_ioe].
+    pub fn issue_of_ephemeris(&self) -> Option<u16> {
+        self.ioe
+    }
+
     /// Manual reset of the internal buffer.
     pub fn reset(&mut self) {
         self.prev_t = None;
         self.size = 0;
+        self.ioe = None;
         self.buffer.clear();
+        self.reset_smoothers();
+    }
+
+    fn reset_smoothers(&mut self) {
+        if let Some((refsv, refsys)) = &mut self.smoothers {
+            refsv.reset();
+            refsys.reset();
+        }
     }
 
     /// True if at least one measurement is currently latched and may contribute to a fit.
@@ -123,30 +355,130 @@ impl SVTracker {
         self.size > 0
     }
 
+    /// Returns the number of measurements currently latched.
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    /// True if no measurement is currently latched.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Returns the [Duration] covered by the measurements currently latched
+    /// (last minus first buffered [Epoch]), if any.
+    pub fn time_span(&self) -> Option<Duration> {
+        let first = self.buffer.first()?;
+        let last = self.buffer.last()?;
+        Some(last.epoch - first.epoch)
+    }
+
+    /// Returns the current [SvTrackingStatus] of this [SVTracker].
+    pub fn status(&self) -> SvTrackingStatus {
+        SvTrackingStatus {
+            sv: self.sv,
+            samples: self.size,
+            first: self.buffer.first().map(|data| data.epoch),
+            last: self.buffer.last().map(|data| data.epoch),
+            fit_ready: self.size >= self.min_symbols,
+        }
+    }
+
     /// Apply fit algorithm over internal buffer.
     /// You manage the buffer content and sampling and are responsible
     /// for the [FittedData] you may obtain. The requirement being at least 3
-    /// symbols must have been buffered.
+    /// symbols (by default) must have been buffered, see [Self::with_min_symbols].
     pub fn fit(&mut self) -> Result<FittedData, FitError> {
-        // Request 3 symbols at least
-        if self.size < 3 {
+        if self.size < self.min_symbols {
             return Err(FitError::NotEnoughSymbols);
         }
 
-        let midpoint = if self.size % 2 == 0 {
+        let midpoint = self.midpoint_index();
+        let t_mid = self.buffer[midpoint].epoch;
+        self.fit_at(t_mid, midpoint)
+    }
+
+    /// Apply fit algorithm over internal buffer, like [Self::fit],
+    /// only if the elevation at midpoint is at least `min_elevation_deg`.
+    /// Otherwise, the buffered content is discarded (low passes do not
+    /// produce tracks) and [FitError::BelowElevationMask] is returned.
+    pub fn fit_with_min_elevation(
+        &mut self,
+        min_elevation_deg: f64,
+    ) -> Result<FittedData, FitError> {
+        if self.size < self.min_symbols {
+            return Err(FitError::NotEnoughSymbols);
+        }
+
+        let elevation = self.buffer[self.midpoint_index()].elevation;
+
+        if elevation < min_elevation_deg {
+            self.t0 = None;
+            self.buffer.clear();
+            self.size = 0;
+            return Err(FitError::BelowElevationMask(elevation));
+        }
+
+        self.fit()
+    }
+
+    /// Index of the data-derived midpoint, within the internal buffer.
+    fn midpoint_index(&self) -> usize {
+        if self.size % 2 == 0 {
             self.size / 2 - 1
         } else {
             (self.size + 1) / 2 - 1
-        };
+        }
+    }
+
+    /// Apply fit algorithm over internal buffer, like [Self::fit],
+    /// but evaluates the fitted values at this forced `midpoint`, instead of
+    /// the data-derived one. This allows aligning [FittedData] to the BIPM
+    /// tracking calendar. Azimuth, elevation and hardware channel are those
+    /// of the closest buffered measurement.
+    /// The `midpoint` must lie within the buffered period.
+    pub fn fit_at_midpoint(&mut self, midpoint: Epoch) -> Result<FittedData, FitError> {
+        if self.size < self.min_symbols {
+            return Err(FitError::NotEnoughSymbols);
+        }
+
+        let (t0, t_last) = (self.buffer[0].epoch, self.buffer[self.size - 1].epoch);
+
+        // expressed like the buffered content
+        let t_mid = midpoint.to_time_scale(t0.time_scale);
+
+        if t_mid < t0 || t_mid > t_last {
+            return Err(FitError::MidpointOutOfRange);
+        }
+
+        let closest = self
+            .buffer
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, data)| (data.epoch - t_mid).abs())
+            .map(|(index, _)| index)
+            .unwrap_or_default();
+
+        self.fit_at(t_mid, closest)
+    }
 
+    /// Fit algorithm, evaluated at `t_mid`, while azimuth, elevation and
+    /// hardware channel are picked at the `midpoint` index.
+    fn fit_at(&mut self, t_mid: Epoch, midpoint: usize) -> Result<FittedData, FitError> {
         // Retrieve information @ mid point
         let t0 = self.buffer[0].epoch;
-        let t_mid = self.buffer[midpoint].epoch;
         let t_mid_s = t_mid.duration.to_unit(Unit::Second);
         let t_last = self.buffer[self.size - 1].epoch;
 
         let azim_mid = self.buffer[midpoint].azimuth;
         let elev_mid = self.buffer[midpoint].elevation;
+        let hc_mid = self.buffer[midpoint].channel;
+
+        if let Some(hc) = hc_mid {
+            if hc > 99 {
+                return Err(FitError::InvalidHardwareChannel(hc));
+            }
+        }
 
         let mut fitted = FittedData::default();
 
@@ -156,6 +488,7 @@ impl SVTracker {
         fitted.midtrack = t_mid;
         fitted.azimuth_deg = azim_mid;
         fitted.elevation_deg = elev_mid;
+        fitted.hc = hc_mid;
 
         // retrieve x_s
         let x_s = self
@@ -295,13 +628,22 @@ impl SVTracker {
 
 #[cfg(test)]
 mod test {
-    use crate::prelude::{Duration, Epoch, Observation, SVTracker, SV};
+    use crate::prelude::{
+        Duration, Epoch, FitError, HatchFilter, IonosphericData, MovingAverage, Observation,
+        SVTracker, Smoother, TimeScale, Track, TrackData, SV,
+    };
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use std::str::FromStr;
 
     #[test]
     fn tracker_no_gap_x3() {
         let g01 = SV::from_str("G01").unwrap();
         let mut tracker = SVTracker::new(g01);
+        assert_eq!(tracker.sv(), g01);
+
+        assert!(tracker.is_empty());
+        assert_eq!(tracker.len(), 0);
+        assert_eq!(tracker.time_span(), None);
 
         for obs in [
             Observation {
@@ -311,6 +653,9 @@ mod test {
                 mdtr: 3.0,
                 mdio: 4.0,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.0,
                 azimuth: 7.0,
             },
@@ -321,6 +666,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -330,6 +678,9 @@ mod test {
 
         assert!(tracker.fit().is_err());
 
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(tracker.time_span(), Some(Duration::from_seconds(30.0)));
+
         tracker.new_observation(Observation {
             epoch: Epoch::from_str("2020-01-01T00:01:00 UTC").unwrap(),
             refsv: 1.2,
@@ -337,10 +688,17 @@ mod test {
             mdtr: 3.2,
             mdio: 4.2,
             msio: None,
+            carrier_delta: None,
+            cycle_slip: false,
+            channel: None,
             elevation: 6.2,
             azimuth: 7.2,
         });
 
+        assert_eq!(tracker.len(), 3);
+        assert!(!tracker.is_empty());
+        assert_eq!(tracker.time_span(), Some(Duration::from_seconds(60.0)));
+
         let fitted = tracker.fit().unwrap();
 
         assert_eq!(fitted.sv, g01);
@@ -373,6 +731,9 @@ mod test {
                 mdtr: 3.0,
                 mdio: 4.0,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.0,
                 azimuth: 7.0,
             },
@@ -383,6 +744,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -399,6 +763,9 @@ mod test {
             mdtr: 3.2,
             mdio: 4.2,
             msio: None,
+            carrier_delta: None,
+            cycle_slip: false,
+            channel: None,
             elevation: 6.2,
             azimuth: 7.2,
         });
@@ -437,6 +804,9 @@ mod test {
                 mdtr: 3.0,
                 mdio: 4.0,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.0,
                 azimuth: 7.0,
             },
@@ -447,6 +817,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -457,6 +830,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -467,6 +843,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -492,6 +871,9 @@ mod test {
                 mdtr: 3.0,
                 mdio: 4.0,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.0,
                 azimuth: 7.0,
             },
@@ -502,6 +884,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -512,6 +897,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -522,6 +910,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -539,6 +930,9 @@ mod test {
                 mdtr: 3.0,
                 mdio: 4.0,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.0,
                 azimuth: 7.0,
             },
@@ -549,6 +943,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -559,6 +956,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -569,6 +969,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -594,6 +997,9 @@ mod test {
                 mdtr: 3.0,
                 mdio: 4.0,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.0,
                 azimuth: 7.0,
             },
@@ -604,6 +1010,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -614,6 +1023,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -624,6 +1036,9 @@ mod test {
                 mdtr: 3.1,
                 mdio: 4.1,
                 msio: None,
+                carrier_delta: None,
+                cycle_slip: false,
+                channel: None,
                 elevation: 6.1,
                 azimuth: 7.1,
             },
@@ -633,4 +1048,315 @@ mod test {
 
         assert!(tracker.fit().is_err());
     }
+
+    /// Feeds a noisy constant series (1 us, +/- 10 ns) to [SVTracker]
+    /// and returns fitted (REFSYS, DSG)
+    fn noisy_series_fit(tracker: SVTracker) -> (f64, f64) {
+        let mut tracker = tracker;
+        let mut rng = StdRng::seed_from_u64(0);
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+
+        for i in 0..27 {
+            let noise = rng.gen_range(-10.0E-9..10.0E-9);
+            tracker.new_observation(Observation {
+                epoch: t0 + i as f64 * Duration::from_seconds(30.0),
+                refsv: 1.0E-6 + noise,
+                refsys: 1.0E-6 + noise,
+                carrier_delta: Some(0.0),
+                ..Default::default()
+            });
+        }
+
+        let fitted = tracker.fit().unwrap();
+        (fitted.refsys_s, fitted.dsg)
+    }
+
+    fn smoothed_series<S: Smoother + Clone + 'static>(smoother: S) {
+        let g01 = SV::from_str("G01").unwrap();
+
+        let (raw_refsys, raw_dsg) = noisy_series_fit(SVTracker::new(g01));
+        let (refsys, dsg) = noisy_series_fit(SVTracker::new(g01).with_smoother(smoother));
+
+        assert!(dsg < raw_dsg / 2.0, "dsg={} raw={}", dsg, raw_dsg);
+        assert!((raw_refsys - 1.0E-6).abs() < 5.0E-9);
+        assert!((refsys - 1.0E-6).abs() < 5.0E-9);
+    }
+
+    #[test]
+    fn hatch_smoothing() {
+        smoothed_series(HatchFilter::new(100));
+    }
+
+    #[test]
+    fn moving_average_smoothing() {
+        smoothed_series(MovingAverage::new(10));
+    }
+
+    #[test]
+    fn smoother_reset_on_cycle_slip() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let mut tracker = SVTracker::new(g01).with_smoother(HatchFilter::new(100));
+
+        for (i, &(refsys, cycle_slip)) in [(1.0, false), (3.0, false), (10.0, true)]
+            .iter()
+            .enumerate()
+        {
+            tracker.new_observation(Observation {
+                epoch: t0 + i as f64 * Duration::from_seconds(30.0),
+                refsys,
+                carrier_delta: Some(0.0),
+                cycle_slip,
+                ..Default::default()
+            });
+        }
+
+        let refsys = tracker
+            .buffer
+            .iter()
+            .map(|obs| obs.refsys)
+            .collect::<Vec<_>>();
+
+        assert_eq!(refsys, vec![1.0, 2.0, 10.0]);
+    }
+
+    #[test]
+    fn tracker_hardware_channel() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let mut tracker = SVTracker::new(g01);
+
+        for (i, &channel) in [3, 12, 3].iter().enumerate() {
+            tracker.new_observation(Observation {
+                epoch: t0 + i as f64 * Duration::from_seconds(30.0),
+                channel: Some(channel),
+                ..Default::default()
+            });
+        }
+
+        let fitted = tracker.fit().unwrap();
+        assert_eq!(fitted.hc, Some(12));
+
+        for (i, &channel) in [100, 100, 100].iter().enumerate() {
+            tracker.new_observation(Observation {
+                epoch: t0 + i as f64 * Duration::from_seconds(30.0),
+                channel: Some(channel),
+                ..Default::default()
+            });
+        }
+
+        assert!(matches!(
+            tracker.fit(),
+            Err(FitError::InvalidHardwareChannel(100))
+        ));
+    }
+
+    #[test]
+    fn observation_builder() {
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+
+        let obs = Observation::new(t0)
+            .with_refsv(1.0E-6)
+            .with_refsys(2.0E-9)
+            .with_mdtr(3.0E-9)
+            .with_mdio(4.0E-9)
+            .with_msio(5.0E-9)
+            .with_elevation(45.0)
+            .with_azimuth(120.0)
+            .with_carrier_delta(1.0E-12)
+            .with_cycle_slip(true)
+            .with_channel(7);
+
+        assert_eq!(obs.epoch, t0);
+        assert_eq!(obs.refsv, 1.0E-6);
+        assert_eq!(obs.refsys, 2.0E-9);
+        assert_eq!(obs.mdtr, 3.0E-9);
+        assert_eq!(obs.mdio, 4.0E-9);
+        assert_eq!(obs.msio, Some(5.0E-9));
+        assert_eq!(obs.elevation, 45.0);
+        assert_eq!(obs.azimuth, 120.0);
+        assert_eq!(obs.carrier_delta, Some(1.0E-12));
+        assert!(obs.cycle_slip);
+        assert_eq!(obs.channel, Some(7));
+    }
+
+    #[test]
+    fn observations_from_tracks() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+
+        // REFSYS drifting by 1 ns per track
+        let tracks = (0..5)
+            .map(|nth| Track {
+                sv: g01,
+                epoch: t0 + Duration::from_seconds(960.0 * nth as f64),
+                duration: Duration::from_seconds(780.0),
+                elevation_deg: 30.0 + nth as f64,
+                azimuth_deg: 120.0,
+                data: TrackData {
+                    refsv: 1.0E-6,
+                    refsys: nth as f64 * 1.0E-9,
+                    ..Default::default()
+                },
+                iono: Some(IonosphericData {
+                    msio: 5.0E-9,
+                    smsi: 0.0,
+                    isg: 0.0,
+                }),
+                hc: if nth == 0 { 0 } else { 3 },
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let obs = Observation::from(&tracks[0]);
+        assert_eq!(obs.epoch, t0 + Duration::from_seconds(390.0));
+        assert_eq!(obs.refsv, 1.0E-6);
+        assert_eq!(obs.refsys, 0.0);
+        assert_eq!(obs.msio, Some(5.0E-9));
+        assert_eq!(obs.elevation, 30.0);
+        assert_eq!(obs.channel, None);
+
+        let mut tracker = SVTracker::new(g01);
+
+        for track in tracks.iter() {
+            tracker.new_observation(Observation::from_track(track));
+        }
+
+        let fitted = tracker.fit().unwrap();
+
+        // re-fitted over the five tracks
+        assert_eq!(fitted.first_t, t0 + Duration::from_seconds(390.0));
+        assert_eq!(
+            fitted.midtrack,
+            tracks[2].epoch + Duration::from_seconds(390.0)
+        );
+        assert!((fitted.refsys_s - 2.0E-9).abs() < 1.0E-15);
+        assert!((fitted.srsys_s_s - 1.0E-9 / 960.0).abs() < 1.0E-15);
+        assert!((fitted.refsv_s - 1.0E-6).abs() < 1.0E-15);
+        assert_eq!(fitted.elevation_deg, 32.0);
+        assert_eq!(fitted.hc, Some(3));
+    }
+
+    #[test]
+    fn forced_midpoint() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+
+        let mut tracker = SVTracker::new(g01);
+
+        // REFSYS drifting by 1 ns/s
+        let fill = |tracker: &mut SVTracker| {
+            for nth in 0..4 {
+                let dt = 30.0 * nth as f64;
+                tracker.new_observation(
+                    Observation::new(t0 + Duration::from_seconds(dt))
+                        .with_refsys(dt * 1.0E-9)
+                        .with_elevation(10.0 + nth as f64),
+                );
+            }
+        };
+
+        fill(&mut tracker);
+        let derived = tracker.fit().unwrap();
+
+        fill(&mut tracker);
+        let forced = tracker
+            .fit_at_midpoint(t0 + Duration::from_seconds(45.0))
+            .unwrap();
+
+        // data derived midpoint is the second measurement
+        assert_eq!(derived.midtrack, t0 + Duration::from_seconds(30.0));
+        assert!((derived.refsys_s - 30.0E-9).abs() < 1.0E-12);
+
+        assert_eq!(forced.midtrack, t0 + Duration::from_seconds(45.0));
+        assert!((forced.refsys_s - 45.0E-9).abs() < 1.0E-12);
+        assert!((forced.srsys_s_s - derived.srsys_s_s).abs() < 1.0E-15);
+        assert_eq!(forced.first_t, derived.first_t);
+        assert_eq!(forced.duration, derived.duration);
+
+        // forced midpoint expressed in another timescale
+        fill(&mut tracker);
+        let gpst = (t0 + Duration::from_seconds(60.0)).to_time_scale(TimeScale::GPST);
+        let forced = tracker.fit_at_midpoint(gpst).unwrap();
+        assert!((forced.refsys_s - 60.0E-9).abs() < 1.0E-12);
+        assert_eq!(forced.elevation_deg, 12.0);
+
+        // outside of the buffered period
+        fill(&mut tracker);
+        assert!(matches!(
+            tracker.fit_at_midpoint(t0 + Duration::from_seconds(120.0)),
+            Err(FitError::MidpointOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn elevation_mask() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+
+        let mut tracker = SVTracker::new(g01);
+
+        let fill = |tracker: &mut SVTracker, elevation: f64| {
+            for nth in 0..3 {
+                tracker.new_observation(
+                    Observation::new(t0 + Duration::from_seconds(30.0 * nth as f64))
+                        .with_elevation(elevation + nth as f64),
+                );
+            }
+        };
+
+        // low pass
+        fill(&mut tracker, 5.0);
+
+        match tracker.fit_with_min_elevation(10.0) {
+            Err(FitError::BelowElevationMask(elevation)) => assert_eq!(elevation, 6.0),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // low pass is discarded
+        assert!(tracker.is_empty());
+
+        fill(&mut tracker, 9.0);
+
+        let fitted = tracker.fit_with_min_elevation(10.0).unwrap();
+        assert_eq!(fitted.elevation_deg, 10.0);
+    }
+
+    #[test]
+    fn min_symbols() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+
+        for min_symbols in [2, 3, 5] {
+            let mut tracker = SVTracker::new(g01).with_min_symbols(min_symbols);
+
+            for nth in 0..min_symbols - 1 {
+                tracker.new_observation(
+                    Observation::new(t0 + Duration::from_seconds(30.0 * nth as f64))
+                        .with_refsys(nth as f64),
+                );
+            }
+
+            assert!(!tracker.status().fit_ready);
+            assert!(matches!(tracker.fit(), Err(FitError::NotEnoughSymbols)));
+
+            tracker.new_observation(
+                Observation::new(t0 + Duration::from_seconds(30.0 * (min_symbols - 1) as f64))
+                    .with_refsys((min_symbols - 1) as f64),
+            );
+
+            assert!(tracker.status().fit_ready);
+
+            let fitted = tracker.fit().unwrap();
+            assert_eq!(
+                fitted.duration,
+                Duration::from_seconds(30.0 * (min_symbols - 1) as f64)
+            );
+        }
+
+        // linear regression requires 2 symbols at least
+        let mut tracker = SVTracker::new(g01).with_min_symbols(1);
+        tracker.new_observation(Observation::new(t0));
+        assert!(matches!(tracker.fit(), Err(FitError::NotEnoughSymbols)));
+    }
 }
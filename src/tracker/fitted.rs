@@ -1,15 +1,25 @@
-use crate::prelude::{CommonViewClass, Duration, Epoch, IonosphericData, Track, TrackData, SV};
+use crate::prelude::{
+    CarrierCode, CommonViewClass, Duration, EphemerisData, Epoch, FitError, IoeKind,
+    IonosphericData, Track, TrackBuilder, TrackData, SV,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// [FittedData] resulting from running the fit algorithm over many [Observation]s.
 #[derive(Debug, Copy, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FittedData {
     /// [SV] that was used
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::sv"))]
     pub sv: SV,
     /// Fit time window duration
     pub duration: Duration,
     /// Fit start time
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::epoch"))]
     pub first_t: Epoch,
     /// [Epoch] at midtrack
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_support::epoch"))]
     pub midtrack: Epoch,
     /// Satellite elevation at midtrack (in degrees)
     pub elevation_deg: f64,
@@ -39,9 +49,44 @@ pub struct FittedData {
     pub smsi_s_s: Option<f64>,
     /// Possible ISG: MSIO Root Mean Square
     pub isg: Option<f64>,
+    /// Possible receiver hardware channel (0-99) at midtrack
+    pub hc: Option<u8>,
 }
 
 impl FittedData {
+    /// CSV header, describing each field of [Self::to_csv_row].
+    pub const CSV_HEADER: &'static str = "sv,first_t,duration_s,midtrack,elevation_deg,azimuth_deg,refsv_s,srsv_s_s,refsys_s,srsys_s_s,dsg,mdtr_s,smdt_s_s,mdio_s,smdi_s_s,msio_s,smsi_s_s,isg,hc";
+
+    /// Formats this [FittedData] as a CSV row, following [Self::CSV_HEADER].
+    /// Values are expressed in seconds (and seconds per second) and degrees,
+    /// undefined optional fields are left empty.
+    pub fn to_csv_row(&self) -> String {
+        let optional = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.sv,
+            self.first_t,
+            self.duration.to_seconds(),
+            self.midtrack,
+            self.elevation_deg,
+            self.azimuth_deg,
+            self.refsv_s,
+            self.srsv_s_s,
+            self.refsys_s,
+            self.srsys_s_s,
+            self.dsg,
+            self.mdtr_s,
+            self.smdt_s_s,
+            self.mdio_s,
+            self.smdi_s_s,
+            optional(self.msio_s),
+            optional(self.smsi_s_s),
+            optional(self.isg),
+            self.hc.map(|hc| hc.to_string()).unwrap_or_default(),
+        )
+    }
+
     /// Form a new CGGTTS [Track] from this [FittedData],
     /// ready to be formatted.
     /// ## Input
@@ -52,6 +97,11 @@ impl FittedData {
     /// daily quarters of hours, starting at 1 for 00:00:00 midnight.
     /// For BeiDou, the hour of clock, between 0-23 should be used.
     /// - rinex_code: RINEX code.
+    ///
+    /// The hardware channel is not verified here, prefer the
+    /// constellation aware methods which verify every field.
+    /// An invalid `rinex_code` is not rejected: only its first (up to 3)
+    /// ASCII non whitespace characters are preserved.
     pub fn to_track(&self, class: CommonViewClass, data: u16, rinex_code: &str) -> Track {
         Track {
             class,
@@ -61,30 +111,469 @@ impl FittedData {
             azimuth_deg: self.azimuth_deg,
             elevation_deg: self.elevation_deg,
             fdma_channel: None,
-            data: TrackData {
-                ioe: data,
-                refsv: self.refsv_s,
-                srsv: self.srsv_s_s,
-                refsys: self.refsys_s,
-                srsys: self.srsys_s_s,
-                dsg: self.dsg,
-                mdtr: self.mdtr_s,
-                smdt: self.smdt_s_s,
-                mdio: self.mdio_s,
-                smdi: self.smdi_s_s,
+            data: self.track_data(data),
+            iono: self.ionospheric_data(),
+            hc: self.hc.unwrap_or(0),
+            frc: CarrierCode::truncated(rinex_code),
+            provenance: None,
+            ck: None,
+            computed_ck: None,
+        }
+    }
+
+    /// Returns [TrackData] with desired `IOE` field
+    fn track_data(&self, ioe: u16) -> TrackData {
+        TrackData {
+            ioe,
+            refsv: self.refsv_s,
+            srsv: self.srsv_s_s,
+            refsys: self.refsys_s,
+            srsys: self.srsys_s_s,
+            dsg: self.dsg,
+            mdtr: self.mdtr_s,
+            smdt: self.smdt_s_s,
+            mdio: self.mdio_s,
+            smdi: self.smdi_s_s,
+        }
+    }
+
+    /// Returns [IonosphericData], if measured
+    fn ionospheric_data(&self) -> Option<IonosphericData> {
+        self.msio_s.map(|msio| IonosphericData {
+            msio,
+            smsi: self.smsi_s_s.unwrap_or_default(),
+            isg: self.isg.unwrap_or_default(),
+        })
+    }
+
+    /// Form a new CGGTTS [Track] from this [FittedData], for constellations
+    /// that use an Issue of Ephemeris (GPS, Galileo, QZSS..).
+    /// ## Input
+    /// - class: [CommonViewClass]
+    /// - ioe: Issue of Ephemeris, between 0-999.
+    /// - rinex_code: RINEX code.
+    pub fn to_gps_track(
+        &self,
+        class: CommonViewClass,
+        ioe: u16,
+        rinex_code: &str,
+    ) -> Result<Track, FitError> {
+        self.check_hardware_channel()?;
+        Self::check_carrier_code(rinex_code)?;
+        if ioe > 999 {
+            return Err(FitError::InvalidIssueOfEphemeris(ioe));
+        }
+        Ok(self.to_track(class, ioe, rinex_code))
+    }
+
+    /// Form a new CGGTTS [Track] from this [FittedData], for BeiDou.
+    /// ## Input
+    /// - class: [CommonViewClass]
+    /// - hour: hour of clock, between 0-23.
+    /// - rinex_code: RINEX code.
+    pub fn to_beidou_track(
+        &self,
+        class: CommonViewClass,
+        hour: u8,
+        rinex_code: &str,
+    ) -> Result<Track, FitError> {
+        self.check_hardware_channel()?;
+        Self::check_carrier_code(rinex_code)?;
+        if hour > 23 {
+            return Err(FitError::InvalidBeidouHour(hour));
+        }
+        Ok(self.to_track(class, hour as u16, rinex_code))
+    }
+
+    /// Form a new CGGTTS [Track] from this [FittedData], for Glonass.
+    /// ## Input
+    /// - class: [CommonViewClass]
+    /// - quarter: date of ephemeris as daily quarter of hours, between 1-96,
+    ///   starting at 1 for 00:00:00 midnight.
+    /// - rinex_code: RINEX code.
+    pub fn to_glonass_track(
+        &self,
+        class: CommonViewClass,
+        quarter: u8,
+        rinex_code: &str,
+    ) -> Result<Track, FitError> {
+        self.check_hardware_channel()?;
+        Self::check_carrier_code(rinex_code)?;
+        if quarter == 0 || quarter > 96 {
+            return Err(FitError::InvalidGlonassQuarter(quarter));
+        }
+        Ok(self.to_track(class, quarter as u16, rinex_code))
+    }
+
+    /// Form a new CGGTTS [Track] from this [FittedData], deriving the `IOE`
+    /// field from the [Constellation](crate::prelude::Constellation) of the tracked [SV]:
+    /// - `iode` is used as is, for constellations that use an Issue of Ephemeris
+    /// - the BeiDou hour of clock is derived from `ephemeris_epoch` (toc)
+    /// - the Glonass quarter of hour is derived from `ephemeris_epoch` (tb)
+    /// ## Input
+    /// - class: [CommonViewClass]
+    /// - iode: Issue of Ephemeris, between 0-999.
+    /// - ephemeris_epoch: [Epoch] of the ephemeris used.
+    /// - rinex_code: RINEX code.
+    pub fn to_track_with_ephemeris(
+        &self,
+        class: CommonViewClass,
+        iode: u16,
+        ephemeris_epoch: Epoch,
+        rinex_code: &str,
+    ) -> Result<Track, FitError> {
+        match IoeKind::from(self.sv.constellation) {
+            IoeKind::IssueOfEphemeris => self.to_gps_track(class, iode, rinex_code),
+            IoeKind::BeidouHour => {
+                let hour = EphemerisData::from_beidou_epoch(ephemeris_epoch).ioe();
+                self.to_beidou_track(class, hour as u8, rinex_code)
             },
-            iono: if let Some(msio_s) = self.msio_s {
-                Some(IonosphericData {
-                    msio: msio_s,
-                    smsi: self.smsi_s_s.unwrap(),
-                    isg: self.isg.unwrap(),
-                })
-            } else {
-                None
+            IoeKind::GlonassQuarter => {
+                let quarter = EphemerisData::from_glonass_epoch(ephemeris_epoch).ioe();
+                self.to_glonass_track(class, quarter as u8, rinex_code)
             },
-            // TODO
-            hc: 0,
-            frc: rinex_code.to_string(),
         }
     }
+
+    /// Combines several [FittedData] (one per satellite, over the same
+    /// common view period) into a single solution, as a weighted average.
+    /// REFSV, REFSYS, the modeled delays and their derivatives are averaged.
+    /// DSG combines the individual DSGs with the dispersion of REFSYS
+    /// between satellites. The time window spans all fits.
+    /// The [SV], elevation, azimuth and hardware channel are those of the
+    /// reference satellite: the one with the highest weight.
+    /// The measured ionospheric delay is only combined
+    /// if all satellites provide it.
+    pub fn weighted_combination(fitted: &[(FittedData, f64)]) -> Result<Self, FitError> {
+        let reference = fitted
+            .iter()
+            .max_by(|(_, w1), (_, w2)| w1.total_cmp(w2))
+            .map(|(data, _)| data)
+            .ok_or(FitError::NotEnoughSymbols)?;
+
+        if fitted
+            .iter()
+            .any(|(_, weight)| !weight.is_finite() || *weight < 0.0)
+        {
+            return Err(FitError::InvalidWeights);
+        }
+
+        let total_weight = fitted.iter().map(|(_, weight)| weight).sum::<f64>();
+
+        if total_weight <= 0.0 {
+            return Err(FitError::InvalidWeights);
+        }
+
+        let mean = |value: &dyn Fn(&FittedData) -> f64| {
+            fitted
+                .iter()
+                .map(|(data, weight)| value(data) * weight)
+                .sum::<f64>()
+                / total_weight
+        };
+
+        let first_t = fitted
+            .iter()
+            .map(|(data, _)| data.first_t)
+            .min()
+            .unwrap_or(reference.first_t);
+
+        let last_t = fitted
+            .iter()
+            .map(|(data, _)| data.first_t + data.duration)
+            .max()
+            .unwrap_or(reference.first_t);
+
+        let refsys_s = mean(&|data| data.refsys_s);
+        let dsg = mean(&|data| data.dsg.powi(2) + (data.refsys_s - refsys_s).powi(2)).sqrt();
+
+        let mut combined = Self {
+            sv: reference.sv,
+            duration: last_t - first_t,
+            first_t,
+            midtrack: first_t + (last_t - first_t) / 2,
+            elevation_deg: reference.elevation_deg,
+            azimuth_deg: reference.azimuth_deg,
+            refsv_s: mean(&|data| data.refsv_s),
+            srsv_s_s: mean(&|data| data.srsv_s_s),
+            refsys_s,
+            srsys_s_s: mean(&|data| data.srsys_s_s),
+            dsg,
+            mdtr_s: mean(&|data| data.mdtr_s),
+            smdt_s_s: mean(&|data| data.smdt_s_s),
+            mdio_s: mean(&|data| data.mdio_s),
+            smdi_s_s: mean(&|data| data.smdi_s_s),
+            msio_s: None,
+            smsi_s_s: None,
+            isg: None,
+            hc: reference.hc,
+        };
+
+        if fitted.iter().all(|(data, _)| data.msio_s.is_some()) {
+            combined.msio_s = Some(mean(&|data| data.msio_s.unwrap_or_default()));
+            combined.smsi_s_s = Some(mean(&|data| data.smsi_s_s.unwrap_or_default()));
+            combined.isg = Some(mean(&|data| data.isg.unwrap_or_default()));
+        }
+
+        Ok(combined)
+    }
+
+    fn check_carrier_code(rinex_code: &str) -> Result<(), FitError> {
+        match rinex_code.parse::<CarrierCode>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(FitError::InvalidCarrierCode(rinex_code.to_string())),
+        }
+    }
+
+    fn check_hardware_channel(&self) -> Result<(), FitError> {
+        match self.hc {
+            Some(hc) if hc > 99 => Err(FitError::InvalidHardwareChannel(hc)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::fmt::Display for FittedData {
+    /// Readable description of this [FittedData], for logging purposes.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} ({}) elev={:.2}° azim={:.2}° refsv={:E}s srsv={:E}s/s refsys={:E}s srsys={:E}s/s dsg={:E}s",
+            self.sv,
+            self.first_t,
+            self.duration,
+            self.elevation_deg,
+            self.azimuth_deg,
+            self.refsv_s,
+            self.srsv_s_s,
+            self.refsys_s,
+            self.srsys_s_s,
+            self.dsg,
+        )?;
+
+        if let Some(msio) = self.msio_s {
+            write!(f, " msio={:E}s", msio)?;
+        }
+
+        if let Some(hc) = self.hc {
+            write!(f, " hc={}", hc)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Converts [FittedData] to a [TrackBuilder], for further customization
+/// and verification. The `IOE` field, [CommonViewClass]
+/// and carrier code remain to be defined.
+impl From<FittedData> for TrackBuilder {
+    fn from(fitted: FittedData) -> Self {
+        let builder = Self::default()
+            .sv(fitted.sv)
+            .epoch(fitted.first_t)
+            .duration(fitted.duration)
+            .elevation(fitted.elevation_deg)
+            .azimuth(fitted.azimuth_deg)
+            .data(fitted.track_data(0))
+            .channel(fitted.hc.unwrap_or(0));
+
+        match fitted.ionospheric_data() {
+            Some(iono) => builder.iono(iono),
+            None => builder,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::buffer::Utf8Buffer;
+    use crate::prelude::{
+        CommonViewClass, Duration, EphemerisData, Epoch, FitError, FittedData, Track,
+        TrackBuildError, TrackBuilder, SV,
+    };
+    use std::io::BufWriter;
+    use std::str::FromStr;
+
+    #[test]
+    fn ephemeris_data_ranges() {
+        let fitted = FittedData::default();
+        let class = CommonViewClass::SingleChannel;
+
+        let track = fitted.to_gps_track(class, 999, "L1C").unwrap();
+        assert_eq!(track.data.ioe, 999);
+        assert!(matches!(
+            fitted.to_gps_track(class, 1000, "L1C"),
+            Err(FitError::InvalidIssueOfEphemeris(1000))
+        ));
+
+        for hour in [0, 23] {
+            let track = fitted.to_beidou_track(class, hour, "B1I").unwrap();
+            assert_eq!(track.data.ioe, hour as u16);
+        }
+        assert!(matches!(
+            fitted.to_beidou_track(class, 24, "B1I"),
+            Err(FitError::InvalidBeidouHour(24))
+        ));
+
+        for quarter in [1, 96] {
+            let track = fitted.to_glonass_track(class, quarter, "C1C").unwrap();
+            assert_eq!(track.data.ioe, quarter as u16);
+        }
+        for quarter in [0, 97] {
+            assert!(matches!(
+                fitted.to_glonass_track(class, quarter, "C1C"),
+                Err(FitError::InvalidGlonassQuarter(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn ephemeris_aware_track() {
+        let mut fitted = FittedData::default();
+        let class = CommonViewClass::SingleChannel;
+        let t = Epoch::from_str("2023-01-01T22:20:00 UTC").unwrap();
+
+        for (sv, frc, ioe) in [("G01", "L1C", 123), ("E01", "E1", 123), ("R01", "C1C", 6)] {
+            fitted.sv = SV::from_str(sv).unwrap();
+            let track = fitted.to_track_with_ephemeris(class, 123, t, frc).unwrap();
+            assert_eq!(track.data.ioe, ioe, "failed for {}", sv);
+        }
+
+        fitted.sv = SV::from_str("C01").unwrap();
+        let t = Epoch::from_str("2023-01-01T22:20:00 BDT").unwrap();
+        let track = fitted
+            .to_track_with_ephemeris(class, 123, t, "B1I")
+            .unwrap();
+        assert_eq!(track.data.ioe, 22);
+
+        fitted.sv = SV::from_str("G01").unwrap();
+        assert!(matches!(
+            fitted.to_track_with_ephemeris(class, 1000, t, "L1C"),
+            Err(FitError::InvalidIssueOfEphemeris(1000))
+        ));
+    }
+
+    #[test]
+    fn track_builder() {
+        let fitted = FittedData {
+            sv: SV::from_str("E05").unwrap(),
+            duration: Duration::from_seconds(780.0),
+            first_t: Epoch::from_str("2023-01-01T00:02:00 UTC").unwrap(),
+            elevation_deg: 45.0,
+            azimuth_deg: 120.0,
+            refsys_s: 1.0E-9,
+            dsg: 1.0E-10,
+            msio_s: Some(2.0E-9),
+            smsi_s_s: Some(1.0E-12),
+            isg: Some(3.0E-10),
+            hc: Some(12),
+            ..Default::default()
+        };
+
+        let track = TrackBuilder::from(fitted)
+            .ephemeris(EphemerisData::Ioe(76))
+            .frc("E1")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            track,
+            fitted
+                .to_gps_track(CommonViewClass::SingleChannel, 76, "E1")
+                .unwrap()
+        );
+
+        // carrier code remains to be defined
+        assert_eq!(
+            TrackBuilder::from(fitted).build(),
+            Err(TrackBuildError::MissingCarrierCode)
+        );
+    }
+
+    #[test]
+    fn hardware_channel_round_trip() {
+        let mut fitted = FittedData::default();
+        let class = CommonViewClass::MultiChannel;
+
+        fitted.hc = Some(100);
+        assert!(matches!(
+            fitted.to_gps_track(class, 1, "L1C"),
+            Err(FitError::InvalidHardwareChannel(100))
+        ));
+
+        fitted.hc = Some(42);
+        fitted.elevation_deg = 45.0;
+        fitted.azimuth_deg = 120.0;
+        fitted.duration = crate::prelude::Duration::from_seconds(780.0);
+
+        let track = fitted.to_gps_track(class, 1, "L1C").unwrap();
+        assert_eq!(track.hc, 42);
+
+        let mut buf = Utf8Buffer::new(1024);
+        let mut user_buf = BufWriter::new(Utf8Buffer::new(1024));
+        track.format(&mut user_buf, &mut buf).unwrap();
+
+        let inner = user_buf.into_inner().unwrap_or_else(|_| panic!("oops"));
+        let ascii_utf8 = inner.to_utf8_ascii().unwrap();
+
+        let parsed = Track::from_str(ascii_utf8).unwrap();
+        assert_eq!(parsed.hc, 42);
+        assert_eq!(parsed.frc, "L1C");
+        assert_eq!(parsed.class, class);
+    }
+
+    #[test]
+    fn csv_and_display() {
+        let t0 = Epoch::from_str("2020-01-01T00:02:00 UTC").unwrap();
+
+        let fitted = FittedData {
+            sv: SV::from_str("G08").unwrap(),
+            duration: Duration::from_seconds(780.0),
+            first_t: t0,
+            midtrack: t0 + Duration::from_seconds(390.0),
+            elevation_deg: 45.5,
+            azimuth_deg: 120.25,
+            refsv_s: 1.0E-6,
+            srsv_s_s: 2.0E-12,
+            refsys_s: -3.0E-9,
+            srsys_s_s: 4.0E-13,
+            dsg: 5.0E-10,
+            mdtr_s: 6.0E-9,
+            smdt_s_s: 7.0E-13,
+            mdio_s: 8.0E-9,
+            smdi_s_s: 9.0E-13,
+            msio_s: None,
+            smsi_s_s: None,
+            isg: None,
+            hc: Some(12),
+        };
+
+        assert_eq!(FittedData::CSV_HEADER.split(',').count(), 19);
+
+        assert_eq!(
+            fitted.to_csv_row(),
+            "G08,2020-01-01T00:02:00 UTC,780,2020-01-01T00:08:30 UTC,45.5,120.25,0.000001,0.000000000002,-0.000000003,0.0000000000004,0.0000000005,0.000000006,0.0000000000007,0.000000008,0.0000000000009,,,,12"
+        );
+
+        let mut dual_freq = fitted;
+        dual_freq.msio_s = Some(1.0E-8);
+        dual_freq.smsi_s_s = Some(-1.0E-12);
+        dual_freq.isg = Some(2.0E-10);
+        dual_freq.hc = None;
+
+        let row = dual_freq.to_csv_row();
+        let fields = row.split(',').collect::<Vec<_>>();
+        assert_eq!(fields.len(), 19);
+        assert_eq!(
+            fields[15..],
+            ["0.00000001", "-0.000000000001", "0.0000000002", ""]
+        );
+
+        let display = fitted.to_string();
+        assert!(
+            display.starts_with("G08 2020-01-01T00:02:00 UTC (13 min) elev=45.50° azim=120.25°")
+        );
+        assert!(display.contains("refsys=-3E-9s"));
+        assert!(display.ends_with("hc=12"));
+    }
 }
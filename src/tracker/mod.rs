@@ -1,8 +1,12 @@
 mod fit;
 mod fitted;
+mod smoothing;
+mod status;
 
 pub use fit::{FitError, Observation, SVTracker};
 pub use fitted::FittedData;
+pub use smoothing::{HatchFilter, MovingAverage, Smoother};
+pub use status::SvTrackingStatus;
 
 use crate::prelude::{Duration, SV};
 
@@ -16,6 +20,8 @@ pub struct SkyTracker {
     trackers: HashMap<SV, SVTracker>,
     /// Gap tolerance
     gap_tolerance: Option<Duration>,
+    /// Possible [Smoother] pre-filter
+    smoother: Option<Box<dyn Smoother>>,
 }
 
 impl SkyTracker {
@@ -24,6 +30,7 @@ impl SkyTracker {
         Self {
             trackers: HashMap::with_capacity(8),
             gap_tolerance: None,
+            smoother: None,
         }
     }
 
@@ -33,24 +40,59 @@ impl SkyTracker {
     }
 
     /// Define a [SkyTracker] with desired observation gap tolerance.
-    pub fn with_gap_tolerance(&self, tolerance: Duration) -> Self {
-        let mut s = self.clone();
+    pub fn with_gap_tolerance(self, tolerance: Duration) -> Self {
+        let mut s = self;
         s.gap_tolerance = Some(tolerance);
         s
     }
 
+    /// Define a [SkyTracker] with desired [Smoother] pre-filter,
+    /// that will apply to every satellite being tracked.
+    pub fn with_smoother<S: Smoother + Clone + 'static>(self, smoother: S) -> Self {
+        let mut s = self;
+        s.smoother = Some(Box::new(smoother));
+        s
+    }
+
     /// Provide new [Observation] for that particular satellite.
     pub fn new_observation(&mut self, satellite: SV, data: Observation) {
-        if let Some(tracker) = self.trackers.get_mut(&satellite) {
-            tracker.new_observation(data);
-        } else {
+        self.sv_tracker(satellite).new_observation(data);
+    }
+
+    /// Provide new [Observation] for that particular satellite, obtained with
+    /// this Issue of Ephemeris. The satellite buffer is reset when
+    /// the Issue of Ephemeris changes, so each fit uses a single ephemeris.
+    pub fn new_observation_with_ioe(&mut self, satellite: SV, data: Observation, ioe: u16) {
+        self.sv_tracker(satellite)
+            .new_observation_with_ioe(data, ioe);
+    }
+
+    /// Returns the [SVTracker] of this satellite, created if needed.
+    fn sv_tracker(&mut self, satellite: SV) -> &mut SVTracker {
+        let (gap_tolerance, smoother) = (self.gap_tolerance, &self.smoother);
+
+        self.trackers.entry(satellite).or_insert_with(|| {
             let mut new = SVTracker::new(satellite);
-            if let Some(tolerance) = self.gap_tolerance {
+            if let Some(tolerance) = gap_tolerance {
                 new = new.with_gap_tolerance(tolerance);
             }
-            new.new_observation(data);
-            self.trackers.insert(satellite, new);
-        }
+            if let Some(smoother) = smoother {
+                new = new.with_boxed_smoother(smoother.clone());
+            }
+            new
+        })
+    }
+
+    /// Reports the [SvTrackingStatus] of every satellite being tracked,
+    /// sorted by [SV]. This includes partial arcs that may not be fitted yet.
+    pub fn status(&self) -> Vec<SvTrackingStatus> {
+        let mut status = self
+            .trackers
+            .values()
+            .map(|tracker| tracker.status())
+            .collect::<Vec<_>>();
+        status.sort_by_key(|status| status.sv);
+        status
     }
 
     /// Attempt new satellite track fitting.
@@ -67,4 +109,218 @@ impl SkyTracker {
             Err(FitError::UnknownSatellite)
         }
     }
+
+    /// Fits every satellite in view, and combines them into a single
+    /// solution for this common view period, which is what laboratories
+    /// publish as the station solution. Satellites that cannot be fitted
+    /// (not enough symbols, or any other [FitError]) are not considered,
+    /// so the fits of the other satellites are never lost. When no satellite
+    /// could be fitted, the first [FitError] other than
+    /// [FitError::NotEnoughSymbols] is returned, if any.
+    /// Refer to [FittedData::weighted_combination] for the combination itself.
+    /// ## Input
+    /// - weight_fn: weight of each satellite, for example
+    ///   derived from the elevation, or the inverse of DSG squared.
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let mut tracker = SkyTracker::new();
+    ///
+    /// // nothing to fit
+    /// assert!(tracker.combined_fit(|fitted| fitted.elevation_deg.to_radians().sin()).is_err());
+    /// ```
+    pub fn combined_fit<F: Fn(&FittedData) -> f64>(
+        &mut self,
+        weight_fn: F,
+    ) -> Result<FittedData, FitError> {
+        let mut satellites = self.trackers.keys().copied().collect::<Vec<_>>();
+        satellites.sort();
+
+        let mut fitted = Vec::with_capacity(satellites.len());
+        let mut error = Option::<FitError>::None;
+
+        for satellite in satellites {
+            if let Some(tracker) = self.trackers.get_mut(&satellite) {
+                match tracker.fit() {
+                    Ok(data) => {
+                        let weight = weight_fn(&data);
+                        fitted.push((data, weight));
+                    },
+                    Err(FitError::NotEnoughSymbols) => {},
+                    Err(e) => {
+                        error.get_or_insert(e);
+                    },
+                }
+            }
+        }
+
+        match error {
+            Some(e) if fitted.is_empty() => Err(e),
+            _ => FittedData::weighted_combination(&fitted),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{Duration, Epoch, FitError, Observation, SkyTracker, SV};
+    use std::str::FromStr;
+
+    #[test]
+    fn sky_tracker_status() {
+        let g01 = SV::from_str("G01").unwrap();
+        let g02 = SV::from_str("G02").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        let mut tracker = SkyTracker::new();
+
+        for i in 0..4 {
+            tracker.new_observation(
+                g01,
+                Observation {
+                    epoch: t0 + i as f64 * dt,
+                    ..Default::default()
+                },
+            );
+        }
+
+        for i in 2..4 {
+            tracker.new_observation(
+                g02,
+                Observation {
+                    epoch: t0 + i as f64 * dt,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let status = tracker.status();
+        assert_eq!(status.len(), 2);
+
+        assert_eq!(status[0].sv, g01);
+        assert_eq!(status[0].samples, 4);
+        assert_eq!(status[0].first, Some(t0));
+        assert_eq!(status[0].last, Some(t0 + 3.0 * dt));
+        assert!(status[0].fit_ready);
+
+        assert_eq!(status[1].sv, g02);
+        assert_eq!(status[1].samples, 2);
+        assert_eq!(status[1].first, Some(t0 + 2.0 * dt));
+        assert_eq!(status[1].last, Some(t0 + 3.0 * dt));
+        assert!(!status[1].fit_ready);
+
+        assert!(tracker.track_fit(g02).is_err());
+    }
+
+    #[test]
+    fn ephemeris_update_reset() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        let mut tracker = SkyTracker::new();
+
+        for i in 0..4 {
+            let data = Observation {
+                epoch: t0 + i as f64 * dt,
+                ..Default::default()
+            };
+            tracker.new_observation_with_ioe(g01, data, 10);
+        }
+
+        let status = tracker.status();
+        assert_eq!(status[0].samples, 4);
+        assert_eq!(status[0].first, Some(t0));
+
+        // new ephemeris
+        for i in 4..6 {
+            let data = Observation {
+                epoch: t0 + i as f64 * dt,
+                ..Default::default()
+            };
+            tracker.new_observation_with_ioe(g01, data, 11);
+        }
+
+        let status = tracker.status();
+        assert_eq!(status[0].samples, 2);
+        assert_eq!(status[0].first, Some(t0 + 4.0 * dt));
+        assert_eq!(status[0].last, Some(t0 + 5.0 * dt));
+        assert!(!status[0].fit_ready);
+    }
+
+    #[test]
+    fn equally_weighted_combination() {
+        let g01 = SV::from_str("G01").unwrap();
+        let g02 = SV::from_str("G02").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        let mut tracker = SkyTracker::new();
+
+        for i in 0..26 {
+            for (sv, refsys, elevation) in [(g01, 1.0E-9, 30.0), (g02, 3.0E-9, 60.0)] {
+                tracker.new_observation(
+                    sv,
+                    Observation {
+                        epoch: t0 + i as f64 * dt,
+                        refsv: 2.0 * refsys,
+                        refsys,
+                        elevation,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        let combined = tracker.combined_fit(|_| 1.0).unwrap();
+
+        assert!((combined.refsys_s - 2.0E-9).abs() < 1.0E-15);
+        assert!((combined.refsv_s - 4.0E-9).abs() < 1.0E-15);
+        assert!(combined.srsys_s_s.abs() < 1.0E-18);
+
+        // dispersion between both satellites
+        assert!((combined.dsg - 1.0E-9).abs() < 1.0E-15);
+
+        assert_eq!(combined.first_t, t0);
+        assert_eq!(combined.duration, 25.0 * dt);
+
+        // buffers were consumed
+        assert!(tracker.combined_fit(|_| 1.0).is_err());
+    }
+
+    #[test]
+    fn combination_skips_failing_satellites() {
+        let g01 = SV::from_str("G01").unwrap();
+        let g02 = SV::from_str("G02").unwrap();
+        let g03 = SV::from_str("G03").unwrap();
+        let t0 = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let dt = Duration::from_seconds(30.0);
+
+        let mut tracker = SkyTracker::new();
+
+        for i in 0..26 {
+            for (sv, refsys, channel) in [(g01, 1.0E-9, 1), (g02, 3.0E-9, 2), (g03, 9.0E-9, 100)] {
+                tracker.new_observation(
+                    sv,
+                    Observation {
+                        epoch: t0 + i as f64 * dt,
+                        refsys,
+                        channel: Some(channel),
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+
+        // G03 cannot be fitted (invalid channel): G01 and G02 are preserved
+        let combined = tracker.combined_fit(|_| 1.0).unwrap();
+        assert!((combined.refsys_s - 2.0E-9).abs() < 1.0E-15);
+
+        // only G03 remains
+        assert!(matches!(
+            tracker.combined_fit(|_| 1.0),
+            Err(FitError::InvalidHardwareChannel(100))
+        ));
+    }
 }
@@ -0,0 +1,156 @@
+//! Code smoothing, applied prior fitting
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+/// [Smoother] is a pre-filter stage that applies to the raw
+/// REFSV and REFSYS series, prior running the fit algorithm.
+/// The smoother is fed one sample at a time, in chronological order.
+pub trait Smoother: Debug + Send + Sync {
+    /// Smooth a new code based sample.
+    /// ## Input
+    /// - code: raw code based sample (s)
+    /// - carrier_delta: possible carrier phase variation (s),
+    ///   since the previous sample.
+    /// ## Output
+    /// - smoothed sample (s)
+    fn smooth(&mut self, code: f64, carrier_delta: Option<f64>) -> f64;
+
+    /// Reset this [Smoother]. This is automatically invoked
+    /// on data gaps and cycle slips.
+    fn reset(&mut self);
+
+    /// Clones this [Smoother] into a new [Box].
+    fn box_clone(&self) -> Box<dyn Smoother>;
+}
+
+impl Clone for Box<dyn Smoother> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// [HatchFilter] is the standard carrier smoothed code filter.
+/// The carrier phase variation is used to propagate the previous
+/// estimate, which is then blended with the new code sample.
+/// When no carrier phase variation is provided, the filter restarts
+/// from the raw code sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HatchFilter {
+    /// Maximal smoothing window (in samples)
+    window: usize,
+    /// Current number of samples
+    n: usize,
+    /// Previous estimate
+    prev: f64,
+}
+
+impl HatchFilter {
+    /// Builds a new [HatchFilter] with desired maximal smoothing window,
+    /// expressed in number of samples.
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            n: 0,
+            prev: 0.0,
+        }
+    }
+}
+
+impl Smoother for HatchFilter {
+    fn smooth(&mut self, code: f64, carrier_delta: Option<f64>) -> f64 {
+        let smoothed = match carrier_delta {
+            Some(delta) if self.n > 0 => {
+                self.n = (self.n + 1).min(self.window);
+                let n = self.n as f64;
+                code / n + (n - 1.0) / n * (self.prev + delta)
+            },
+            _ => {
+                self.n = 1;
+                code
+            },
+        };
+        self.prev = smoothed;
+        smoothed
+    }
+
+    fn reset(&mut self) {
+        self.n = 0;
+        self.prev = 0.0;
+    }
+
+    fn box_clone(&self) -> Box<dyn Smoother> {
+        Box::new(*self)
+    }
+}
+
+/// [MovingAverage] is a simple fallback [Smoother], to be used
+/// when carrier phase is not available.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MovingAverage {
+    /// Averaging window (in samples)
+    window: usize,
+    /// Internal buffer
+    buffer: VecDeque<f64>,
+}
+
+impl MovingAverage {
+    /// Builds a new [MovingAverage] with desired window,
+    /// expressed in number of samples.
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            buffer: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+impl Smoother for MovingAverage {
+    fn smooth(&mut self, code: f64, _: Option<f64>) -> f64 {
+        if self.buffer.len() == self.window {
+            self.buffer.pop_front();
+        }
+        self.buffer.push_back(code);
+        self.buffer.iter().sum::<f64>() / self.buffer.len() as f64
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+    }
+
+    fn box_clone(&self) -> Box<dyn Smoother> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HatchFilter, MovingAverage, Smoother};
+
+    #[test]
+    fn hatch_filter() {
+        let mut hatch = HatchFilter::new(3);
+
+        assert_eq!(hatch.smooth(1.0, None), 1.0);
+        assert_eq!(hatch.smooth(4.0, Some(0.0)), 2.5);
+        assert!((hatch.smooth(3.0, Some(0.5)) - 3.0).abs() < 1.0E-12);
+
+        // no carrier: restarts
+        assert_eq!(hatch.smooth(10.0, None), 10.0);
+
+        hatch.reset();
+        assert_eq!(hatch.smooth(5.0, Some(1.0)), 5.0);
+    }
+
+    #[test]
+    fn moving_average() {
+        let mut avg = MovingAverage::new(2);
+
+        assert_eq!(avg.smooth(1.0, None), 1.0);
+        assert_eq!(avg.smooth(3.0, None), 2.0);
+        assert_eq!(avg.smooth(5.0, Some(1.0)), 4.0);
+
+        avg.reset();
+        assert_eq!(avg.smooth(5.0, None), 5.0);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::prelude::{Epoch, SV};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [SvTrackingStatus] describes the current state of a
+/// satellite tracker, even when it does not permit a fit yet.
+/// This is typically used to log partial arcs at the end
+/// of a common view period.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SvTrackingStatus {
+    /// [SV] being tracked
+    pub sv: SV,
+    /// Number of buffered samples
+    pub samples: usize,
+    /// First buffered [Epoch], if any
+    pub first: Option<Epoch>,
+    /// Last buffered [Epoch], if any
+    pub last: Option<Epoch>,
+    /// True if a fit would currently succeed
+    pub fit_ready: bool,
+}
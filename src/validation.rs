@@ -0,0 +1,593 @@
+//! CGGTTS validation, to verify produced files prior submission.
+use std::collections::BTreeMap;
+
+use crate::prelude::{Code, CommonViewClass, CGGTTS};
+
+#[cfg(feature = "scheduler")]
+use crate::prelude::{CommonViewCalendar, Duration, Epoch};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [WarningKind] classifies each [ValidationWarning]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WarningKind {
+    /// A carrier used by the tracks is not described in the delay section
+    DelayCoverage,
+    /// Frequency dependent delays are declared without CAL_ID
+    MissingCalibrationID,
+    /// CAB DLY or REF DLY was declared without unit
+    MissingDelayUnit,
+    /// Tracks do not use the same constellation
+    MixedConstellation,
+    /// Track duration does not follow BIPM recommendations
+    TrackDuration,
+    /// Track does not start on the BIPM common view schedule
+    ScheduleMisalignment,
+    /// Elevation is out of the [0, 90] degrees range
+    Elevation,
+    /// Azimuth is out of the [0, 360[ degrees range
+    Azimuth,
+    /// Carrier (FRC) is not legal for the tracked constellation
+    InvalidCarrier,
+    /// Multi channel tracks while the number of channels is not declared
+    ChannelNumber,
+}
+
+impl WarningKind {
+    /// Returns the [Severity] of this [WarningKind]. Only [Severity::Error]
+    /// prevents the submission of a file.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::MissingCalibrationID => Severity::Info,
+            Self::ScheduleMisalignment | Self::MissingDelayUnit | Self::ChannelNumber => {
+                Severity::Warning
+            },
+            Self::DelayCoverage
+            | Self::MixedConstellation
+            | Self::TrackDuration
+            | Self::Elevation
+            | Self::Azimuth
+            | Self::InvalidCarrier => Severity::Error,
+        }
+    }
+
+    /// Returns the identifier of the CGGTTS 2E rule this [WarningKind]
+    /// refers to: paragraph number and short name.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            Self::DelayCoverage | Self::MissingCalibrationID | Self::MissingDelayUnit => {
+                "3.4 delays"
+            },
+            Self::MixedConstellation => "2.2 constellation",
+            Self::TrackDuration => "4.2 track length",
+            Self::ScheduleMisalignment => "4.1 tracking schedule",
+            Self::Elevation | Self::Azimuth => "4.3 satellite position",
+            Self::InvalidCarrier => "4.4 carrier code",
+            Self::ChannelNumber => "3.1 receiver channels",
+        }
+    }
+}
+
+/// [Severity] of a [ValidationIssue]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Severity {
+    /// Informative: does not affect the exploitation of the file
+    Info,
+    /// Deviation from recommendations, that is tolerated
+    Warning,
+    /// Standard violation, that prevents submission
+    Error,
+}
+
+/// [ValidationWarning] describes a standard conformance issue.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidationWarning {
+    /// [WarningKind]
+    pub kind: WarningKind,
+    /// Readable description of the issue
+    pub message: String,
+    /// Index of the offending [Track](crate::prelude::Track), if any
+    pub track_index: Option<usize>,
+}
+
+/// [ValidationIssue] is a [ValidationWarning] classified
+/// by [Severity] and rule, as found in a [ValidationReport].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidationIssue {
+    /// [Severity] of this issue
+    pub severity: Severity,
+    /// Identifier of the CGGTTS 2E rule, for example "4.2 track length"
+    pub rule: String,
+    /// [WarningKind]
+    pub kind: WarningKind,
+    /// Readable description of the issue
+    pub message: String,
+    /// Index of the offending [Track](crate::prelude::Track), if any
+    pub track_index: Option<usize>,
+}
+
+impl From<ValidationWarning> for ValidationIssue {
+    fn from(warning: ValidationWarning) -> Self {
+        Self {
+            severity: warning.kind.severity(),
+            rule: warning.kind.rule().to_string(),
+            kind: warning.kind,
+            message: warning.message,
+            track_index: warning.track_index,
+        }
+    }
+}
+
+/// [ValidationReport] is the machine readable verdict of
+/// [CGGTTS::validation_report], that may be serialized (for example in JSON).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ValidationReport {
+    /// All [ValidationIssue]s that were identified
+    pub issues: Vec<ValidationIssue>,
+    /// Station (APC) geodetic position, as (latitude, longitude) in degrees,
+    /// on the WGS84 ellipsoid. None when the coordinates are unknown.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub station_position: Option<(f64, f64)>,
+}
+
+impl ValidationReport {
+    /// Returns the [ValidationIssue]s of this [Severity]
+    pub fn issues_of(&self, severity: Severity) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.severity == severity)
+    }
+
+    /// Returns the number of [Severity::Error]s
+    pub fn nb_errors(&self) -> usize {
+        self.issues_of(Severity::Error).count()
+    }
+
+    /// Returns the number of [Severity::Warning]s
+    pub fn nb_warnings(&self) -> usize {
+        self.issues_of(Severity::Warning).count()
+    }
+
+    /// Returns true if this report does not contain any [Severity::Error].
+    pub fn is_submission_ready(&self) -> bool {
+        self.nb_errors() == 0
+    }
+}
+
+/// [DelayCoverageReport] describes whether each carrier (FRC)
+/// used by the tracks is described in the delay section of the header.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DelayCoverageReport {
+    /// Each distinct carrier frequency code (FRC) found in the tracks,
+    /// and whether a matching [Code] delay is declared.
+    pub carriers: BTreeMap<String, bool>,
+}
+
+impl DelayCoverageReport {
+    /// Returns the carrier frequency codes (FRC) that are not covered
+    /// by any delay definition.
+    pub fn uncovered(&self) -> Vec<&str> {
+        self.carriers
+            .iter()
+            .filter_map(|(frc, covered)| if *covered { None } else { Some(frc.as_str()) })
+            .collect()
+    }
+
+    /// Returns true if all carriers are covered by a delay definition.
+    pub fn is_complete(&self) -> bool {
+        self.carriers.values().all(|covered| *covered)
+    }
+}
+
+impl CGGTTS {
+    /// Cross-checks the frequency dependent delays declared in the [Header](crate::prelude::Header),
+    /// against the carriers (FRC) actually used by the tracks.
+    pub fn delay_coverage(&self) -> DelayCoverageReport {
+        let mut report = DelayCoverageReport::default();
+
+        for trk in self.tracks.iter() {
+            if report.carriers.contains_key(trk.frc.as_str()) {
+                continue;
+            }
+
+            let covered = match Code::from_frc(&trk.frc) {
+                Some(code) => self
+                    .header
+                    .delay
+                    .freq_dependent_delays
+                    .iter()
+                    .any(|(k, _)| *k == code),
+                None => false,
+            };
+
+            report.carriers.insert(trk.frc.to_string(), covered);
+        }
+
+        report
+    }
+
+    /// Verifies this [CGGTTS] against the standard specifications,
+    /// and returns all [ValidationWarning]s. An empty list means
+    /// no issue was found. This verifies:
+    /// - that each carrier is described in the delay section
+    /// - that delays come with a CAL_ID
+    /// - that cable delays come with their unit
+    /// - that a single constellation is used
+    /// - the BIPM track duration
+    /// - the BIPM common view schedule (requires the `scheduler` feature)
+    /// - elevation and azimuth ranges
+    /// - that each carrier is legal for the tracked constellation
+    pub fn validate(&self) -> Vec<ValidationWarning> {
+        let mut warnings = Vec::new();
+
+        for frc in self.delay_coverage().uncovered() {
+            warnings.push(ValidationWarning {
+                kind: WarningKind::DelayCoverage,
+                message: format!("no delay declared for carrier \"{}\"", frc),
+                track_index: None,
+            });
+        }
+
+        if !self.header.delay.freq_dependent_delays.is_empty()
+            && self.header.delay.calibration_id.is_none()
+        {
+            warnings.push(ValidationWarning {
+                kind: WarningKind::MissingCalibrationID,
+                message: "delays declared without CAL_ID".to_string(),
+                track_index: None,
+            });
+        }
+
+        if self.header.delay.missing_unit {
+            warnings.push(ValidationWarning {
+                kind: WarningKind::MissingDelayUnit,
+                message: "cable delays declared without unit".to_string(),
+                track_index: None,
+            });
+        }
+
+        if self.header.nb_channels == 0 {
+            if let Some(index) = self
+                .tracks
+                .iter()
+                .position(|trk| trk.class == CommonViewClass::MultiChannel)
+            {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::ChannelNumber,
+                    message: "multi channel tracks while CH = 0".to_string(),
+                    track_index: Some(index),
+                });
+            }
+        }
+
+        if let Some(first) = self.tracks.first() {
+            let constellation = first.sv.constellation;
+            if let Some(index) = self
+                .tracks
+                .iter()
+                .position(|trk| trk.sv.constellation != constellation)
+            {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::MixedConstellation,
+                    message: format!(
+                        "{} track while file started with {}",
+                        self.tracks[index].sv.constellation, constellation
+                    ),
+                    track_index: Some(index),
+                });
+            }
+        }
+
+        #[cfg(feature = "scheduler")]
+        let calendar = CommonViewCalendar::bipm();
+
+        for (index, trk) in self.tracks.iter().enumerate() {
+            if !trk.follows_bipm_tracking() {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::TrackDuration,
+                    message: format!("non BIPM track duration: {}", trk.duration),
+                    track_index: Some(index),
+                });
+            }
+
+            #[cfg(feature = "scheduler")]
+            if !follows_calendar(&calendar, trk.epoch) {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::ScheduleMisalignment,
+                    message: format!("{} is not aligned to BIPM schedule", trk.epoch),
+                    track_index: Some(index),
+                });
+            }
+
+            if !(0.0..=90.0).contains(&trk.elevation_deg) {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::Elevation,
+                    message: format!("invalid elevation: {} deg", trk.elevation_deg),
+                    track_index: Some(index),
+                });
+            }
+
+            if !(0.0..360.0).contains(&trk.azimuth_deg) {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::Azimuth,
+                    message: format!("invalid azimuth: {} deg", trk.azimuth_deg),
+                    track_index: Some(index),
+                });
+            }
+
+            if !trk.is_frc_valid() {
+                warnings.push(ValidationWarning {
+                    kind: WarningKind::InvalidCarrier,
+                    message: format!("\"{}\" is not a {} carrier", trk.frc, trk.sv.constellation),
+                    track_index: Some(index),
+                });
+            }
+        }
+
+        warnings
+    }
+
+    /// Verifies this [CGGTTS] like [CGGTTS::validate], and classifies
+    /// each issue by [Severity] and rule, in a [ValidationReport]:
+    /// ```
+    /// use cggtts::prelude::*;
+    ///
+    /// let cggtts = CGGTTS::default();
+    ///
+    /// let report = cggtts.validation_report();
+    /// assert!(report.is_submission_ready());
+    /// ```
+    pub fn validation_report(&self) -> ValidationReport {
+        ValidationReport {
+            issues: self.validate().into_iter().map(Into::into).collect(),
+            station_position: self
+                .header
+                .apc_coordinates
+                .to_geodetic()
+                .map(|(lat_deg, lon_deg, _)| (lat_deg, lon_deg)),
+        }
+    }
+
+    /// Returns true if this [CGGTTS] does not violate any rule,
+    /// refer to [CGGTTS::validation_report].
+    pub fn is_submission_ready(&self) -> bool {
+        self.validation_report().is_submission_ready()
+    }
+}
+
+/// Returns true if this [Epoch] is a period start (or data collection start)
+/// of this [CommonViewCalendar].
+#[cfg(feature = "scheduler")]
+fn follows_calendar(calendar: &CommonViewCalendar, t: Epoch) -> bool {
+    let period_start = calendar.next_period_start_after(t - Duration::from_seconds(1.0));
+    let collection_start = calendar.next_data_collection_after(t - Duration::from_seconds(1.0));
+    t == period_start || t == collection_start
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Severity, WarningKind};
+    use crate::prelude::{
+        CalibrationID, Code, Coordinates, Delay, Duration, Epoch, Track, CGGTTS, SV,
+    };
+    use std::io::BufReader;
+    use std::str::FromStr;
+
+    fn cggtts(frcs: &[&str], codes: &[Code]) -> CGGTTS {
+        let mut cggtts = CGGTTS::default();
+
+        for code in codes {
+            cggtts
+                .header
+                .delay
+                .freq_dependent_delays
+                .push((*code, Delay::Internal(10.0)));
+        }
+
+        for frc in frcs {
+            let mut track = Track::default()
+                .with_sv(SV::from_str("G01").unwrap())
+                .with_carrier_code(frc)
+                .with_elevation_deg(45.0)
+                .with_azimuth_deg(120.0);
+
+            track.duration = Duration::from_seconds(780.0);
+            cggtts.tracks.push(track);
+        }
+
+        cggtts.header.delay = cggtts
+            .header
+            .delay
+            .with_calibration_id(CalibrationID::from_str("1015-2021").unwrap());
+
+        cggtts
+    }
+
+    #[test]
+    fn matching_delay_coverage() {
+        let cggtts = cggtts(&["L1C", "L1C", "L2P"], &[Code::C1, Code::P2]);
+
+        let report = cggtts.delay_coverage();
+        assert_eq!(report.carriers.len(), 2);
+        assert!(report.is_complete());
+        assert!(report.uncovered().is_empty());
+
+        assert!(cggtts
+            .validate()
+            .iter()
+            .all(|w| w.kind == WarningKind::ScheduleMisalignment));
+    }
+
+    #[test]
+    fn mismatching_delay_coverage() {
+        let cggtts = cggtts(&["L1C", "E1", "L3P"], &[Code::C1]);
+
+        let report = cggtts.delay_coverage();
+        assert_eq!(report.carriers.len(), 3);
+        assert!(!report.is_complete());
+        assert_eq!(report.uncovered(), vec!["E1", "L3P"]);
+
+        let warnings = cggtts
+            .validate()
+            .into_iter()
+            .filter(|w| w.kind == WarningKind::DelayCoverage)
+            .collect::<Vec<_>>();
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].message.contains("E1"));
+        assert!(warnings[1].message.contains("L3P"));
+    }
+
+    #[test]
+    fn bad_file_validation() {
+        let mut cggtts = cggtts(&["L1C", "L1C", "L1C", "L1C"], &[Code::C1]);
+        cggtts.header.delay.calibration_id = None;
+
+        // aligned to BIPM schedule
+        let t0 = Epoch::from_mjd_utc(50_722.0) + Duration::from_seconds(120.0);
+        let dt = Duration::from_seconds(960.0);
+
+        for (index, trk) in cggtts.tracks.iter_mut().enumerate() {
+            trk.epoch = t0 + index as f64 * dt;
+            trk.sv = SV::from_str("G01").unwrap();
+        }
+
+        cggtts.tracks[1].duration = Duration::from_seconds(600.0);
+        cggtts.tracks[2].elevation_deg = 91.0;
+        cggtts.tracks[2].azimuth_deg = 360.0;
+        cggtts.tracks[3].sv = SV::from_str("E01").unwrap();
+
+        let warnings = cggtts.validate();
+
+        let kinds = warnings
+            .iter()
+            .filter(|w| w.kind != WarningKind::ScheduleMisalignment)
+            .map(|w| (w.kind, w.track_index))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (WarningKind::MissingCalibrationID, None),
+                (WarningKind::MixedConstellation, Some(3)),
+                (WarningKind::TrackDuration, Some(1)),
+                (WarningKind::Elevation, Some(2)),
+                (WarningKind::Azimuth, Some(2)),
+                (WarningKind::InvalidCarrier, Some(3)),
+            ]
+        );
+
+        #[cfg(feature = "scheduler")]
+        {
+            assert!(warnings
+                .iter()
+                .all(|w| w.kind != WarningKind::ScheduleMisalignment));
+
+            cggtts.tracks[0].epoch += Duration::from_seconds(30.0);
+
+            let warnings = cggtts
+                .validate()
+                .into_iter()
+                .filter(|w| w.kind == WarningKind::ScheduleMisalignment)
+                .collect::<Vec<_>>();
+
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].track_index, Some(0));
+        }
+    }
+
+    #[test]
+    fn validation_report() {
+        let content = "CGGTTS     GENERIC DATA FORMAT VERSION = 2E
+REV DATE = 2023-06-27
+RCVR = GTR51 2204005 1.12.0 2022 2.0
+CH = 20
+IMS = GTR51 2204005 1.12.0 2022 2.0
+LAB = LAB
+X = +3970727.80 m
+Y = +1018888.02 m
+Z = +4870276.84 m
+FRAME = ITRF
+COMMENTS = NO COMMENTS
+INT DLY =   34.6 ns (GAL E1),   0.0 ns (GAL E5)     CAL_ID = 1015-2021
+CAB DLY =  155.2 ns
+REF DLY =    0.0 ns
+REF = UTC(LAB)
+CKSUM = 37
+
+SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK
+             hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s.1ns.1ps/s.1ns
+E03 FF 60258 001000  780 139  548     +723788    +14        -302    -14    2 076  325  -36   32   -3   20  +20   3  0  0  E1 00
+E11 FF 60258 001000  780 278 2245     -283839    +15        -356    +15    2 076  256  -29   24   -3   15   +7   3  0  0  E1 00
+";
+
+        let mut reader = BufReader::new(content.as_bytes());
+        let compliant = CGGTTS::parse(&mut reader).unwrap();
+        assert_eq!(compliant.tracks.len(), 2);
+
+        let report = compliant.validation_report();
+        assert_eq!(report.nb_errors(), 0);
+        assert!(report.is_submission_ready());
+        assert!(compliant.is_submission_ready());
+
+        // deficient file
+        let mut cggtts = cggtts(&["L1C", "L1C", "L2P"], &[Code::C1]);
+        cggtts.header.delay.calibration_id = None;
+        cggtts.tracks[1].duration = Duration::from_seconds(600.0);
+
+        let report = cggtts.validation_report();
+        assert!(!report.is_submission_ready());
+        assert!(!cggtts.is_submission_ready());
+        assert_eq!(report.nb_errors(), 2);
+
+        let errors = report
+            .issues_of(Severity::Error)
+            .map(|issue| (issue.rule.as_str(), issue.track_index))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![("3.4 delays", None), ("4.2 track length", Some(1))]
+        );
+
+        let infos = report.issues_of(Severity::Info).collect::<Vec<_>>();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].kind, WarningKind::MissingCalibrationID);
+        assert_eq!(infos[0].rule, "3.4 delays");
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&report).unwrap();
+            assert!(json.contains(r#""severity":"Error","rule":"4.2 track length""#));
+            assert_eq!(
+                serde_json::from_str::<super::ValidationReport>(&json).unwrap(),
+                report
+            );
+        }
+    }
+
+    #[test]
+    fn station_position() {
+        let mut cggtts = cggtts(&["L1C"], &[Code::C1]);
+
+        // unknown coordinates
+        assert_eq!(cggtts.validation_report().station_position, None);
+
+        cggtts.header.apc_coordinates = Coordinates {
+            x: 3_970_727.80,
+            y: 1_018_888.02,
+            z: 4_870_276.84,
+        };
+
+        let (lat_deg, lon_deg) = cggtts.validation_report().station_position.unwrap();
+        assert!((lat_deg - 50.102).abs() < 1.0E-3, "lat={}", lat_deg);
+        assert!((lon_deg - 14.392).abs() < 1.0E-3, "lon={}", lon_deg);
+    }
+}